@@ -0,0 +1,78 @@
+#ifndef FASTCWT_H
+#define FASTCWT_H
+
+/* Generated from src/ffi.rs by cbindgen. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Scale types of FastCwtScales.
+typedef enum FastCwtScaleType {
+  // Linear scale.
+  FAST_CWT_SCALE_TYPE_LINEAR,
+  // Logarithmic scale.
+  FAST_CWT_SCALE_TYPE_LOG,
+  // Linear for frequency.
+  FAST_CWT_SCALE_TYPE_LIN_FREQ,
+  // Mel scale.
+  FAST_CWT_SCALE_TYPE_MEL,
+  // ERB-rate scale.
+  FAST_CWT_SCALE_TYPE_ERB,
+} FastCwtScaleType;
+
+// Actual continuous wavelet transform.
+typedef struct FastCWT FastCWT;
+
+// Scales of fastcwt_cwt(), which are the arguments of Scales::create().
+typedef struct FastCwtScales {
+  // Scale type.
+  enum FastCwtScaleType scale_type;
+  // Sample rate.
+  double fs;
+  // Beginning frequency.
+  double f0;
+  // End frequency.
+  double f1;
+  // Number of scales.
+  size_t num_scales;
+} FastCwtScales;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates a transform with a Morlet wavelet, which must be released with fastcwt_free().
+//
+// # Arguments
+// bandwidth - Bandwidth of the Morlet wavelet
+//
+// normalize - Normalize the coefficients by the FFT size if true
+struct FastCWT *fastcwt_create(double bandwidth, bool normalize);
+
+// Transforms num samples and writes num_scales × num complex coefficients, scale by scale, as interleaved real and imaginary parts.
+// (The layout of std::complex<double>.)
+//
+// Returns 0 on success, or -1 if a pointer is null or the transform panicked.
+//
+// # Safety
+// transform must come from fastcwt_create(), input must hold num values, and output must hold 2 × num_scales × num values.
+int32_t fastcwt_cwt(struct FastCWT *transform,
+                    const double *input,
+                    size_t num,
+                    struct FastCwtScales scales,
+                    double *output);
+
+// Releases a transform from fastcwt_create(). Null is ignored.
+//
+// # Safety
+// transform must come from fastcwt_create() and must not be used afterwards.
+void fastcwt_free(struct FastCWT *transform);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* FASTCWT_H */
@@ -0,0 +1,81 @@
+//! Apache Arrow tables of results.
+use crate::*;
+use alloc::sync::Arc;
+use arrow_array::{ArrayRef, FixedSizeListArray, Float64Array, RecordBatch};
+use arrow_buffer::{Buffer, ScalarBuffer};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use std::collections::HashMap;
+
+impl CwtResult
+{
+    /// Arrow table of the result, with a time column in seconds and a column per scale named by its frequency in Hz.
+    ///
+    /// The columns of the scales are Float64, or FixedSizeList of [re, im] if the output mode is Complex.
+    /// Each of them has its frequency in the field metadata, and the schema metadata has fs, hop, bandwidth and mode.
+    pub fn to_arrow(& self) -> Result<RecordBatch, ArrowError>
+    {
+        let values = match self.complex_coefficients()
+        {
+            Some(coefficients) => coefficients.iter().flat_map(|value| [value.re, value.im]).collect(),
+            None => self.real_coefficients().unwrap_or_default().to_vec()
+        };
+        table(self.arrow_schema(), self.times(), Buffer::from_vec(values), self.num_scales(), self.num_samples())
+    }
+    /// Same as to_arrow(), but moves the coefficients into the table, which only copies them if the output mode is Complex.
+    pub fn into_arrow(self) -> Result<RecordBatch, ArrowError>
+    {
+        let (schema, times, num_scales, num_samples) = (self.arrow_schema(), self.times(), self.num_scales(), self.num_samples());
+        let values = match self.mode()
+        {
+            OutputMode::Complex => self.into_complex().unwrap_or_default().iter().flat_map(|value| [value.re, value.im]).collect(),
+            _ => self.into_real().unwrap_or_default()
+        };
+        table(schema, times, Buffer::from_vec(values), num_scales, num_samples)
+    }
+    /// Writes to_arrow() into an Arrow IPC file, which is also a Feather file. (pandas.read_feather(), polars.read_ipc())
+    pub fn save_arrow(& self, path : impl AsRef<std::path::Path>) -> Result<(), ArrowError>
+    {
+        let table = self.to_arrow()?;
+        let mut writer = arrow_ipc::writer::FileWriter::try_new(std::io::BufWriter::new(std::fs::File::create(path)?), &table.schema())?;
+        writer.write(&table)?;
+        writer.finish()
+    }
+    fn arrow_schema(& self) -> Schema
+    {
+        let data_type = match self.mode()
+        {
+            OutputMode::Complex => DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float64, false)), 2),
+            _ => DataType::Float64
+        };
+        let fields = core::iter::once(Field::new("time", DataType::Float64, false)).chain(self.frequencies().into_iter().map(|frequency|
+        {
+            Field::new(frequency.to_string(), data_type.clone(), false).with_metadata(HashMap::from([("frequency".to_string(), frequency.to_string())]))
+        }));
+        let metadata = HashMap::from(
+        [
+            ("fs".to_string(), self.fs().to_string()),
+            ("hop".to_string(), self.hop().to_string()),
+            ("bandwidth".to_string(), self.wavelet().bandwidth().to_string()),
+            ("mode".to_string(), self.mode().name().to_string())
+        ]);
+        Schema::new_with_metadata(fields.collect::<Vec<_>>(), metadata)
+    }
+}
+
+/// Table of the schema, where every column of a scale is a slice of the values without a copy.
+fn table(schema : Schema, times : Vec<f64>, values : Buffer, num_scales : usize, num_samples : usize) -> Result<RecordBatch, ArrowError>
+{
+    //Complex values are interleaved, so a row is twice as long.
+    let width = if matches!(schema.fields().last().map(|field| field.data_type()), Some(DataType::FixedSizeList(_, _))) { 2 } else { 1 };
+    let mut columns : Vec<ArrayRef> = vec![Arc::new(Float64Array::from(times))];
+    for row in 0 .. num_scales
+    {
+        let row = Float64Array::new(ScalarBuffer::new(values.clone(), row * num_samples * width, num_samples * width), None);
+        columns.push(match width
+        {
+            2 => Arc::new(FixedSizeListArray::try_new(Arc::new(Field::new("item", DataType::Float64, false)), 2, Arc::new(row), None)?),
+            _ => Arc::new(row)
+        });
+    }
+    RecordBatch::try_new(Arc::new(schema), columns)
+}
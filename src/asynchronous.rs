@@ -0,0 +1,135 @@
+//! Transforms for async code, which run on their own thread and wake the task when results are ready.
+//!
+//! They do not depend on any executor, so they work the same on tokio, async-std, smol or a plain block_on().
+use crate::*;
+use core::{future::Future, pin::Pin, task::{Context, Poll, Waker}};
+use std::sync::{mpsc, Arc, Mutex, PoisonError};
+
+type Slot = Arc<Mutex<Option<Waker>>>;
+
+impl FastCWT
+{
+    /// Same as cwt(), but runs on its own thread and returns a future, so the async runtime is never blocked.
+    ///
+    /// # Arguments
+    /// input     - Input data in vector format
+    ///
+    /// scales    - Scales object
+    ///
+    /// The future resolves to the transform, so that it can be reused, and the result.
+    pub fn cwt_async(mut self, input : Vec<f64>, scales : Scales) -> CwtFuture
+    {
+        let (sender, receiver) = channel(1);
+        std::thread::spawn(move ||
+        {
+            let result = self.cwt(input.len(), &input, &scales);
+            sender.send((self, result));
+        });
+        CwtFuture { receiver }
+    }
+    /// Runs the transform on its own thread and returns a stream of the complex coefficients of every scale as soon as they are computed.
+    ///
+    /// # Arguments
+    /// input     - Input data in vector format
+    ///
+    /// scales    - Scales object
+    ///
+    /// Each item is the index of the scale and its coefficients for every input sample, regardless of the output mode and hop.
+    /// Rows can arrive out of order when the transform is parallel, and the transform waits while a few rows are not consumed.
+    pub fn cwt_stream(mut self, input : Vec<f64>, scales : Scales) -> RowStream
+    {
+        let (sender, receiver) = channel(self.threads());
+        std::thread::spawn(move ||
+        {
+            self.cwt_for_each_scale(&input, &scales, |index, row| sender.send((index, row.to_vec())));
+        });
+        RowStream { receiver }
+    }
+}
+
+/// Future of FastCWT::cwt_async().
+pub struct CwtFuture
+{
+    receiver : Receiver<(FastCWT, CwtResult)>
+}
+impl Future for CwtFuture
+{
+    type Output = (FastCWT, CwtResult);
+    fn poll(self : Pin<&mut Self>, context : &mut Context<'_>) -> Poll<Self::Output>
+    {
+        match self.receiver.poll(context)
+        {
+            Poll::Ready(Some(output)) => Poll::Ready(output),
+            Poll::Ready(None) => panic!("Transform thread panicked."),
+            Poll::Pending => Poll::Pending
+        }
+    }
+}
+
+/// Stream of FastCWT::cwt_stream(), which yields the index of a scale and its complex coefficients.
+pub struct RowStream
+{
+    receiver : Receiver<(usize, Vec<Complex<f64>>)>
+}
+impl futures_core::Stream for RowStream
+{
+    type Item = (usize, Vec<Complex<f64>>);
+    fn poll_next(self : Pin<&mut Self>, context : &mut Context<'_>) -> Poll<Option<Self::Item>> { self.receiver.poll(context) }
+}
+
+fn channel<T>(capacity : usize) -> (Sender<T>, Receiver<T>)
+{
+    let (sender, receiver) = mpsc::sync_channel(capacity);
+    let waker = Slot::default();
+    (Sender { sender : Some(sender), waker : waker.clone() }, Receiver { receiver, waker })
+}
+
+/// Sending half, which wakes the task after every value and when it is dropped, also by a panic.
+struct Sender<T>
+{
+    sender : Option<mpsc::SyncSender<T>>,
+    waker : Slot
+}
+impl<T> Sender<T>
+{
+    fn send(& self, value : T)
+    {
+        //The receiver is gone if the future or stream was dropped, then the values are discarded.
+        if self.sender.as_ref().is_some_and(|sender| sender.send(value).is_ok()) { wake(&self.waker); }
+    }
+}
+impl<T> Drop for Sender<T>
+{
+    fn drop(&mut self)
+    {
+        //The channel is closed before waking, so the task sees the end.
+        self.sender = None;
+        wake(&self.waker);
+    }
+}
+
+struct Receiver<T>
+{
+    receiver : mpsc::Receiver<T>,
+    waker : Slot
+}
+impl<T> Receiver<T>
+{
+    fn poll(& self, context : &mut Context<'_>) -> Poll<Option<T>>
+    {
+        //The waker is stored before checking, so a value sent in between still wakes the task.
+        *self.waker.lock().unwrap_or_else(PoisonError::into_inner) = Some(context.waker().clone());
+        match self.receiver.try_recv()
+        {
+            Ok(value) => Poll::Ready(Some(value)),
+            Err(mpsc::TryRecvError::Empty) => Poll::Pending,
+            Err(mpsc::TryRecvError::Disconnected) => Poll::Ready(None)
+        }
+    }
+}
+
+fn wake(slot : &Slot)
+{
+    let waker = slot.lock().unwrap_or_else(PoisonError::into_inner).take();
+    if let Some(waker) = waker { waker.wake(); }
+}
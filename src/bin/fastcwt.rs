@@ -0,0 +1,194 @@
+//! Computes the scalogram of a WAV or CSV file.
+//!
+//! fastcwt input.wav -o scalogram.png --f0 20 --f1 20000 --scales 300 --scale-type log
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, ValueEnum};
+use fastcwt::*;
+
+#[derive(Clone, Copy, PartialEq, Debug, ValueEnum)]
+enum ScaleType
+{
+    Linear,
+    Log,
+    Linfreq,
+    Mel,
+    Erb
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, ValueEnum)]
+enum Mode
+{
+    Magnitude,
+    Power,
+    Logpower
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, ValueEnum)]
+enum Format
+{
+    Npy,
+    Csv,
+    Png
+}
+
+/// Computes the continuous wavelet transform of a WAV or CSV file and writes the scalogram to NPY, CSV or PNG.
+#[derive(Parser, Debug)]
+#[command(name = "fastcwt", version)]
+struct Args
+{
+    /// Input file. (.wav, or .csv with one sample per row)
+    input : PathBuf,
+    /// Output file. The format follows the extension unless --format is given.
+    #[arg(short, long)]
+    output : PathBuf,
+    /// Output format.
+    #[arg(long, value_enum)]
+    format : Option<Format>,
+    /// Sample rate, which is required for CSV input. For WAV input, the rate of the file is used.
+    #[arg(long)]
+    fs : Option<f64>,
+    /// Channel of WAV input, or column of CSV input, starting at 0.
+    #[arg(long, default_value_t = 0)]
+    channel : usize,
+    /// Beginning frequency.
+    #[arg(long, default_value_t = 20.0)]
+    f0 : f64,
+    /// End frequency. Defaults to the Nyquist frequency.
+    #[arg(long)]
+    f1 : Option<f64>,
+    /// Number of scales.
+    #[arg(short = 'n', long, default_value_t = 200)]
+    scales : usize,
+    /// Distribution of the scales.
+    #[arg(long, value_enum, default_value_t = ScaleType::Log)]
+    scale_type : ScaleType,
+    /// Bandwidth of the Morlet wavelet.
+    #[arg(short, long, default_value_t = 1.0)]
+    bandwidth : f64,
+    /// Values of the scalogram.
+    #[arg(long, value_enum, default_value_t = Mode::Magnitude)]
+    mode : Mode,
+    /// Number of input samples per output sample, keeping the maximum of each hop.
+    #[arg(long, default_value_t = 1)]
+    hop : usize,
+    /// Dynamic range of PNG output in decibels.
+    #[arg(long, default_value_t = 80.0)]
+    range : f64
+}
+
+fn main()
+{
+    let args = Args::parse();
+    if let Err(error) = run(&args)
+    {
+        eprintln!("fastcwt: {}", error);
+        std::process::exit(1);
+    }
+}
+
+fn run(args : &Args) -> Result<(), String>
+{
+    let extension = |path : &Path| path.extension().and_then(|extension| extension.to_str()).unwrap_or("").to_ascii_lowercase();
+
+    let (input, fs) = match extension(&args.input).as_str()
+    {
+        "wav" =>
+        {
+            let (channels, fs) = io::read_wav(&args.input).map_err(|error| format!("{}: {}", args.input.display(), error))?;
+            let channel = channels.into_iter().nth(args.channel).ok_or_else(|| format!("The file has no channel {}.", args.channel))?;
+            (channel, args.fs.unwrap_or(fs))
+        }
+        "csv" => (read_csv(&args.input, args.channel)?, args.fs.ok_or("CSV input needs the sample rate. (--fs)")?),
+        _ => return Err("The input must be a .wav or .csv file.".to_string())
+    };
+    if input.is_empty() { return Err("The input has no samples.".to_string()); }
+
+    let format = match args.format
+    {
+        Some(format) => format,
+        None => match extension(&args.output).as_str()
+        {
+            "npy" => Format::Npy,
+            "csv" => Format::Csv,
+            "png" => Format::Png,
+            _ => return Err("Set the output format with --format, or use a .npy, .csv or .png output file.".to_string())
+        }
+    };
+
+    let scale_type = match args.scale_type
+    {
+        ScaleType::Linear => ScaleTypes::Linear,
+        ScaleType::Log => ScaleTypes::Log,
+        ScaleType::Linfreq => ScaleTypes::LinFreq,
+        ScaleType::Mel => ScaleTypes::Mel,
+        ScaleType::Erb => ScaleTypes::ERB
+    };
+    let mode = match args.mode
+    {
+        Mode::Magnitude => OutputMode::Magnitude,
+        Mode::Power => OutputMode::Power,
+        Mode::Logpower => OutputMode::LogPower
+    };
+    let scales = Scales::try_create(scale_type, fs, args.f0, args.f1.unwrap_or(fs / 2.0), args.scales).map_err(|error| error.to_string())?;
+    let mut transform = FastCWT::builder(Wavelet::create(args.bandwidth)).output_mode(mode).hop(args.hop, Pooling::Max).build().map_err(|error| error.to_string())?;
+
+    let result = transform.cwt(input.len(), &input, &scales);
+    let values = result.real_coefficients().unwrap_or_default();
+    let (rows, columns) = (result.num_scales(), result.num_samples());
+
+    match format
+    {
+        Format::Npy => result.save_npy(&args.output),
+        Format::Csv => write_csv(&args.output, values, &scales.frequencies(), columns),
+        Format::Png => write_png(&args.output, &result.magnitude(), rows, columns, args.range)
+    }.map_err(|error| format!("{}: {}", args.output.display(), error))
+}
+
+/// Reads a column of a CSV file. Rows which are not numbers, like a header, are skipped.
+fn read_csv(path : &Path, column : usize) -> Result<Vec<f64>, String>
+{
+    let file = std::fs::File::open(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+    let mut samples = vec![];
+    for line in std::io::BufReader::new(file).lines()
+    {
+        let line = line.map_err(|error| format!("{}: {}", path.display(), error))?;
+        if let Some(Ok(sample)) = line.split([',', ';', '\t']).nth(column).map(|value| value.trim().parse::<f64>()) { samples.push(sample); }
+    }
+    Ok(samples)
+}
+
+/// Writes a row per scale, starting with the frequency of the scale.
+fn write_csv(path : &Path, values : &[f64], frequencies : &[f64], columns : usize) -> std::io::Result<()>
+{
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    for (frequency, row) in frequencies.iter().zip(values.chunks(columns.max(1)))
+    {
+        write!(writer, "{}", frequency)?;
+        for value in row { write!(writer, ",{}", value)?; }
+        writeln!(writer)?;
+    }
+    writer.flush()
+}
+
+/// Writes the magnitude as an 8-bit grayscale image in decibels, with the last scale at the top.
+fn write_png(path : &Path, magnitude : &[f64], rows : usize, columns : usize, range : f64) -> std::io::Result<()>
+{
+    let peak = magnitude.iter().copied().fold(f64::MIN_POSITIVE, f64::max);
+    let mut pixels = Vec::with_capacity(rows * columns);
+    for row in magnitude.chunks(columns.max(1)).rev()
+    {
+        pixels.extend(row.iter().map(|value|
+        {
+            let decibels = 20.0 * (value / peak).max(f64::MIN_POSITIVE).log10();
+            ((1.0 + decibels / range).clamp(0.0, 1.0) * 255.0).round() as u8
+        }));
+    }
+
+    let mut encoder = png::Encoder::new(std::io::BufWriter::new(std::fs::File::create(path)?), columns as u32, rows as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    Ok(writer.write_image_data(&pixels)?)
+}
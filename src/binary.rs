@@ -0,0 +1,101 @@
+//! Binary streams of coefficients, for piping results to files, sockets or other processes.
+//!
+//! The stream starts with a header of 48 bytes, followed by the scales and the rows of coefficients, all little-endian.
+//! The header is "FCWTROWS", the version (u32), the encoding (u32), num_scales and num_samples (u64), fs and bandwidth (f64).
+//! Every row holds the values of a scale for every input sample, as [re, im] pairs if the encoding is complex.
+use crate::*;
+use std::io::{Error, ErrorKind, Write};
+
+const MAGIC : &[u8; 8] = b"FCWTROWS";
+const VERSION : u32 = 1;
+
+/// Values and sample format of a binary stream.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Encoding
+{
+    /// Interleaved real and imaginary parts as f32.
+    ComplexF32,
+    /// Interleaved real and imaginary parts as f64.
+    ComplexF64,
+    /// Magnitude as f32.
+    MagnitudeF32,
+    /// Magnitude as f64.
+    MagnitudeF64
+}
+impl Encoding
+{
+    /// Code of the encoding in the header.
+    pub fn code(& self) -> u32
+    {
+        match self
+        {
+            Encoding::ComplexF32 => 0,
+            Encoding::ComplexF64 => 1,
+            Encoding::MagnitudeF32 => 2,
+            Encoding::MagnitudeF64 => 3
+        }
+    }
+    fn encode(& self, row : &[Complex<f64>], bytes : &mut Vec<u8>)
+    {
+        bytes.clear();
+        for value in row
+        {
+            match self
+            {
+                Encoding::ComplexF32 =>
+                {
+                    bytes.extend_from_slice(&(value.re as f32).to_le_bytes());
+                    bytes.extend_from_slice(&(value.im as f32).to_le_bytes());
+                }
+                Encoding::ComplexF64 =>
+                {
+                    bytes.extend_from_slice(&value.re.to_le_bytes());
+                    bytes.extend_from_slice(&value.im.to_le_bytes());
+                }
+                Encoding::MagnitudeF32 => bytes.extend_from_slice(&(value.norm() as f32).to_le_bytes()),
+                Encoding::MagnitudeF64 => bytes.extend_from_slice(&value.norm().to_le_bytes())
+            }
+        }
+    }
+}
+
+impl FastCWT
+{
+    /// Transforms the input and writes the header and every row to the writer as soon as it is done, without keeping the whole scalogram.
+    ///
+    /// # Arguments
+    /// input     - Input data in vector format
+    ///
+    /// scales    - Scales object
+    ///
+    /// writer    - Destination of the stream
+    ///
+    /// encoding  - Values and sample format of the rows
+    ///
+    /// The rows hold every input sample regardless of the output mode and hop. Only as many rows as there are threads are kept at once.
+    /// Returns an error of ErrorKind::Interrupted if the cancellation token was cancelled.
+    pub fn cwt_write(&mut self, input : &[f64], scales : &Scales, writer : &mut impl Write, encoding : Encoding) -> std::io::Result<()>
+    {
+        let mut header = Vec::with_capacity(48 + 8 * scales.num_scales);
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        header.extend_from_slice(&encoding.code().to_le_bytes());
+        for value in [scales.num_scales, input.len()] { header.extend_from_slice(&(value as u64).to_le_bytes()); }
+        for value in [scales.fs, self.wavelet.fb] { header.extend_from_slice(&value.to_le_bytes()); }
+        for scale in scales.scales.iter() { header.extend_from_slice(&scale.to_le_bytes()); }
+        writer.write_all(&header)?;
+        if input.is_empty() { return Ok(()); }
+
+        //Rows are computed a batch at a time, so they are written in order while the threads stay busy.
+        let spectrum = self.forward(input);
+        let mut bytes = vec![];
+        self.convolve_batches(&spectrum, scales, self.threads(), |row|
+        {
+            if self.is_cancelled() { return Err(Error::new(ErrorKind::Interrupted, "Transform was cancelled.")); }
+            encoding.encode(row, &mut bytes);
+            writer.write_all(&bytes)
+        })?;
+        writer.flush()
+    }
+}
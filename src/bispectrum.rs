@@ -0,0 +1,65 @@
+//! Wavelet bispectrum and bicoherence, which find quadratic phase coupling between frequencies. (van Milligen et al., 1995)
+//!
+//! Components at f1 and f2 which interact nonlinearly create a component at f1 + f2 whose phase is the sum of theirs,
+//! so W(f1) × W(f2) × conj(W(f1 + f2)) keeps its phase over time and adds up, while it averages out for independent components.
+//! The frequency f1 + f2 is taken at the nearest scale, and a pair whose sum is not within half a step of a scale is NaN.
+use crate::*;
+
+/// Wavelet bispectrum of every pair of scales, summed over time, only if the output mode is Complex. (num_scales × num_scales, row-major)
+///
+/// Row i and column j is the pair of the frequencies of scale i and scale j, so the matrix is symmetric.
+pub fn bispectrum(result : &CwtResult) -> Option<Vec<Complex<f64>>>
+{
+    Some(pairs(result)?.into_iter().map(|(bispectrum, _, _)| bispectrum).collect())
+}
+
+/// Squared wavelet bicoherence of every pair of scales, only if the output mode is Complex. (0.0 ..= 1.0, num_scales × num_scales, row-major)
+///
+/// It is |B(f1, f2)|² / (Σ|W(f1) W(f2)|² × Σ|W(f1 + f2)|²), which is 1 if the phases are coupled at every time and near 0 if they are independent.
+/// Its bias for independent phases is about 1 / (number of independent times), so long results separate coupling from noise better.
+pub fn bicoherence(result : &CwtResult) -> Option<Vec<f64>>
+{
+    Some(pairs(result)?.into_iter().map(|(bispectrum, pair, sum)|
+    {
+        let denominator = pair * sum;
+        if bispectrum.is_nan() { f64::NAN } else if denominator > 0.0 { (bispectrum.norm_sqr() / denominator).min(1.0) } else { 0.0 }
+    }).collect())
+}
+
+/// Bispectrum, Σ|W(f1) W(f2)|² and Σ|W(f1 + f2)|² of every pair of scales.
+fn pairs(result : &CwtResult) -> Option<Vec<(Complex<f64>, f64, f64)>>
+{
+    let coefficients = result.complex_coefficients()?;
+    let (num_scales, num_samples) = (result.num_scales(), result.num_samples());
+    let frequencies = result.frequencies();
+    let steps = result.scales().log_steps();
+
+    //Scale nearest to the sum of every pair, within half of its step in log-scale.
+    let sum_scale = |i : usize, j : usize| -> Option<usize>
+    {
+        let target = (frequencies[i] + frequencies[j]).ln();
+        let nearest = (0 .. num_scales).min_by(|a, b| (frequencies[*a].ln() - target).abs().total_cmp(&(frequencies[*b].ln() - target).abs()))?;
+        ((frequencies[nearest].ln() - target).abs() <= steps[nearest] / 2.0).then_some(nearest)
+    };
+    let row = |i : usize| &coefficients[i * num_samples .. (i + 1) * num_samples];
+
+    let mut output = vec![(Complex::new(f64::NAN, f64::NAN), 0.0, 0.0); num_scales * num_scales];
+    if num_scales == 0 { return Some(output); }
+    for_each_row(false, &mut output, num_scales, || (), |_, i, out|
+    {
+        for (j, out) in out.iter_mut().enumerate()
+        {
+            let Some(k) = sum_scale(i, j) else { continue; };
+            let (mut bispectrum, mut pair, mut sum) = (Complex::new(0.0, 0.0), 0.0, 0.0);
+            for ((x, y), z) in row(i).iter().zip(row(j)).zip(row(k))
+            {
+                if x.is_nan() || y.is_nan() || z.is_nan() { continue; }
+                bispectrum += x * y * z.conj();
+                pair += (x * y).norm_sqr();
+                sum += z.norm_sqr();
+            }
+            *out = (bispectrum, pair, sum);
+        }
+    });
+    Some(output)
+}
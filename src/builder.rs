@@ -0,0 +1,149 @@
+use crate::*;
+
+#[cfg(feature = "parallel")]
+enum Threads
+{
+    Global,
+    Count(usize),
+    Pool(alloc::sync::Arc<rayon::ThreadPool>)
+}
+
+/// Builder for the FastCWT configuration.
+pub struct FastCWTBuilder
+{
+    wavelet : Wavelet,
+    normalization : bool,
+    #[cfg(feature = "parallel")]
+    threads : Threads,
+    padding : Padding,
+    detrend : Detrend,
+    taper : Taper,
+    missing : MissingSamples,
+    fft_length : FftLength,
+    output_mode : OutputMode,
+    hop : usize,
+    pooling : Pooling,
+    precision : Precision,
+    accuracy : Accuracy,
+    sequential : bool,
+    direct_scale : f64,
+    fft : alloc::sync::Arc<dyn crate::fft::FftBackend>,
+    progress : Option<Progress>,
+    cancellation : Option<Cancellation>,
+    #[cfg(feature = "std")]
+    stats : bool,
+    #[cfg(feature = "gpu")]
+    gpu : Option<alloc::sync::Arc<crate::gpu::Gpu>>
+}
+impl FastCWTBuilder
+{
+    /// Create a builder with the default configuration, which is the same as FastCWT::create(wavelet, true).
+    ///
+    /// wavelet             - Wavelet object.
+    pub fn create(wavelet : Wavelet) -> Self
+    {
+        Self
+        {
+            wavelet,
+            normalization : true,
+            #[cfg(feature = "parallel")]
+            threads : Threads::Global,
+            padding : Padding::Zero,
+            detrend : Detrend::None,
+            taper : Taper::None,
+            missing : MissingSamples::Propagate,
+            fft_length : FftLength::PowerOfTwo,
+            output_mode : OutputMode::Complex,
+            hop : 1,
+            pooling : Pooling::Decimate,
+            precision : Precision::Double,
+            accuracy : Accuracy::Fast,
+            sequential : false,
+            direct_scale : 0.0,
+            fft : crate::fft::default_backend(),
+            progress : None,
+            cancellation : None,
+            #[cfg(feature = "std")]
+            stats : false,
+            #[cfg(feature = "gpu")]
+            gpu : None
+        }
+    }
+    /// Normalize the coefficients by the FFT size.
+    pub fn normalization(mut self, normalization : bool) -> Self { self.normalization = normalization; self }
+    /// Number of threads of a dedicated thread pool. Without this or thread_pool(), the global rayon pool is used.
+    #[cfg(feature = "parallel")]
+    pub fn threads(mut self, threads : usize) -> Self { self.threads = Threads::Count(threads); self }
+    /// Thread pool of the application to run the transform in, which replaces threads().
+    #[cfg(feature = "parallel")]
+    pub fn thread_pool(mut self, pool : alloc::sync::Arc<rayon::ThreadPool>) -> Self { self.threads = Threads::Pool(pool); self }
+    /// Zero | Reflect | Symmetric | Periodic | ConstantEdge
+    pub fn padding(mut self, padding : Padding) -> Self { self.padding = padding; self }
+    /// None | Mean | Linear | HighPass
+    pub fn detrend(mut self, detrend : Detrend) -> Self { self.detrend = detrend; self }
+    /// None | Tukey | Hann
+    pub fn taper(mut self, taper : Taper) -> Self { self.taper = taper; self }
+    /// Propagate | Error | Zero | Interpolate | Mask
+    pub fn missing(mut self, missing : MissingSamples) -> Self { self.missing = missing; self }
+    /// PowerOfTwo | Exact | Fast
+    pub fn fft_length(mut self, fft_length : FftLength) -> Self { self.fft_length = fft_length; self }
+    /// Complex | Magnitude | Power | LogPower
+    pub fn output_mode(mut self, output_mode : OutputMode) -> Self { self.output_mode = output_mode; self }
+    /// Number of input samples per output sample, and Decimate | Max | Mean for how the samples within a hop are reduced.
+    pub fn hop(mut self, hop : usize, pooling : Pooling) -> Self { self.hop = hop; self.pooling = pooling; self }
+    /// Double | Single
+    pub fn precision(mut self, precision : Precision) -> Self { self.precision = precision; self }
+    /// Fast | Interpolated | Exact
+    pub fn accuracy(mut self, accuracy : Accuracy) -> Self { self.accuracy = accuracy; self }
+    /// Run all work on the calling thread, scale by scale in order.
+    pub fn sequential(mut self, sequential : bool) -> Self { self.sequential = sequential; self }
+    /// Largest scale to convolve in the time domain instead of with the FFT, which is 0.0 (none) by default.
+    pub fn direct_convolution(mut self, max_scale : f64) -> Self { self.direct_scale = max_scale; self }
+    /// FFT implementation, which is fft::RustFft by default.
+    pub fn fft_backend(mut self, backend : alloc::sync::Arc<dyn crate::fft::FftBackend>) -> Self { self.fft = backend; self }
+    /// Callback of the number of completed scales and the number of scales.
+    pub fn progress(mut self, progress : Progress) -> Self { self.progress = Some(progress); self }
+    /// Token to cancel the remaining scales of a transform from another thread.
+    pub fn cancellation(mut self, cancellation : Cancellation) -> Self { self.cancellation = Some(cancellation); self }
+    /// Collect the time and memory of every phase of the transforms.
+    #[cfg(feature = "std")]
+    pub fn stats(mut self, stats : bool) -> Self { self.stats = stats; self }
+    /// GPU device to run the per-scale convolutions on.
+    #[cfg(feature = "gpu")]
+    pub fn gpu(mut self, gpu : alloc::sync::Arc<crate::gpu::Gpu>) -> Self { self.gpu = Some(gpu); self }
+    /// Validate the configuration and create the transform.
+    pub fn build(self) -> Result<FastCWT, FastCwtError>
+    {
+        if self.hop == 0 { return Err(FastCwtError::InvalidHop); }
+        check_taper(self.taper)?;
+        check_detrend(self.detrend)?;
+
+        let mut transform = FastCWT::create(self.wavelet, self.normalization);
+        #[cfg(feature = "parallel")]
+        match self.threads
+        {
+            Threads::Global => {}
+            Threads::Count(threads) => transform.set_threads(threads)?,
+            Threads::Pool(pool) => transform.set_thread_pool(Some(pool))
+        }
+        transform.set_output_mode(self.output_mode);
+        transform.set_hop(self.hop, self.pooling);
+        transform.set_padding(self.padding);
+        transform.set_detrend(self.detrend);
+        transform.set_taper(self.taper);
+        transform.set_missing(self.missing);
+        transform.set_fft_length(self.fft_length);
+        transform.set_precision(self.precision);
+        transform.set_accuracy(self.accuracy);
+        transform.set_sequential(self.sequential);
+        transform.set_direct_convolution(self.direct_scale);
+        transform.set_fft_backend(self.fft);
+        transform.set_progress(self.progress);
+        transform.set_cancellation(self.cancellation);
+        #[cfg(feature = "std")]
+        transform.set_stats(self.stats);
+        #[cfg(feature = "gpu")]
+        transform.set_gpu(self.gpu);
+        Ok(transform)
+    }
+}
@@ -0,0 +1,73 @@
+//! Transforms of signals too long to transform at once, in overlapping chunks within a memory budget.
+//!
+//! Every chunk is extended by a margin on both sides, which is transformed and discarded,
+//! so the coefficients of the chunks match the transform of the whole signal except at its own edges.
+//! The margin is 5 × bandwidth × the largest scale samples, where the envelope of the widest daughter wavelet falls below 1e-5.
+//! Scales close to the Nyquist frequency still differ by a few percent, as they do between FFT lengths of the whole transform.
+use crate::*;
+
+/// Driver of the transform for long signals.
+#[derive(Clone, Debug)]
+pub struct LongSignalCwt
+{
+    transform : FastCWT,
+    memory : usize
+}
+impl LongSignalCwt
+{
+    /// # Arguments
+    /// transform - Transform to run on every chunk, with its output mode and hop
+    ///
+    /// memory    - Memory budget of a chunk in bytes, for the FFT buffers and the coefficients
+    pub fn create(transform : FastCWT, memory : usize) -> LongSignalCwt { LongSignalCwt { transform, memory } }
+    /// Samples transformed and discarded on each side of a chunk, which is a multiple of the hop.
+    pub fn margin(& self, scales : &Scales) -> usize { self.transform.margin(scales) }
+    /// Samples kept from each chunk, which is a multiple of the hop, or 0 if the budget cannot fit the margins.
+    pub fn chunk_length(& self, scales : &Scales) -> usize
+    {
+        //Padded FFT buffers take up to 2 × 16 bytes per sample for each thread and the spectrum, and every scale stores an output per hop.
+        let buffers = if self.transform.padding == Padding::Zero { 32 } else { 64 } * (self.transform.threads() + 1);
+        let output = if self.transform.output_mode == OutputMode::Complex { 16 } else { 8 } * scales.num_scales / self.transform.hop;
+
+        let total = self.memory / (buffers + output).max(1);
+        let length = total.saturating_sub(2 * self.margin(scales));
+        length / self.transform.hop * self.transform.hop
+    }
+    /// Transforms the input chunk by chunk and hands every chunk to the sink, which is the only place the coefficients are held.
+    ///
+    /// # Arguments
+    /// input     - Input data in vector format
+    ///
+    /// scales    - Scales object
+    ///
+    /// sink      - Called with the index of the first input sample and the result of every chunk, in order
+    ///
+    /// Mean and Linear detrending and the taper are applied to the whole input before it is cut, which takes a copy of the input.
+    pub fn process(&mut self, input : &[f64], scales : &Scales, mut sink : impl FnMut(usize, CwtResult))
+    {
+        let (margin, length, hop) = (self.margin(scales), self.chunk_length(scales), self.transform.hop);
+        assert!(length > 0, "Memory budget is too small for the margins of the largest scale.");
+
+        self.transform.preprocessed(input, |transform, input| for start in (0 .. input.len()).step_by(length)
+        {
+            let end = (start + length).min(input.len());
+            let (lower, upper) = (start.saturating_sub(margin), (end + margin).min(input.len()));
+
+            //Both ends of the kept part are on the hop grid of the chunk, because the start and the margin are multiples of the hop.
+            let result = transform.cwt(upper - lower, &input[lower .. upper], scales);
+            sink(start, result.crop((start - lower) / hop .. (end - lower).div_ceil(hop)));
+        });
+    }
+    /// Transforms the whole input chunk by chunk and joins the chunks, which only bounds the memory of the FFT buffers.
+    ///
+    /// # Arguments
+    /// input     - Input data in vector format
+    ///
+    /// scales    - Scales object
+    pub fn cwt(&mut self, input : &[f64], scales : &Scales) -> CwtResult
+    {
+        let mut parts = vec![];
+        self.process(input, scales, |_, part| parts.push(part));
+        CwtResult::concatenate(&parts, scales, self.transform.wavelet.parameters(), self.transform.output_mode, self.transform.hop, self.transform.use_normalization)
+    }
+}
@@ -0,0 +1,383 @@
+//! Cross-wavelet transform, wavelet coherence, phase-locking value and time lag between two signals.
+//!
+//! Coherence follows Torrence & Webster (1999) and Grinsted et al. (2004),
+//! smoothing in time with a Gaussian as wide as the wavelet of each scale and across scales with a boxcar, which FastCWT::smooth() applies to any result.
+//! The spectra are divided by the squared gain of the normalization of the wavelet before smoothing, which brings every scale to the same peak gain
+//! like the 1/scale weighting of the original formula does for L2 normalized wavelets, so coherence does not depend on the Normalization.
+use crate::*;
+
+/// Smoothing applied to the wavelet spectra before computing the coherence.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Smoothing
+{
+    time : f64,
+    scale : usize
+}
+impl Smoothing
+{
+    /// # Arguments
+    /// time                - Standard deviation of the Gaussian window in time, relative to the time support of each scale
+    ///
+    /// scale               - Width of the boxcar window across scales, in number of scales
+    pub fn create(time : f64, scale : usize) -> Smoothing { Smoothing { time, scale } }
+}
+impl Default for Smoothing
+{
+    fn default() -> Self { Self::create(1.0, 3) }
+}
+
+/// Cross-wavelet spectrum of two signals.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, Debug)]
+pub struct CrossWavelet
+{
+    coefficients : Vec<Complex<f64>>,
+    num_samples : usize
+}
+impl CrossWavelet
+{
+    /// Wa × conj(Wb), scale by scale. (num_scales × samples, row-major)
+    pub fn coefficients(& self) -> &[Complex<f64>] { &self.coefficients }
+    /// Cross-wavelet power |Wa × conj(Wb)|.
+    pub fn power(& self) -> Vec<f64> { self.coefficients.iter().map(|value| value.norm()).collect() }
+    /// Phase of a relative to b in radians. (-π ..= π)
+    pub fn phase(& self) -> Vec<f64> { self.coefficients.iter().map(|value| value.arg()).collect() }
+    /// Number of samples in each row.
+    pub fn num_samples(& self) -> usize { self.num_samples }
+}
+
+/// Wavelet coherence of two signals.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, Debug)]
+pub struct Coherence
+{
+    coherence : Vec<f64>,
+    phase : Vec<f64>,
+    num_samples : usize
+}
+impl Coherence
+{
+    /// Squared wavelet coherence, scale by scale. (0.0 ..= 1.0, num_scales × samples, row-major)
+    pub fn coherence(& self) -> &[f64] { &self.coherence }
+    /// Phase of the smoothed cross-wavelet spectrum in radians. (-π ..= π)
+    pub fn phase(& self) -> &[f64] { &self.phase }
+    /// Number of samples in each row.
+    pub fn num_samples(& self) -> usize { self.num_samples }
+}
+
+impl FastCWT
+{
+    /// Cross-wavelet transform of two signals of the same length.
+    ///
+    /// # Arguments
+    /// a         - First input data
+    ///
+    /// b         - Second input data
+    ///
+    /// scales    - Scales object
+    pub fn xwt(&mut self, a : &[f64], b : &[f64], scales : &Scales) -> CrossWavelet
+    {
+        let (wa, wb) = self.transform_pair(a, b, scales);
+        let coefficients = wa.iter().zip(&wb).map(|(x, y)| x * y.conj()).collect();
+        CrossWavelet { coefficients, num_samples : a.len() }
+    }
+    /// Wavelet coherence of two signals of the same length.
+    ///
+    /// # Arguments
+    /// a         - First input data
+    ///
+    /// b         - Second input data
+    ///
+    /// scales    - Scales object
+    ///
+    /// smoothing - Smoothing in time and across scales
+    pub fn wtc(&mut self, a : &[f64], b : &[f64], scales : &Scales, smoothing : Smoothing) -> Coherence
+    {
+        let size = a.len();
+        let (wa, wb) = self.transform_pair(a, b, scales);
+
+        let mut cross : Vec<Complex<f64>> = wa.iter().zip(&wb).map(|(x, y)| x * y.conj()).collect();
+        let mut power_a : Vec<Complex<f64>> = wa.iter().map(|x| Complex::new(x.norm_sqr(), 0.0)).collect();
+        let mut power_b : Vec<Complex<f64>> = wb.iter().map(|y| Complex::new(y.norm_sqr(), 0.0)).collect();
+
+        self.run(||
+        {
+            //Same peak gain at every scale, so the boxcar across scales weighs them equally.
+            for values in [&mut cross, &mut power_a, &mut power_b]
+            {
+                for (row, scale) in values.chunks_mut(size.max(1)).zip(scales.scales.iter())
+                {
+                    let gain = self.wavelet.gain(*scale);
+                    for value in row.iter_mut() { *value /= gain * gain; }
+                }
+            }
+            let sigmas : Vec<f64> = scales.scales.iter().map(|scale| smoothing.time * self.wavelet.fb * scale).collect();
+            for values in [&mut cross, &mut power_a, &mut power_b] { smooth(values, size, &sigmas, smoothing.scale, self.fft.as_ref(), self.sequential); }
+        });
+
+        let coherence = cross.iter().zip(power_a.iter().zip(&power_b)).map(|(xy, (x, y))|
+        {
+            let denominator = x.re * y.re;
+            if denominator > 0.0 { (xy.norm_sqr() / denominator).min(1.0) } else { 0.0 }
+        }).collect();
+        let phase = cross.iter().map(|xy| xy.arg()).collect();
+
+        Coherence { coherence, phase, num_samples : size }
+    }
+    /// Time-resolved phase-locking value of two signals of the same length, scale by scale. (0.0 ..= 1.0, num_scales × samples, row-major)
+    ///
+    /// It is the magnitude of the mean of exp(i × (phase of a - phase of b)) over a window centered at each sample, which ignores the amplitudes.
+    /// The window is shortened at the first and the last samples.
+    ///
+    /// # Arguments
+    /// a         - First input data
+    ///
+    /// b         - Second input data
+    ///
+    /// scales    - Scales object
+    ///
+    /// window    - Length of the window in samples
+    pub fn plv(&mut self, a : &[f64], b : &[f64], scales : &Scales, window : usize) -> Vec<f64>
+    {
+        assert!(window > 0, "Window must be at least 1 sample.");
+
+        let size = a.len();
+        let (wa, wb) = self.transform_pair(a, b, scales);
+        let mut plv = vec![0.0; scales.num_scales * size];
+        for (row, (ra, rb)) in plv.chunks_mut(size.max(1)).zip(wa.chunks(size.max(1)).zip(wb.chunks(size.max(1))))
+        {
+            //Prefix sums of the phase differences as unit vectors, so that every window is summed at once.
+            let mut sums = vec![Complex::new(0.0, 0.0); size + 1];
+            for (n, (x, y)) in ra.iter().zip(rb).enumerate()
+            {
+                let xy = x * y.conj();
+                let norm = xy.norm();
+                sums[n + 1] = sums[n] + if norm > 0.0 { xy / norm } else { Complex::new(0.0, 0.0) };
+            }
+            for (n, out) in row.iter_mut().enumerate()
+            {
+                let (lower, upper) = (n.saturating_sub(window / 2), (n + (window - 1) / 2 + 1).min(size));
+                *out = (sums[upper] - sums[lower]).norm() / (upper - lower) as f64;
+            }
+        }
+        plv
+    }
+    /// Lag of b relative to a for every scale, where the magnitude of the cross-correlation of their coefficients peaks.
+    ///
+    /// The correlation of the complex coefficients follows their envelopes, so the lag is the group delay of the band of the scale,
+    /// without the ambiguity of a period of the phase. It is refined between samples by a parabola through the peak and its neighbours,
+    /// and is positive if b is later than a.
+    ///
+    /// # Arguments
+    /// a         - First input data
+    ///
+    /// b         - Second input data
+    ///
+    /// scales    - Scales object
+    ///
+    /// max_lag   - Longest lag in samples to search, in both directions
+    ///
+    /// Returns the lag in samples and the peak correlation (0.0 ..= 1.0) of every scale.
+    pub fn lag(&mut self, a : &[f64], b : &[f64], scales : &Scales, max_lag : usize) -> Vec<(f64, f64)>
+    {
+        let size = a.len();
+        let (wa, wb) = self.transform_pair(a, b, scales);
+        let mut lags = vec![(0.0, 0.0); scales.num_scales];
+        if size == 0 { return lags; }
+
+        //Zero padding by the longest lag keeps the circular correlation from wrapping into the searched lags.
+        let max_lag = max_lag.min(size - 1) as isize;
+        let newsize = (size + max_lag as usize).next_power_of_two();
+        let forward = self.fft.plan_f64(newsize, false);
+        let inverse = self.fft.plan_f64(newsize, true);
+
+        self.run(|| for_each_row(self.sequential, &mut lags, 1, || (vec![Complex::new(0.0, 0.0); newsize], vec![Complex::new(0.0, 0.0); newsize], vec![]), |(x, y, scratch), i, out|
+        {
+            no_denormals(||
+            {
+                let (ra, rb) = (&wa[i * size .. (i + 1) * size], &wb[i * size .. (i + 1) * size]);
+                for (buffer, row) in [(&mut *x, ra), (&mut *y, rb)]
+                {
+                    buffer.fill(Complex::new(0.0, 0.0));
+                    buffer[.. size].copy_from_slice(row);
+                    forward.process(buffer, scratch);
+                }
+                for (x, y) in x.iter_mut().zip(y.iter()) { *x = x.conj() * y; }
+                inverse.process(x, scratch);
+
+                let energy = (ra.iter().map(|value| value.norm_sqr()).sum::<f64>() * rb.iter().map(|value| value.norm_sqr()).sum::<f64>()).sqrt() * newsize as f64;
+                if energy <= 0.0 { return; }
+                let at = |lag : isize| x[lag.rem_euclid(newsize as isize) as usize].norm();
+                let best = (- max_lag ..= max_lag).max_by(|p, q| at(*p).total_cmp(&at(*q))).unwrap_or(0);
+
+                let (lower, center, upper) = (at(best - 1), at(best), at(best + 1));
+                let curvature = lower - 2.0 * center + upper;
+                let offset = if best.abs() < max_lag && curvature < 0.0 { (0.5 * (lower - upper) / curvature).clamp(-0.5, 0.5) } else { 0.0 };
+                out[0] = (best as f64 + offset, (center / energy).min(1.0));
+            });
+        }));
+        lags
+    }
+    fn transform_pair(&mut self, a : &[f64], b : &[f64], scales : &Scales) -> (Vec<Complex<f64>>, Vec<Complex<f64>>)
+    {
+        assert!(a.len() == b.len(), "Both signals must have the same length.");
+
+        let mut wa = vec![Complex::new(0.0, 0.0); scales.num_scales * a.len()];
+        let mut wb = vec![Complex::new(0.0, 0.0); scales.num_scales * b.len()];
+        self.transform(a, scales, &mut wa);
+        self.transform(b, scales, &mut wb);
+        (wa, wb)
+    }
+}
+
+impl FastCWT
+{
+    /// Result smoothed like the spectra of wtc(), in time with a Gaussian as wide as the wavelet of each scale and across scales with a boxcar.
+    ///
+    /// Complex coefficients are smoothed as they are, which averages their phase as well, while the other output modes are smoothed in power
+    /// and converted back. The smoothing is normalized by the weight of the window inside the result, so coefficients masked with NaN
+    /// and the edges do not pull their neighbours towards zero, and masked coefficients stay NaN.
+    ///
+    /// # Arguments
+    /// result    - Result of the transform
+    ///
+    /// smoothing - Smoothing in time and across scales
+    pub fn smooth(& self, result : &CwtResult, smoothing : &Smoothing) -> CwtResult
+    {
+        let (size, hop) = (result.num_samples(), result.hop());
+        //Window is in samples of the input, so it is narrowed to the columns of the hop.
+        let sigmas : Vec<f64> = result.scale_axis().iter().map(|scale| smoothing.time * result.wavelet().fb * scale / hop as f64).collect();
+        let mut values : Vec<Complex<f64>> = match result.complex_coefficients()
+        {
+            Some(coefficients) => coefficients.to_vec(),
+            None => result.power().into_iter().map(|power| Complex::new(power, 0.0)).collect()
+        };
+        let masked : Vec<bool> = values.iter().map(|value| value.is_nan()).collect();
+        let mut weights : Vec<Complex<f64>> = masked.iter().map(|masked| Complex::new(if *masked { 0.0 } else { 1.0 }, 0.0)).collect();
+        for value in values.iter_mut().filter(|value| value.is_nan()) { *value = Complex::new(0.0, 0.0); }
+        self.run(||
+        {
+            for values in [&mut values, &mut weights] { smooth(values, size, &sigmas, smoothing.scale, self.fft.as_ref(), self.sequential); }
+        });
+        for ((value, weight), masked) in values.iter_mut().zip(&weights).zip(&masked)
+        {
+            *value = if *masked || weight.re <= 0.0 { Complex::new(f64::NAN, f64::NAN) } else { *value / weight.re };
+        }
+
+        let (scales, wavelet) = (result.scales().clone(), result.wavelet().parameters());
+        match result.mode()
+        {
+            OutputMode::Complex => CwtResult::complex(values, scales, wavelet, size, hop, result.is_normalized()),
+            mode =>
+            {
+                let values = values.iter().map(|value| match mode
+                {
+                    OutputMode::Magnitude => value.re.max(0.0).sqrt(),
+                    OutputMode::LogPower => 10.0 * value.re.max(0.0).log10(),
+                    _ => value.re
+                }).collect();
+                CwtResult::real(values, mode, scales, wavelet, size, hop, result.is_normalized())
+            }
+        }
+    }
+}
+
+impl CwtResult
+{
+    /// Result smoothed like the spectra of wtc(), with the FFT backend and the threads of the transform. (FastCWT::smooth())
+    ///
+    /// # Arguments
+    /// transform - Transform whose FFT backend and threads do the smoothing
+    ///
+    /// smoothing - Smoothing in time and across scales
+    pub fn smooth(& self, transform : &FastCWT, smoothing : &Smoothing) -> CwtResult { transform.smooth(self, smoothing) }
+}
+
+/// Smooths (num_scales × size) values with a Gaussian of the standard deviation of each scale in time, then with a boxcar of width scales.
+pub(crate) fn smooth(values : &mut [Complex<f64>], size : usize, sigmas : &[f64], width : usize, fft : &dyn fft::FftBackend, sequential : bool)
+{
+    if size == 0 || sigmas.is_empty() { return; }
+
+    //Zero pad by 3 sigma of the widest window.
+    let widest = sigmas.iter().fold(0.0_f64, |widest, sigma| widest.max(*sigma));
+    let newsize = (size + 2 * (3.0 * widest).ceil() as usize).next_power_of_two();
+
+    let forward = fft.plan_f64(newsize, false);
+    let inverse = fft.plan_f64(newsize, true);
+
+    for_each_row(sequential, values, size, || (vec![Complex::new(0.0, 0.0); newsize], vec![]), |(buffer, scratch), i, row|
+    {
+        no_denormals(||
+        {
+            let sigma = sigmas[i];
+
+            buffer.fill(Complex::new(0.0, 0.0));
+            buffer[.. size].copy_from_slice(row);
+            forward.process(buffer, scratch);
+
+            //Fourier transform of the Gaussian window, with the 1 / newsize of the inverse FFT folded in.
+            for (k, bin) in buffer.iter_mut().enumerate()
+            {
+                let k = if k <= newsize / 2 { k as f64 } else { k as f64 - newsize as f64 };
+                let omega = 2.0 * core::f64::consts::PI * k / newsize as f64;
+                *bin *= (- omega * omega * sigma * sigma / 2.0).exp() / newsize as f64;
+            }
+            inverse.process(buffer, scratch);
+            row.copy_from_slice(&buffer[.. size]);
+        });
+    });
+
+    if width < 2 { return; }
+
+    //Boxcar across scales, which is narrowed at the first and the last scales.
+    let num_scales = sigmas.len();
+    let mut smoothed = vec![Complex::new(0.0, 0.0); num_scales * size];
+    for_each_row(sequential, &mut smoothed, size, || (), |_, i, row|
+    {
+        let (lower, upper) = (i.saturating_sub((width - 1) / 2), core::cmp::min(num_scales - 1, i + width / 2));
+        for j in lower ..= upper
+        {
+            for (out, value) in row.iter_mut().zip(&values[j * size .. (j + 1) * size]) { *out += value; }
+        }
+        for out in row.iter_mut() { *out /= (upper - lower + 1) as f64; }
+    });
+    values.copy_from_slice(&smoothed);
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn constant(value : f64, num_scales : usize, size : usize) -> CwtResult
+    {
+        let scales = Scales::create(ScaleTypes::LinFreq, 1000.0, 10.0, 200.0, num_scales);
+        CwtResult::real(vec![value; num_scales * size], OutputMode::Power, scales, Wavelet::create(1.0), size, 1, true)
+    }
+
+    #[test]
+    fn smooth_keeps_constant()
+    {
+        let transform = FastCWT::create(Wavelet::create(1.0), true);
+        let smoothed = transform.smooth(&constant(2.0, 8, 500), &Smoothing::default());
+        assert!(smoothed.power().iter().all(|power| (power - 2.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn smooth_skips_nan()
+    {
+        let transform = FastCWT::create(Wavelet::create(1.0), true);
+        let (num_scales, size) = (8, 500);
+        let mut power = vec![2.0; num_scales * size];
+        for row in power.chunks_mut(size) { row[200 .. 260].fill(f64::NAN); }
+        let result = CwtResult::real(power, OutputMode::Power, constant(0.0, num_scales, size).scales().clone(), Wavelet::create(1.0), size, 1, true);
+
+        let smoothed = result.smooth(&transform, &Smoothing::default());
+        for (column, power) in smoothed.power().iter().enumerate()
+        {
+            if (200 .. 260).contains(&(column % size)) { assert!(power.is_nan()); }
+            else { assert!((power - 2.0).abs() < 1e-9); }
+        }
+    }
+}
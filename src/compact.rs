@@ -0,0 +1,199 @@
+//! Compact results, which store the coefficients in fewer bits for visualization and machine learning.
+//!
+//! Complex f64 coefficients take 16 bytes each, while most plots and models only need f32, half floats or 8-bit decibels.
+use crate::*;
+pub use half::f16;
+
+/// Sample format of a compact result.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OutputPrecision
+{
+    /// Single precision in the output mode of the transform.
+    F32,
+    /// Half precision in the output mode of the transform.
+    F16,
+    /// Power in decibels mapped linearly from floor (0) to ceiling (255), regardless of the output mode.
+    DecibelU8 { floor : f64, ceiling : f64 }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, Debug)]
+enum Values
+{
+    F32(Vec<f32>),
+    F16(Vec<f16>),
+    U8 { levels : Vec<u8>, floor : f64, ceiling : f64 }
+}
+
+/// Result of the transform in a compact sample format. (num_scales × num_samples, row-major)
+///
+/// Complex coefficients are stored as interleaved [re, im] pairs.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, Debug)]
+pub struct CompactCwt
+{
+    values : Values,
+    mode : OutputMode,
+    num_samples : usize,
+    hop : usize,
+    scales : Scales,
+    wavelet : Wavelet
+}
+impl CompactCwt
+{
+    /// Output mode of the values, which is LogPower for DecibelU8.
+    pub fn mode(& self) -> OutputMode { self.mode }
+    /// Sample format of the values.
+    pub fn precision(& self) -> OutputPrecision
+    {
+        match self.values
+        {
+            Values::F32(_) => OutputPrecision::F32,
+            Values::F16(_) => OutputPrecision::F16,
+            Values::U8 { floor, ceiling, .. } => OutputPrecision::DecibelU8 { floor, ceiling }
+        }
+    }
+    /// Number of scales. (rows)
+    pub fn num_scales(& self) -> usize { self.scales.len() }
+    /// Number of samples of each scale. (columns)
+    pub fn num_samples(& self) -> usize { self.num_samples }
+    /// Number of input samples per output sample.
+    pub fn hop(& self) -> usize { self.hop }
+    /// Scales the result was computed with.
+    pub fn scales(& self) -> &Scales { &self.scales }
+    /// Wavelet the result was computed with. (only its parameters)
+    pub fn wavelet(& self) -> &Wavelet { &self.wavelet }
+    /// Values, only if the precision is F32.
+    pub fn f32_values(& self) -> Option<&[f32]>
+    {
+        match &self.values
+        {
+            Values::F32(values) => Some(values),
+            _ => None
+        }
+    }
+    /// Values, only if the precision is F16.
+    pub fn f16_values(& self) -> Option<&[f16]>
+    {
+        match &self.values
+        {
+            Values::F16(values) => Some(values),
+            _ => None
+        }
+    }
+    /// Values, only if the precision is DecibelU8.
+    pub fn u8_values(& self) -> Option<&[u8]>
+    {
+        match &self.values
+        {
+            Values::U8 { levels, .. } => Some(levels),
+            _ => None
+        }
+    }
+    /// Values widened to f64, with the levels of DecibelU8 mapped back to decibels.
+    pub fn to_f64(& self) -> Vec<f64>
+    {
+        match &self.values
+        {
+            Values::F32(values) => values.iter().map(|value| *value as f64).collect(),
+            Values::F16(values) => values.iter().map(|value| value.to_f64()).collect(),
+            Values::U8 { levels, floor, ceiling } => levels.iter().map(|level| floor + *level as f64 / 255.0 * (ceiling - floor)).collect()
+        }
+    }
+}
+
+impl FastCWT
+{
+    /// Same as cwt(), but converts the coefficients to a compact sample format as every scale is done, without a full f64 copy.
+    ///
+    /// # Arguments
+    /// num       - Number of samples to transform
+    ///
+    /// input     - Input data in vector format
+    ///
+    /// scales    - Scales object
+    ///
+    /// precision - Sample format of the result
+    pub fn cwt_compact(&mut self, num : usize, input : &[f64], scales : &Scales, precision : OutputPrecision) -> CompactCwt
+    {
+        assert!(num <= input.len(), "Number of samples cannot be longer than the input.");
+        if let OutputPrecision::DecibelU8 { floor, ceiling } = precision { assert!(floor < ceiling, "Floor must be below the ceiling."); }
+
+        let spectrum = (num > 0).then(|| self.forward(&input[.. num]));
+        let columns = num.div_ceil(self.hop);
+        let (mode, values) = match precision
+        {
+            OutputPrecision::F32 => (self.output_mode, Values::F32(self.encode(spectrum.as_ref(), scales, columns, |value| value as f32))),
+            OutputPrecision::F16 => (self.output_mode, Values::F16(self.encode(spectrum.as_ref(), scales, columns, f16::from_f64))),
+            OutputPrecision::DecibelU8 { floor, ceiling } =>
+            {
+                let mut output = vec![0; scales.num_scales * columns];
+                let (hop, pooling) = (self.hop, self.pooling);
+                if let Some(spectrum) = spectrum.as_ref()
+                {
+                    self.convolve_with(spectrum, scales, &mut output, |row, out|
+                    {
+                        for (out, window) in out.iter_mut().zip(row.chunks(hop))
+                        {
+                            //Decibels of each sample are pooled, like the values of OutputMode::LogPower.
+                            let decibel = pool(window, pooling, |data| OutputMode::LogPower.apply(*data));
+                            //NaN and silence map to the floor.
+                            *out = ((decibel - floor) / (ceiling - floor) * 255.0).clamp(0.0, 255.0).round() as u8;
+                        }
+                    });
+                }
+                (OutputMode::LogPower, Values::U8 { levels : output, floor, ceiling })
+            }
+        };
+        CompactCwt { values, mode, num_samples : columns, hop : self.hop, scales : scales.clone(), wavelet : self.wavelet.parameters() }
+    }
+    /// Same as cwt(), but writes the coefficients in the output mode of the transform as f32 into a buffer of the caller.
+    ///
+    /// Complex coefficients are interleaved as real and imaginary parts, so they take two values each.
+    ///
+    /// # Arguments
+    /// input     - Input data in vector format
+    ///
+    /// scales    - Scales object
+    ///
+    /// output    - Buffer of num_scales × ceil(samples / hop) values, or twice that for Complex, row-major
+    pub fn cwt_into_f32(&mut self, input : &[f64], scales : &Scales, output : &mut [f32]) -> Result<(), FastCwtError>
+    {
+        let width = if self.output_mode == OutputMode::Complex { 2 } else { 1 };
+        let expected = scales.num_scales * input.len().div_ceil(self.hop) * width;
+        if output.len() != expected { return Err(FastCwtError::OutputLength { expected, len : output.len() }); }
+        if input.is_empty() { return Ok(()); }
+
+        let spectrum = self.forward(input);
+        self.encode_into(&spectrum, scales, output, |value| value as f32);
+        Ok(())
+    }
+    /// Coefficients in the output mode and the hop of the transform, converted by narrow().
+    fn encode<T : Copy + Default + Send>(&self, spectrum : Option<&Spectrum>, scales : &Scales, columns : usize, narrow : impl Fn(f64) -> T + Sync) -> Vec<T>
+    {
+        let width = if self.output_mode == OutputMode::Complex { 2 } else { 1 };
+        let mut output = vec![T::default(); scales.num_scales * columns * width];
+        if let Some(spectrum) = spectrum { self.encode_into(spectrum, scales, &mut output, narrow); }
+        output
+    }
+    /// Same as encode(), but into an output of the right length.
+    fn encode_into<T : Copy + Send>(&self, spectrum : &Spectrum, scales : &Scales, output : &mut [T], narrow : impl Fn(f64) -> T + Sync)
+    {
+        let (mode, hop, pooling) = (self.output_mode, self.hop, self.pooling);
+        let width = if mode == OutputMode::Complex { 2 } else { 1 };
+        self.convolve_with(spectrum, scales, output, |row, out|
+        {
+            for (out, window) in out.chunks_mut(width).zip(row.chunks(hop))
+            {
+                if mode == OutputMode::Complex
+                {
+                    let value = pool(window, pooling, |data| *data);
+                    out[0] = narrow(value.re);
+                    out[1] = narrow(value.im);
+                }
+                else { out[0] = narrow(pool(window, pooling, |data| mode.apply(*data))); }
+            }
+        });
+    }
+}
@@ -0,0 +1,67 @@
+//! Constant-Q transform.
+//!
+//! Bins are spaced geometrically by bins_per_octave from the lowest frequency, and every bin has the same quality factor
+//! Q = 1 / (2^(1 / bins_per_octave) - 1), so neighbouring bins cross at half power.
+//! It runs the transform with a Morlet wavelet of that Q, which is a Gaussian window in frequency instead of the usual Hann window in time.
+use crate::*;
+
+/// Constant-Q transform with its bins and hop.
+#[derive(Clone, Debug)]
+pub struct ConstantQ
+{
+    transform : FastCWT,
+    scales : Scales,
+    bins_per_octave : usize
+}
+impl ConstantQ
+{
+    /// # Arguments
+    /// fs              - Sample frequency
+    ///
+    /// f_min           - Frequency of the lowest bin
+    ///
+    /// f_max           - Highest frequency, which the highest bin does not exceed
+    ///
+    /// bins_per_octave - Number of bins per octave, which sets the Q
+    ///
+    /// hop             - Number of input samples per output sample
+    pub fn create(fs : f64, f_min : f64, f_max : f64, bins_per_octave : usize, hop : usize) -> ConstantQ
+    {
+        assert!(f_min > 0.0 && f_min <= f_max, "Lowest frequency must be positive and cannot be higher than the highest frequency.");
+        assert!(bins_per_octave > 0, "There must be at least one bin per octave.");
+
+        let num_bins = (bins_per_octave as f64 * (f_max / f_min).log2() + 1e-9).floor() as usize + 1;
+        let frequencies : Vec<f64> = (0 .. num_bins).map(|k| f_min * 2.0_f64.powf(k as f64 / bins_per_octave as f64)).collect();
+        let scales = Scales::from_frequencies(fs, &frequencies);
+
+        //Full width at half power of the Morlet wavelet is 2 sqrt(ln 2) / (2 pi fb) relative to the center frequency, which must be 1 / Q.
+        let mut wavelet = Wavelet::create(quality(bins_per_octave) * core::f64::consts::LN_2.sqrt() / core::f64::consts::PI);
+        wavelet.set_normalization(Normalization::L1);
+
+        let mut transform = FastCWT::create(wavelet, true);
+        transform.set_hop(hop, Pooling::Decimate);
+        ConstantQ { transform, scales, bins_per_octave }
+    }
+    /// Number of bins per octave.
+    pub fn bins_per_octave(& self) -> usize { self.bins_per_octave }
+    /// Quality factor of every bin, which is its frequency over its full width at half power.
+    pub fn q(& self) -> f64 { quality(self.bins_per_octave) }
+    /// Number of input samples per output sample.
+    pub fn hop(& self) -> usize { self.transform.hop }
+    /// Scales of the bins, from the lowest frequency up.
+    pub fn scales(& self) -> &Scales { &self.scales }
+    /// Center frequency of every bin, in ascending order.
+    pub fn frequencies(& self) -> Vec<f64> { self.scales.frequencies() }
+    /// Transform that computes the bins, to set its threads, padding or precision.
+    pub fn transform(&mut self) -> &mut FastCWT { &mut self.transform }
+    /// Constant-Q transform of the input, a row per bin from the lowest frequency up.
+    ///
+    /// # Arguments
+    /// input     - Input data in vector format
+    ///
+    /// A cosine of amplitude A at the frequency of a bin gives coefficients of magnitude A in that bin.
+    pub fn cqt(&mut self, input : &[f64]) -> CwtResult { self.transform.cwt(input.len(), input, &self.scales) }
+}
+
+/// Q = 1 / (2^(1 / bins_per_octave) - 1), where neighbouring bins cross at half power.
+fn quality(bins_per_octave : usize) -> f64 { 1.0 / (2.0_f64.powf(1.0 / bins_per_octave as f64) - 1.0) }
@@ -0,0 +1,116 @@
+//! CSV tables of results, for spreadsheets and R.
+//!
+//! A table has a row per scale. The header row holds the component and the time of each column in seconds,
+//! and every row starts with the frequency of its scale in Hz.
+//!
+//! ```text
+//! magnitude,0,0.001,0.002,...
+//! 20,0.013,0.015,0.016,...
+//! ```
+use crate::*;
+use std::io::{BufRead, Error, ErrorKind, Write};
+
+/// Component of the coefficients in a table.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Component
+{
+    /// Magnitude. (|W|)
+    Magnitude,
+    /// Phase in radians. (arg W)
+    Phase,
+    /// Real part.
+    Re,
+    /// Imaginary part.
+    Im
+}
+impl Component
+{
+    fn name(& self) -> &'static str
+    {
+        match self
+        {
+            Component::Magnitude => "magnitude",
+            Component::Phase => "phase",
+            Component::Re => "re",
+            Component::Im => "im"
+        }
+    }
+}
+
+/// Table read with read(). (frequencies.len() × times.len(), row-major)
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Table
+{
+    /// Component of the values.
+    pub component : Component,
+    /// Frequency of each row in Hz.
+    pub frequencies : Vec<f64>,
+    /// Time of each column in seconds.
+    pub times : Vec<f64>,
+    /// Values, row by row.
+    pub values : Vec<f64>
+}
+
+impl CwtResult
+{
+    /// Writes a component of the coefficients as a CSV table, which read() reads back.
+    ///
+    /// Phase, Re and Im need the complex coefficients, and are an error if the output mode is not Complex.
+    pub fn to_csv(& self, writer : impl Write, component : Component) -> std::io::Result<()>
+    {
+        let values = match (component, self.complex_coefficients())
+        {
+            (Component::Magnitude, _) => self.magnitude(),
+            (Component::Phase, Some(coefficients)) => coefficients.iter().map(|value| value.arg()).collect(),
+            (Component::Re, Some(coefficients)) => coefficients.iter().map(|value| value.re).collect(),
+            (Component::Im, Some(coefficients)) => coefficients.iter().map(|value| value.im).collect(),
+            (_, None) => return Err(Error::new(ErrorKind::InvalidInput, "Phase, Re and Im need the complex coefficients."))
+        };
+
+        let mut writer = std::io::BufWriter::new(writer);
+        write!(writer, "{}", component.name())?;
+        for time in self.times() { write!(writer, ",{}", time)?; }
+        writeln!(writer)?;
+        for (frequency, row) in self.frequencies().iter().zip(values.chunks(self.num_samples().max(1)))
+        {
+            write!(writer, "{}", frequency)?;
+            for value in row { write!(writer, ",{}", value)?; }
+            writeln!(writer)?;
+        }
+        writer.flush()
+    }
+}
+
+/// Reads a table written by CwtResult::to_csv().
+pub fn read(reader : impl BufRead) -> std::io::Result<Table>
+{
+    let invalid = |message : &str| Error::new(ErrorKind::InvalidData, message);
+    let parse = |cell : &str| cell.trim().parse::<f64>().map_err(|_| invalid("Cell is not a number."));
+
+    let mut lines = reader.lines().filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()));
+    let header = lines.next().ok_or_else(|| invalid("Table is empty."))??;
+    let mut header = header.split(',');
+    let component = match header.next().unwrap_or_default().trim()
+    {
+        "magnitude" => Component::Magnitude,
+        "phase" => Component::Phase,
+        "re" => Component::Re,
+        "im" => Component::Im,
+        _ => return Err(invalid("Header must start with magnitude, phase, re or im."))
+    };
+    let times = header.map(parse).collect::<Result<Vec<_>, _>>()?;
+
+    let (mut frequencies, mut values) = (vec![], vec![]);
+    for line in lines
+    {
+        let line = line?;
+        let mut cells = line.split(',');
+        frequencies.push(parse(cells.next().unwrap_or_default())?);
+        let length = values.len();
+        for cell in cells { values.push(parse(cell)?); }
+        if values.len() - length != times.len() { return Err(invalid("Row has a different number of columns than the header.")); }
+    }
+    Ok(Table { component, frequencies, times, values })
+}
@@ -0,0 +1,114 @@
+//! Wavelet-domain denoising.
+//!
+//! The coefficients of every scale are shrunk towards zero by a threshold relative to the noise of that scale, and the signal is reconstructed by the inverse transform.
+//! The noise is estimated from the median magnitude of each scale, which is Rayleigh distributed for complex Gaussian noise.
+use crate::*;
+
+/// Rule for the threshold of each scale.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Threshold
+{
+    /// Universal threshold of Donoho & Johnstone (1994), which about one coefficient of pure noise exceeds in every scale.
+    Universal,
+    /// Threshold minimizing Stein's unbiased risk estimate of soft shrinkage, which keeps more of the signal, up to the universal threshold.
+    Sure
+}
+
+/// How the coefficients are shrunk by the threshold.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Shrinkage
+{
+    /// Magnitudes are reduced by the threshold, and the ones below it become zero.
+    Soft,
+    /// Coefficients below the threshold become zero, and the others are kept.
+    Hard
+}
+
+impl FastCWT
+{
+    /// Removes noise from the input by thresholding its coefficients and reconstructing it with the inverse transform.
+    ///
+    /// The coefficients are computed in complex without a hop regardless of the settings.
+    /// Components outside the frequency range of the scales are removed, except the mean which is kept.
+    ///
+    /// Returns FastCwtError::NotNormalized if the transform is not normalized, which the inverse transform needs.
+    ///
+    /// # Arguments
+    /// input     - Input data in vector format
+    ///
+    /// scales    - Scales object, which should be dense enough to overlap (about 8 or more per octave)
+    ///
+    /// threshold - Universal | Sure
+    ///
+    /// shrinkage - Soft | Hard
+    pub fn denoise(&mut self, input : &[f64], scales : &Scales, threshold : Threshold, shrinkage : Shrinkage) -> Result<Vec<f64>, FastCwtError>
+    {
+        if !self.use_normalization { return Err(FastCwtError::NotNormalized); }
+        let size = input.len();
+        if size == 0 { return Ok(vec![]); }
+
+        let mut coefficients = vec![Complex::new(0.0, 0.0); scales.num_scales * size];
+        let spectrum = self.forward(input);
+        self.convolve(&spectrum, scales, &mut coefficients);
+
+        for row in coefficients.chunks_mut(size)
+        {
+            let mut magnitudes : Vec<f64> = row.iter().map(|value| value.norm()).collect();
+            magnitudes.sort_by(|a, b| a.total_cmp(b));
+
+            //Median of a Rayleigh distribution is sigma × √(2 ln 2), where sigma is the noise of the real and imaginary parts.
+            let sigma = magnitudes[size / 2] / (2.0 * core::f64::consts::LN_2).sqrt();
+            if sigma <= 0.0 { continue; }
+            let lambda = match threshold
+            {
+                Threshold::Universal => sigma * (2.0 * (size as f64).ln()).sqrt(),
+                Threshold::Sure => sigma * sure(&magnitudes, sigma)
+            };
+            for value in row.iter_mut()
+            {
+                let magnitude = value.norm();
+                *value = match shrinkage
+                {
+                    _ if magnitude <= lambda => Complex::new(0.0, 0.0),
+                    Shrinkage::Soft => *value * ((magnitude - lambda) / magnitude),
+                    Shrinkage::Hard => *value
+                };
+            }
+        }
+
+        let mean = input.iter().sum::<f64>() / size as f64;
+        let output = CwtResult::complex(coefficients, scales.clone(), self.wavelet.parameters(), size, 1, true).icwt().unwrap_or_default();
+        Ok(output.into_iter().map(|value| value + mean).collect())
+    }
+}
+
+/// Threshold relative to sigma which minimizes SURE of complex soft shrinkage, up to the universal threshold.
+///
+/// For magnitudes r normalized by sigma, the risk of the threshold t is Σ min(r, t)² + 2 Σ_{r > t} (2 - t / r) - 2N,
+/// whose minimum is at one of the magnitudes or at the universal threshold.
+fn sure(magnitudes : &[f64], sigma : f64) -> f64
+{
+    let num = magnitudes.len();
+    let universal = (2.0 * (num as f64).ln()).sqrt();
+    let normalized : Vec<f64> = magnitudes.iter().map(|magnitude| magnitude / sigma).collect();
+
+    //Sum of 1 / r above each index, so that the risk of every candidate is computed in one pass.
+    let mut inverse = vec![0.0; num + 1];
+    for k in (0 .. num).rev() { inverse[k] = inverse[k + 1] + if normalized[k] > 0.0 { 1.0 / normalized[k] } else { 0.0 }; }
+
+    let (mut best, mut minimum) = (universal, f64::INFINITY);
+    let mut below = 0.0;
+    for k in 0 ..= num
+    {
+        //Magnitudes before index k are below t, and the search ends at the universal threshold.
+        let t = if k < num && normalized[k] < universal { normalized[k] } else { universal };
+        let above = (num - k) as f64;
+        let risk = below + above * t * t + 2.0 * (2.0 * above - t * inverse[k]) - 2.0 * num as f64;
+        if risk < minimum { (best, minimum) = (t, risk); }
+        if t == universal { break; }
+        below += normalized[k] * normalized[k];
+    }
+    best
+}
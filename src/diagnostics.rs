@@ -0,0 +1,68 @@
+use crate::*;
+
+/// Problem of a configuration which does not stop the transform, but makes part of its output meaningless.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Warning
+{
+    /// Bandwidth of the wavelet is not a positive number.
+    InvalidBandwidth(f64),
+    /// Gain of the wavelet at 0 Hz relative to its peak is above 1%, so the mean of the signal leaks into every scale.
+    NotAdmissible { dc_gain : f64 },
+    /// Gain of the daughter wavelet at the Nyquist frequency relative to its peak is above 1%, so the band of the scale is cut and folds back.
+    NearNyquist { index : usize, frequency : f64, nyquist_gain : f64 },
+    /// Cone of influence of the scale from both edges covers the whole signal, so every coefficient is affected by the edges.
+    LongerThanSignal { index : usize, frequency : f64, support : f64 },
+    /// Signal holds less than 2 periods of the center frequency of the scale.
+    FewCycles { index : usize, frequency : f64, cycles : f64 }
+}
+impl core::fmt::Display for Warning
+{
+    fn fmt(& self, f : &mut core::fmt::Formatter<'_>) -> core::fmt::Result
+    {
+        match self
+        {
+            Warning::InvalidBandwidth(bandwidth) => write!(f, "Bandwidth of the wavelet must be a positive number, not {}.", bandwidth),
+            Warning::NotAdmissible { dc_gain } => write!(f, "Wavelet is not admissible, as its gain at 0 Hz is {} of its peak.", dc_gain),
+            Warning::NearNyquist { index, frequency, nyquist_gain } => write!(f, "Scale {} ({} Hz) has a gain of {} of its peak at the Nyquist frequency, so it aliases.", index, frequency, nyquist_gain),
+            Warning::LongerThanSignal { index, frequency, support } => write!(f, "Scale {} ({} Hz) is affected by the edges over {} samples, which is longer than the signal.", index, frequency, support),
+            Warning::FewCycles { index, frequency, cycles } => write!(f, "Signal holds only {} periods of scale {} ({} Hz).", cycles, index, frequency)
+        }
+    }
+}
+
+/// Checks a configuration before running the transform, and returns what makes part of its output meaningless.
+///
+/// The scales are checked one by one, so a warning names the index and the center frequency of its scale.
+///
+/// # Arguments
+/// wavelet     - Wavelet of the transform
+///
+/// scales      - Scales object
+///
+/// signal_len  - Number of samples of the signals to transform
+pub fn validate(wavelet : &Wavelet, scales : &Scales, signal_len : usize) -> Vec<Warning>
+{
+    let mut warnings = vec![];
+    match wavelet.validate()
+    {
+        Err(FastCwtError::InvalidBandwidth(bandwidth)) => return vec![Warning::InvalidBandwidth(bandwidth)],
+        Err(FastCwtError::NotAdmissible { dc_gain }) => warnings.push(Warning::NotAdmissible { dc_gain }),
+        _ => {}
+    }
+
+    //Gaussian spectrum of the mother wavelet relative to its peak, where the Nyquist frequency is at u = scale / 2.
+    let sigma = 1.0 / (2.0 * core::f64::consts::PI * wavelet.fb);
+    for (index, (scale, frequency)) in scales.iter().enumerate()
+    {
+        let nyquist_gain = (- ((scale / 2.0 - 1.0) / sigma).powi(2) / 2.0).exp();
+        if nyquist_gain > 0.01 { warnings.push(Warning::NearNyquist { index, frequency, nyquist_gain }); }
+
+        //e-folding time of the power at an edge, as in CwtResult::coi().
+        let support = 2.0 * core::f64::consts::SQRT_2 * wavelet.fb * scale;
+        if support >= signal_len as f64 { warnings.push(Warning::LongerThanSignal { index, frequency, support }); }
+
+        let cycles = signal_len as f64 / scale;
+        if cycles < 2.0 { warnings.push(Warning::FewCycles { index, frequency, cycles }); }
+    }
+    warnings
+}
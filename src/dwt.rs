@@ -0,0 +1,342 @@
+//! Discrete wavelet transforms with orthonormal filters.
+//!
+//! The maximal overlap DWT (MODWT) is not decimated, so every level keeps one coefficient per input sample like the CWT,
+//! and it does not depend on where the signal starts. The wavelet packet transform is decimated and splits both halves of every band,
+//! so its nodes form a full binary tree to pick a basis from. The signal is treated as periodic, like the FFT of the CWT.
+use crate::*;
+
+/// Orthonormal wavelet filters of the discrete transforms.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DiscreteWavelet
+{
+    /// Haar wavelet. (length 2)
+    Haar,
+    /// Daubechies extremal phase wavelet with 2 vanishing moments. (length 4)
+    D4,
+    /// Daubechies extremal phase wavelet with 3 vanishing moments. (length 6)
+    D6,
+    /// Daubechies extremal phase wavelet with 4 vanishing moments. (length 8)
+    D8,
+    /// Daubechies least asymmetric wavelet with 4 vanishing moments, also known as symlet 4. (length 8)
+    LA8
+}
+impl DiscreteWavelet
+{
+    /// Scaling (low-pass) filter, which sums to sqrt(2) and has unit energy.
+    pub fn scaling_filter(& self) -> &'static [f64]
+    {
+        match self
+        {
+            DiscreteWavelet::Haar => &[core::f64::consts::FRAC_1_SQRT_2, core::f64::consts::FRAC_1_SQRT_2],
+            DiscreteWavelet::D4 => &[0.4829629131445341, 0.8365163037378079, 0.2241438680420134, -0.1294095225512604],
+            DiscreteWavelet::D6 => &[0.3326705529500826, 0.8068915093110925, 0.4598775021184915, -0.1350110200102546, -0.0854412738820267, 0.0352262918857095],
+            DiscreteWavelet::D8 => &[0.2303778133088964, 0.7148465705529154, 0.6308807679298587, -0.0279837694168599, -0.1870348117190931, 0.0308413818355607, 0.0328830116668852, -0.0105974017850690],
+            DiscreteWavelet::LA8 => &[-0.07576571478927333, -0.02963552764599851, 0.49761866763201545, 0.8037387518059161, 0.29785779560527736, -0.09921954357684722, -0.012603967262037833, 0.0322231006040427]
+        }
+    }
+    /// Wavelet (high-pass) filter, which is the quadrature mirror of the scaling filter. (h[l] = (-1)^l g[L - 1 - l])
+    pub fn wavelet_filter(& self) -> Vec<f64>
+    {
+        let scaling = self.scaling_filter();
+        let length = scaling.len();
+        (0 .. length).map(|l| if l % 2 == 0 { scaling[length - 1 - l] } else { - scaling[length - 1 - l] }).collect()
+    }
+}
+
+/// Result of the MODWT, with the detail coefficients of every level and the smooth coefficients of the last level.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "ModwtData"))]
+#[derive(Clone, PartialEq, Debug)]
+pub struct Modwt
+{
+    details : Vec<Vec<f64>>,
+    smooth : Vec<f64>,
+    wavelet : DiscreteWavelet
+}
+/// Fields of a deserialized Modwt, which are checked before they become one.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct ModwtData
+{
+    details : Vec<Vec<f64>>,
+    smooth : Vec<f64>,
+    wavelet : DiscreteWavelet
+}
+#[cfg(feature = "serde")]
+impl TryFrom<ModwtData> for Modwt
+{
+    type Error = FastCwtError;
+    fn try_from(data : ModwtData) -> Result<Modwt, FastCwtError>
+    {
+        //Every level has as many samples as the input.
+        if let Some(detail) = data.details.iter().find(|detail| detail.len() != data.smooth.len()) { return Err(FastCwtError::ResultLength { expected : data.smooth.len(), len : detail.len() }); }
+        Ok(Modwt { details : data.details, smooth : data.smooth, wavelet : data.wavelet })
+    }
+}
+impl Modwt
+{
+    /// Number of levels.
+    pub fn levels(& self) -> usize { self.details.len() }
+    /// Number of samples of every level, which is the length of the input.
+    pub fn num_samples(& self) -> usize { self.smooth.len() }
+    /// Filters the transform was computed with.
+    pub fn wavelet(& self) -> DiscreteWavelet { self.wavelet }
+    /// Detail (wavelet) coefficients of a level, starting from 1, which cover fs / 2^(level + 1) to fs / 2^level.
+    pub fn detail(& self, level : usize) -> &[f64]
+    {
+        assert!(level >= 1 && level <= self.levels(), "Level must be from 1 to the number of levels.");
+        &self.details[level - 1]
+    }
+    /// Smooth (scaling) coefficients of the last level, which cover 0 to fs / 2^(levels + 1).
+    pub fn smooth(& self) -> &[f64] { &self.smooth }
+    /// Wavelet variance of every level from 1, which is the mean of the squared detail coefficients.
+    ///
+    /// Together with the mean square of the smooth coefficients, it adds up to the mean square of the input.
+    pub fn variance(& self) -> Vec<f64> { self.details.iter().map(|detail| detail.iter().map(|value| value * value).sum::<f64>() / detail.len().max(1) as f64).collect() }
+    /// Inverse MODWT, which reconstructs the input exactly.
+    pub fn imodwt(& self) -> Vec<f64>
+    {
+        let (scaling, wavelet) = filters(self.wavelet);
+        let size = self.num_samples();
+        let mut smooth = self.smooth.clone();
+        let mut next = vec![0.0; size];
+
+        for level in (1 ..= self.levels()).rev()
+        {
+            let detail = &self.details[level - 1];
+            let stride = 1 << (level - 1);
+            for (t, out) in next.iter_mut().enumerate()
+            {
+                *out = scaling.iter().zip(&wavelet).enumerate().map(|(l, (g, h))|
+                {
+                    let index = (t + stride * l) % size;
+                    h * detail[index] + g * smooth[index]
+                }).sum();
+            }
+            core::mem::swap(&mut smooth, &mut next);
+        }
+        smooth
+    }
+}
+
+/// Maximal overlap discrete wavelet transform.
+///
+/// # Arguments
+/// input     - Input data in vector format
+///
+/// wavelet   - Filters of the transform
+///
+/// levels    - Number of levels, each of which halves the frequency band
+pub fn modwt(input : &[f64], wavelet : DiscreteWavelet, levels : usize) -> Modwt
+{
+    assert!(levels > 0, "There must be at least one level.");
+
+    let (scaling, filter) = filters(wavelet);
+    let size = input.len();
+    let mut smooth = input.to_vec();
+    let mut details = Vec::with_capacity(levels);
+
+    for level in 1 ..= levels
+    {
+        //Filters are upsampled by 2^(level - 1) instead of decimating the signal.
+        let stride = 1 << (level - 1);
+        let (mut detail, mut next) = (vec![0.0; size], vec![0.0; size]);
+        for t in 0 .. size
+        {
+            for (l, (g, h)) in scaling.iter().zip(&filter).enumerate()
+            {
+                let value = smooth[(t + size - (stride * l) % size) % size];
+                detail[t] += h * value;
+                next[t] += g * value;
+            }
+        }
+        details.push(detail);
+        smooth = next;
+    }
+    Modwt { details, smooth, wavelet }
+}
+
+/// Result of the wavelet packet transform, a full binary tree of nodes down to the last level.
+///
+/// Node (level, index) is split into (level + 1, 2 × index) by the scaling filter and (level + 1, 2 × index + 1) by the wavelet filter.
+/// Node (0, 0) is the input.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "WaveletPacketData"))]
+#[derive(Clone, PartialEq, Debug)]
+pub struct WaveletPacket
+{
+    nodes : Vec<Vec<f64>>,
+    levels : usize,
+    wavelet : DiscreteWavelet
+}
+/// Fields of a deserialized WaveletPacket, which are checked before they become one.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct WaveletPacketData
+{
+    nodes : Vec<Vec<f64>>,
+    levels : usize,
+    wavelet : DiscreteWavelet
+}
+#[cfg(feature = "serde")]
+impl TryFrom<WaveletPacketData> for WaveletPacket
+{
+    type Error = FastCwtError;
+    fn try_from(data : WaveletPacketData) -> Result<WaveletPacket, FastCwtError>
+    {
+        //Same bounds as the input of wavelet_packet(), which keep the tree size from overflowing.
+        let len = data.nodes.first().map_or(0, Vec::len);
+        if len == 0 || data.levels > len.ilog2() as usize || len % (1 << data.levels) != 0 { return Err(FastCwtError::InvalidLevels { levels : data.levels, len }); }
+        if data.nodes.len() != (2 << data.levels) - 1 { return Err(FastCwtError::ResultLength { expected : (2 << data.levels) - 1, len : data.nodes.len() }); }
+        for level in 0 ..= data.levels
+        {
+            let nodes = &data.nodes[(1 << level) - 1 .. (2 << level) - 1];
+            if let Some(node) = nodes.iter().find(|node| node.len() != len >> level) { return Err(FastCwtError::ResultLength { expected : len >> level, len : node.len() }); }
+        }
+        Ok(WaveletPacket { nodes : data.nodes, levels : data.levels, wavelet : data.wavelet })
+    }
+}
+impl WaveletPacket
+{
+    /// Number of levels below the input.
+    pub fn levels(& self) -> usize { self.levels }
+    /// Filters the transform was computed with.
+    pub fn wavelet(& self) -> DiscreteWavelet { self.wavelet }
+    /// Coefficients of a node, which has input length / 2^level samples.
+    pub fn node(& self, level : usize, index : usize) -> &[f64]
+    {
+        assert!(level <= self.levels && index < 1 << level, "Node must be in the tree.");
+        &self.nodes[(1 << level) - 1 + index]
+    }
+    /// Basis with the lowest Shannon entropy cost, -sum(c^2 × ln(c^2)), as (level, index) nodes from left to right in the tree.
+    ///
+    /// A node is kept if its cost is not more than the total cost of the best bases of its children (Coifman-Wickerhauser).
+    pub fn best_basis(& self) -> Vec<(usize, usize)>
+    {
+        let cost = |node : &[f64]| -> f64 { node.iter().map(|value| value * value).filter(|energy| *energy > 0.0).map(|energy| - energy * energy.ln()).sum() };
+
+        //Best cost and whether the node is kept, from the last level up.
+        let mut best : Vec<(f64, bool)> = self.nodes.iter().map(|node| (cost(node), true)).collect();
+        for level in (0 .. self.levels).rev()
+        {
+            for index in 0 .. 1 << level
+            {
+                let position = (1 << level) - 1 + index;
+                let children = best[2 * position + 1].0 + best[2 * position + 2].0;
+                if children < best[position].0 { best[position] = (children, false); }
+            }
+        }
+
+        let mut basis = vec![];
+        let mut pending = vec![(0, 0)];
+        while let Some((level, index)) = pending.pop()
+        {
+            if best[(1 << level) - 1 + index].1 { basis.push((level, index)); }
+            else { pending.extend([(level + 1, 2 * index + 1), (level + 1, 2 * index)]); }
+        }
+        basis
+    }
+    /// Reconstructs the input from the nodes of a basis, like the one of best_basis(), which must cover the tree exactly once.
+    pub fn reconstruct(& self, basis : &[(usize, usize)]) -> Vec<f64>
+    {
+        let (scaling, wavelet) = (self.wavelet.scaling_filter(), self.wavelet.wavelet_filter());
+        let mut nodes : Vec<Option<Vec<f64>>> = vec![None; self.nodes.len()];
+        for (level, index) in basis { nodes[(1 << level) - 1 + index] = Some(self.node(*level, *index).to_vec()); }
+
+        for level in (0 .. self.levels).rev()
+        {
+            for index in 0 .. 1 << level
+            {
+                let position = (1 << level) - 1 + index;
+                if nodes[position].is_some() { continue; }
+                if let (Some(low), Some(high)) = (nodes[2 * position + 1].take(), nodes[2 * position + 2].take()) { nodes[position] = Some(synthesize(&low, &high, scaling, &wavelet)); }
+            }
+        }
+        nodes[0].take().expect("Basis must cover the tree exactly once.")
+    }
+}
+
+/// Wavelet packet transform.
+///
+/// # Arguments
+/// input     - Input data in vector format, whose length must be a multiple of 2^levels
+///
+/// wavelet   - Filters of the transform
+///
+/// levels    - Number of levels, each of which splits every band in two, up to log2 of the input length
+pub fn wavelet_packet(input : &[f64], wavelet : DiscreteWavelet, levels : usize) -> WaveletPacket
+{
+    //Bounding the levels by the input first keeps 2^levels from overflowing, and the tree within twice the input.
+    assert!(!input.is_empty() && levels <= input.len().ilog2() as usize && input.len() % (1 << levels) == 0, "Input must not be empty, and its length must be a multiple of 2^levels.");
+
+    let (scaling, filter) = (wavelet.scaling_filter(), wavelet.wavelet_filter());
+    let mut nodes = Vec::with_capacity((2 << levels) - 1);
+    nodes.push(input.to_vec());
+    for position in 0 .. (1 << levels) - 1
+    {
+        let (low, high) = analyze(&nodes[position], scaling, &filter);
+        nodes.push(low);
+        nodes.push(high);
+    }
+    WaveletPacket { nodes, levels, wavelet }
+}
+
+/// One level of the periodic decimated DWT, which splits the input into the scaling and wavelet halves.
+fn analyze(input : &[f64], scaling : &[f64], wavelet : &[f64]) -> (Vec<f64>, Vec<f64>)
+{
+    let size = input.len();
+    let half = size / 2;
+    let (mut low, mut high) = (vec![0.0; half], vec![0.0; half]);
+    for k in 0 .. half
+    {
+        for (l, (g, h)) in scaling.iter().zip(wavelet).enumerate()
+        {
+            let value = input[(2 * k + l) % size];
+            low[k] += g * value;
+            high[k] += h * value;
+        }
+    }
+    (low, high)
+}
+
+/// Inverse of analyze(), which is its transpose because the filters are orthonormal.
+fn synthesize(low : &[f64], high : &[f64], scaling : &[f64], wavelet : &[f64]) -> Vec<f64>
+{
+    let size = 2 * low.len();
+    let mut output = vec![0.0; size];
+    for (k, (a, d)) in low.iter().zip(high).enumerate()
+    {
+        for (l, (g, h)) in scaling.iter().zip(wavelet).enumerate() { output[(2 * k + l) % size] += g * a + h * d; }
+    }
+    output
+}
+
+/// Scaling and wavelet filters rescaled by 1 / sqrt(2) for the MODWT.
+fn filters(wavelet : DiscreteWavelet) -> (Vec<f64>, Vec<f64>)
+{
+    let rescale = |filter : &[f64]| filter.iter().map(|value| value * core::f64::consts::FRAC_1_SQRT_2).collect();
+    (rescale(wavelet.scaling_filter()), rescale(&wavelet.wavelet_filter()))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn modwt_reconstructs_input()
+    {
+        let input : Vec<f64> = (0 .. 1000).map(|n| (n as f64 * 0.05).sin() + 0.3 * ((n * 7919 % 1000) as f64 / 500.0 - 1.0)).collect();
+        for wavelet in [DiscreteWavelet::Haar, DiscreteWavelet::D4, DiscreteWavelet::D6, DiscreteWavelet::D8, DiscreteWavelet::LA8]
+        {
+            let transform = modwt(&input, wavelet, 5);
+            let output = transform.imodwt();
+            assert_eq!(output.len(), input.len());
+            for (output, input) in output.iter().zip(&input) { assert!((output - input).abs() < 1e-9, "{:?} does not reconstruct the input.", wavelet); }
+
+            //Energy is preserved across the levels.
+            let mean_square = input.iter().map(|value| value * value).sum::<f64>() / input.len() as f64;
+            let smooth = transform.smooth().iter().map(|value| value * value).sum::<f64>() / input.len() as f64;
+            assert!((transform.variance().iter().sum::<f64>() + smooth - mean_square).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! Epoched analysis of event-related signals, like EEG trials: spectral perturbation and inter-trial coherence.
+//!
+//! The continuous signal is transformed once and the epochs are cut from its coefficients,
+//! so they have no edge effects of their own as long as the signal extends beyond them.
+use crate::*;
+use core::ops::Range;
+
+impl FastCWT
+{
+    /// Transforms the input and cuts an epoch of the coefficients around every event.
+    ///
+    /// The coefficients are computed in complex without a hop regardless of the settings.
+    ///
+    /// # Arguments
+    /// input     - Continuous input data
+    ///
+    /// events    - Sample index of every event
+    ///
+    /// window    - Samples of an epoch relative to its event, like -500 .. 1000, which must be within the input for every event
+    ///
+    /// scales    - Scales object
+    ///
+    /// Returns the result of every epoch, whose first sample is window.start.
+    pub fn epochs(&mut self, input : &[f64], events : &[usize], window : Range<isize>, scales : &Scales) -> Vec<CwtResult>
+    {
+        assert!(window.start < window.end, "Window must not be empty.");
+        assert!(events.iter().all(|event| *event as isize + window.start >= 0 && *event as isize + window.end <= input.len() as isize), "Every epoch must be within the input.");
+
+        let size = input.len();
+        let mut coefficients = vec![Complex::new(0.0, 0.0); scales.num_scales * size];
+        self.transform(input, scales, &mut coefficients);
+
+        let length = (window.end - window.start) as usize;
+        events.iter().map(|event|
+        {
+            let start = (*event as isize + window.start) as usize;
+            let epoch = coefficients.chunks(size.max(1)).flat_map(|row| row[start .. start + length].iter().copied()).collect();
+            CwtResult::complex(epoch, scales.clone(), self.wavelet.parameters(), length, 1, self.use_normalization)
+        }).collect()
+    }
+}
+
+/// Event-related spectral perturbation, which is the power averaged over the epochs in dB relative to the baseline. (num_scales × samples, row-major)
+///
+/// The averaged power is normalized like CwtResult::normalize_baseline() with Baseline::Decibel.
+///
+/// # Arguments
+/// epochs    - Results of the epochs, which must have the same scales and length
+///
+/// baseline  - Samples of the epochs to use as the baseline, like 0 .. 500 for the 500 samples before the events of the window -500 .. 1000
+pub fn ersp(epochs : &[CwtResult], baseline : Range<usize>) -> Vec<f64>
+{
+    let Some(first) = epochs.first() else { return vec![]; };
+    let (num_scales, num_samples) = (first.num_scales(), first.num_samples());
+    assert!(epochs.iter().all(|epoch| epoch.num_scales() == num_scales && epoch.num_samples() == num_samples), "Every epoch must have the same scales and length.");
+
+    let mut power = vec![0.0; num_scales * num_samples];
+    for epoch in epochs
+    {
+        for (sum, value) in power.iter_mut().zip(epoch.power()) { *sum += value / epochs.len() as f64; }
+    }
+    CwtResult::real(power, OutputMode::Power, first.scales().clone(), first.wavelet().parameters(), num_samples, 1, first.is_normalized()).normalize_baseline(baseline, Baseline::Decibel)
+}
+
+/// Inter-trial phase coherence, which is the magnitude of the mean of the unit phase vectors over the epochs. (0.0 ..= 1.0, num_scales × samples, row-major)
+///
+/// It is 1 where the phase is locked to the events in every epoch, and about 1 / √(number of epochs) for random phases.
+///
+/// # Arguments
+/// epochs    - Results of the epochs, which must be complex and have the same scales and length
+pub fn itc(epochs : &[CwtResult]) -> Vec<f64>
+{
+    let Some(first) = epochs.first() else { return vec![]; };
+    let length = first.num_scales() * first.num_samples();
+    assert!(epochs.iter().all(|epoch| epoch.num_scales() == first.num_scales() && epoch.num_samples() == first.num_samples()), "Every epoch must have the same scales and length.");
+
+    let mut sums = vec![Complex::new(0.0, 0.0); length];
+    for epoch in epochs
+    {
+        let coefficients = epoch.complex_coefficients().expect("Epochs must have complex coefficients.");
+        for (sum, value) in sums.iter_mut().zip(coefficients)
+        {
+            let norm = value.norm();
+            if norm > 0.0 { *sum += value / norm; }
+        }
+    }
+    sums.iter().map(|sum| sum.norm() / epochs.len() as f64).collect()
+}
@@ -0,0 +1,92 @@
+/// Errors of the fastcwt configuration.
+#[derive(Debug, thiserror::Error)]
+pub enum FastCwtError
+{
+    /// Hop size is 0.
+    #[error("Hop size must be at least 1.")]
+    InvalidHop,
+    /// Number of threads is 0.
+    #[error("Number of threads must be at least 1.")]
+    InvalidThreads,
+    /// Thread pool could not be created.
+    #[cfg(feature = "parallel")]
+    #[error("Thread pool could not be created: {0}")]
+    ThreadPool(#[source] rayon::ThreadPoolBuildError),
+    /// GPU device could not be opened.
+    #[cfg(feature = "gpu")]
+    #[error("GPU device could not be opened: {0}")]
+    Gpu(String),
+    /// Transform was cancelled by its cancellation token.
+    #[error("Transform was cancelled.")]
+    Cancelled,
+    /// Frequency is above the Nyquist frequency of the sample rate.
+    #[error("Frequency {frequency} Hz is higher than the Nyquist frequency ({nyquist} Hz).")]
+    AboveNyquist { frequency : f64, nyquist : f64 },
+    /// Sample rate is not a positive number.
+    #[error("Sample rate must be a positive number, not {0}.")]
+    InvalidSampleRate(f64),
+    /// Frequency range is not 0 < f0 ≤ f1.
+    #[error("Frequency range must be 0 < f0 <= f1, not {f0} to {f1} Hz.")]
+    InvalidFrequencyRange { f0 : f64, f1 : f64 },
+    /// Frequency is not a positive number.
+    #[error("Frequencies must be positive, not {0}.")]
+    InvalidFrequency(f64),
+    /// Scale is below 2, which is the Nyquist frequency.
+    #[error("Scales cannot be lower than 2, which is the Nyquist frequency, not {0}.")]
+    InvalidScale(f64),
+    /// Number of voices per octave is 0.
+    #[error("There must be at least one voice per octave.")]
+    InvalidVoices,
+    /// Cents or A4 frequency of a musical scale is not positive.
+    #[error("Cents and A4 frequency must be positive, not {cents} cents and {a4} Hz.")]
+    InvalidTuning { cents : f64, a4 : f64 },
+    /// Number of scales does not match the scales, e.g. of deserialized Scales.
+    #[error("Number of scales ({num_scales}) does not match the {len} scales.")]
+    ScalesLength { num_scales : usize, len : usize },
+    /// Number of samples to transform is longer than the input.
+    #[error("Number of samples ({num}) cannot be longer than the input ({len}).")]
+    InputLength { num : usize, len : usize },
+    /// Output buffer does not have the length of the coefficients.
+    #[error("Output buffer must hold {expected} values, not {len}.")]
+    OutputLength { expected : usize, len : usize },
+    /// Fraction of the Tukey taper is not between 0 and 1.
+    #[error("Taper fraction must be between 0 and 1, not {0}.")]
+    InvalidTaper(f64),
+    /// Cutoff of the high-pass detrending is not between 0 and the Nyquist frequency (0.5).
+    #[error("Cutoff must be between 0 and the Nyquist frequency (0.5), not {0}.")]
+    InvalidCutoff(f64),
+    /// Number of channels is 0, or the interleaved input is not a whole number of frames.
+    #[error("Interleaved input of {len} samples is not a whole number of frames of {channels} channels.")]
+    InvalidChannels { channels : usize, len : usize },
+    /// Sample rate of the input is not the sample rate of the scales.
+    #[error("Sample rate of the input ({input} Hz) is not the sample rate of the scales ({scales} Hz).")]
+    SampleRate { scales : f64, input : f64 },
+    /// Coefficients of a deserialized result do not have the length of its shape.
+    #[error("Result must hold {expected} coefficients, not {len}.")]
+    ResultLength { expected : usize, len : usize },
+    /// Coefficients of a deserialized result are complex in a real output mode, or real in OutputMode::Complex.
+    #[error("Coefficients of the result do not match its output mode ({0:?}).")]
+    ResultMode(crate::OutputMode),
+    /// Input of a deserialized wavelet packet cannot be split into its number of levels.
+    #[error("Input of {len} samples cannot be split into {levels} levels.")]
+    InvalidLevels { levels : usize, len : usize },
+    /// Input has a NaN sample while missing samples are errors.
+    #[error("Input has a NaN sample at index {index}.")]
+    MissingSample { index : usize },
+    /// Bandwidth of the wavelet is not a positive number.
+    #[error("Bandwidth of the wavelet must be a positive number, not {0}.")]
+    InvalidBandwidth(f64),
+    /// Gain of the wavelet at 0 Hz relative to its peak is too large for a meaningful transform.
+    #[error("Wavelet is not admissible, as its gain at 0 Hz is {dc_gain} of its peak. Use a larger bandwidth.")]
+    NotAdmissible { dc_gain : f64 },
+    /// Transform is not normalized by the FFT size, which the result needs to be in the units of the input.
+    #[error("Transform must be normalized by the FFT size.")]
+    NotNormalized,
+    /// There are fewer than 2 RR intervals, or an interval is not a positive number.
+    #[error("There must be at least 2 RR intervals, which must be positive.")]
+    InvalidIntervals,
+    /// WAV file could not be read.
+    #[cfg(feature = "hound")]
+    #[error("WAV file could not be read: {0}")]
+    Wav(#[source] hound::Error)
+}
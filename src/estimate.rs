@@ -0,0 +1,53 @@
+use crate::*;
+
+/// Expected resources of a transform, to check a configuration before running it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ResourceEstimate
+{
+    /// FFT size the input is padded to.
+    pub fft_length : usize,
+    /// Size of the coefficients in bytes.
+    pub output_bytes : usize,
+    /// Peak memory of the transform in bytes, which is the coefficients and the FFT buffers of every thread, without the input.
+    pub peak_bytes : usize,
+    /// Approximate number of floating point operations, counting 5 × n × log2(n) for an FFT of size n.
+    pub flops : f64
+}
+impl ResourceEstimate
+{
+    /// Approximate time of the transform in seconds, for a machine with the throughput in floating point operations per second.
+    pub fn seconds(& self, flops_per_second : f64) -> f64 { self.flops / flops_per_second }
+}
+
+impl FastCWT
+{
+    /// Estimates the memory and the work of cwt() without running it.
+    ///
+    /// # Arguments
+    /// num_samples - Number of samples to transform
+    ///
+    /// scales      - Scales object
+    pub fn estimate(& self, num_samples : usize, scales : &Scales) -> ResourceEstimate
+    {
+        if num_samples == 0 { return ResourceEstimate { fft_length : 0, output_bytes : 0, peak_bytes : 0, flops : 0.0 }; }
+
+        let newsize = self.fft_size(num_samples);
+        let num_scales = scales.num_scales;
+        let value = match self.precision { Precision::Double => 8, Precision::Single => 4 };
+
+        let element = if self.output_mode == OutputMode::Complex { 16 } else { 8 };
+        let output_bytes = num_scales * num_samples.div_ceil(self.hop) * element;
+
+        //Spectrum and mother wavelet in f64 and in the precision of the convolutions.
+        let shared = newsize * (16 + 8 + 3 * value);
+        //Buffer and scratch of the inverse FFT, the gains of the daughter wavelet, and the widened row in single precision.
+        let widened = if self.precision == Precision::Single { 16 * num_samples } else { 0 };
+        let thread = newsize * (4 * value + value / 2) + widened;
+        let peak_bytes = output_bytes + shared + self.threads().min(num_scales.max(1)) * thread;
+
+        let fft = if newsize > 1 { 5.0 * newsize as f64 * (newsize as f64).log2() } else { 0.0 };
+        //Every scale multiplies up to half of the spectrum and normalizes or converts its row.
+        let flops = fft * (num_scales + 1) as f64 + num_scales as f64 * (newsize as f64 + 2.0 * num_samples as f64);
+        ResourceEstimate { fft_length : newsize, output_bytes, peak_bytes, flops }
+    }
+}
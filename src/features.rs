@@ -0,0 +1,86 @@
+//! Spectral descriptors of every column of a scalogram, like the spectral features of an STFT frame.
+//!
+//! The power of each scale is weighted by its width in Hz, so that a descriptor approximates the same integral
+//! over frequency whatever the spacing of the scales is. Coefficients masked with NaN are left out,
+//! and a column without any power is NaN.
+use crate::*;
+
+/// Spectral centroid in Hz of every column, which is the mean frequency weighted by the power.
+pub fn centroid(result : &CwtResult) -> Vec<f64>
+{
+    columns(result, |bins| moment(bins, 0.0, 1))
+}
+
+/// Spectral spread in Hz of every column, which is the standard deviation of the frequency around the centroid.
+pub fn spread(result : &CwtResult) -> Vec<f64>
+{
+    columns(result, |bins| moment(bins, moment(bins, 0.0, 1), 2).sqrt())
+}
+
+/// Spectral flatness of every column, which is the geometric mean of the power divided by its arithmetic mean.
+///
+/// It is 1 if every scale has the same power, and approaches 0 for a pure tone.
+pub fn flatness(result : &CwtResult) -> Vec<f64>
+{
+    columns(result, |bins|
+    {
+        let (weights, logs, powers) = bins.iter().fold((0.0, 0.0, 0.0), |(weights, logs, powers), (_, weight, power)| (weights + weight, logs + weight * power.ln(), powers + weight * power));
+        if powers > 0.0 { (logs / weights).exp() / (powers / weights) } else { f64::NAN }
+    })
+}
+
+/// Spectral rolloff in Hz of every column, which is the lowest frequency below which the fraction of the power lies.
+///
+/// The frequency is interpolated linearly between the scales.
+///
+/// # Arguments
+/// result    - Result of the transform
+///
+/// fraction  - Fraction of the power, like 0.85
+pub fn rolloff(result : &CwtResult, fraction : f64) -> Vec<f64>
+{
+    assert!((0.0 ..= 1.0).contains(&fraction), "Fraction must be between 0 and 1.");
+
+    columns(result, |bins|
+    {
+        let total : f64 = bins.iter().map(|(_, weight, power)| weight * power).sum();
+        if total <= 0.0 { return f64::NAN; }
+        let (mut sum, mut previous) = (0.0, bins[0].0);
+        for (frequency, weight, power) in bins
+        {
+            let next = sum + weight * power;
+            if next >= fraction * total
+            {
+                let position = if next > sum { (fraction * total - sum) / (next - sum) } else { 1.0 };
+                return previous + (frequency - previous) * position;
+            }
+            (sum, previous) = (next, *frequency);
+        }
+        previous
+    })
+}
+
+/// Applies f to the (frequency, weight, power) of the scales of every column, in ascending order of frequency.
+fn columns(result : &CwtResult, f : impl Fn(&[(f64, f64, f64)]) -> f64) -> Vec<f64>
+{
+    let (num_scales, num_samples) = (result.num_scales(), result.num_samples());
+    let power = result.power();
+    let weights : Vec<f64> = result.frequencies().iter().zip(result.scales().log_steps()).map(|(frequency, step)| frequency * step).collect();
+    let mut order : Vec<usize> = (0 .. num_scales).collect();
+    order.sort_by(|a, b| result.frequency(*a).total_cmp(&result.frequency(*b)));
+
+    let mut bins = Vec::with_capacity(num_scales);
+    (0 .. num_samples).map(|column|
+    {
+        bins.clear();
+        bins.extend(order.iter().map(|row| (result.frequency(*row), weights[*row], power[row * num_samples + column])).filter(|(_, _, power)| !power.is_nan()));
+        if bins.is_empty() || bins.iter().all(|(_, _, power)| *power <= 0.0) { f64::NAN } else { f(&bins) }
+    }).collect()
+}
+
+/// Central moment of the frequency of the given order around the center, weighted by the power.
+fn moment(bins : &[(f64, f64, f64)], center : f64, order : i32) -> f64
+{
+    let (sum, total) = bins.iter().fold((0.0, 0.0), |(sum, total), (frequency, weight, power)| (sum + (frequency - center).powi(order) * weight * power, total + weight * power));
+    sum / total
+}
@@ -0,0 +1,97 @@
+//! C interface of the transform.
+//!
+//! include/fastcwt.h is generated from this module with cbindgen. (cbindgen --config cbindgen.toml --output include/fastcwt.h)
+//! Build the library for C with cargo rustc --release --features ffi --crate-type staticlib (or cdylib).
+#![allow(unsafe_code)]
+use crate::*;
+
+/// Scale types of FastCwtScales.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub enum FastCwtScaleType
+{
+    /// Linear scale.
+    Linear,
+    /// Logarithmic scale.
+    Log,
+    /// Linear for frequency.
+    LinFreq,
+    /// Mel scale.
+    Mel,
+    /// ERB-rate scale.
+    Erb
+}
+
+/// Scales of fastcwt_cwt(), which are the arguments of Scales::create().
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FastCwtScales
+{
+    /// Scale type.
+    pub scale_type : FastCwtScaleType,
+    /// Sample rate.
+    pub fs : f64,
+    /// Beginning frequency.
+    pub f0 : f64,
+    /// End frequency.
+    pub f1 : f64,
+    /// Number of scales.
+    pub num_scales : usize
+}
+
+/// Creates a transform with a Morlet wavelet, which must be released with fastcwt_free().
+///
+/// # Arguments
+/// bandwidth - Bandwidth of the Morlet wavelet
+///
+/// normalize - Normalize the coefficients by the FFT size if true
+#[no_mangle]
+pub extern "C" fn fastcwt_create(bandwidth : f64, normalize : bool) -> *mut FastCWT
+{
+    Box::into_raw(Box::new(FastCWT::create(Wavelet::create(bandwidth), normalize)))
+}
+
+/// Transforms num samples and writes num_scales × num complex coefficients, scale by scale, as interleaved real and imaginary parts.
+/// (The layout of std::complex<double>.)
+///
+/// Returns 0 on success, or -1 if a pointer is null or the transform panicked.
+///
+/// # Safety
+/// transform must come from fastcwt_create(), input must hold num values, and output must hold 2 × num_scales × num values.
+#[no_mangle]
+pub unsafe extern "C" fn fastcwt_cwt(transform : *mut FastCWT, input : *const f64, num : usize, scales : FastCwtScales, output : *mut f64) -> i32
+{
+    if transform.is_null() || input.is_null() || output.is_null() { return -1; }
+
+    let transform = &mut *transform;
+    let input = core::slice::from_raw_parts(input, num);
+    let output = core::slice::from_raw_parts_mut(output, 2 * scales.num_scales * num);
+
+    //Unwinding into C is undefined behavior, so panics are returned as an error.
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(||
+    {
+        let scale_type = match scales.scale_type
+        {
+            FastCwtScaleType::Linear => ScaleTypes::Linear,
+            FastCwtScaleType::Log => ScaleTypes::Log,
+            FastCwtScaleType::LinFreq => ScaleTypes::LinFreq,
+            FastCwtScaleType::Mel => ScaleTypes::Mel,
+            FastCwtScaleType::Erb => ScaleTypes::ERB
+        };
+        let scales = Scales::create(scale_type, scales.fs, scales.f0, scales.f1, scales.num_scales);
+        let mut complex = vec![Complex::new(0.0, 0.0); scales.len() * num];
+        transform.transform(input, &scales, &mut complex);
+        for (out, value) in output.chunks_exact_mut(2).zip(&complex) { out.copy_from_slice(&[value.re, value.im]); }
+    }));
+    if result.is_ok() { 0 } else { -1 }
+}
+
+/// Releases a transform from fastcwt_create(). Null is ignored.
+///
+/// # Safety
+/// transform must come from fastcwt_create() and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn fastcwt_free(transform : *mut FastCWT)
+{
+    if !transform.is_null() { drop(Box::from_raw(transform)); }
+}
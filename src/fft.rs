@@ -0,0 +1,135 @@
+//! FFT implementations the transform can run on.
+//!
+//! RustFft is the default and pure Rust. Fftw calls the FFTW library, which is what the original fCWT uses.
+//! Without std, there is no default and a backend must be set before the transform is used.
+//! Any other library can be used by implementing FftBackend and FftPlan.
+use crate::*;
+
+/// Planned in-place FFT of a fixed length and direction. Transforms are not normalized.
+pub trait FftPlan<F> : Send + Sync
+{
+    /// Transforms the buffer in place.
+    ///
+    /// # Arguments
+    /// buffer    - Data of the planned length
+    ///
+    /// scratch   - Working memory of the calling thread, which the plan may resize and reuse between calls
+    fn process(& self, buffer : &mut [Complex<F>], scratch : &mut Vec<Complex<F>>);
+}
+
+/// Plans the FFTs of the transform.
+pub trait FftBackend : Send + Sync
+{
+    /// Plans a double precision FFT, which is the inverse if inverse is true.
+    fn plan_f64(& self, length : usize, inverse : bool) -> Box<dyn FftPlan<f64>>;
+    /// Plans a single precision FFT, which is the inverse if inverse is true.
+    fn plan_f32(& self, length : usize, inverse : bool) -> Box<dyn FftPlan<f32>>;
+}
+
+/// FFT of the rustfft crate.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct RustFft;
+#[cfg(feature = "std")]
+impl FftBackend for RustFft
+{
+    fn plan_f64(& self, length : usize, inverse : bool) -> Box<dyn FftPlan<f64>> { Box::new(RustFftPlan(plan(length, inverse))) }
+    fn plan_f32(& self, length : usize, inverse : bool) -> Box<dyn FftPlan<f32>> { Box::new(RustFftPlan(plan(length, inverse))) }
+}
+
+#[cfg(feature = "std")]
+struct RustFftPlan<F>(alloc::sync::Arc<dyn rustfft::Fft<F>>);
+#[cfg(feature = "std")]
+impl<F : rustfft::FftNum> FftPlan<F> for RustFftPlan<F>
+{
+    fn process(& self, buffer : &mut [Complex<F>], scratch : &mut Vec<Complex<F>>)
+    {
+        scratch.resize(self.0.get_inplace_scratch_len(), Complex::new(F::zero(), F::zero()));
+        self.0.process_with_scratch(buffer, scratch);
+    }
+}
+
+#[cfg(feature = "std")]
+fn plan<F : rustfft::FftNum>(length : usize, inverse : bool) -> alloc::sync::Arc<dyn rustfft::Fft<F>>
+{
+    let mut planner = rustfft::FftPlanner::new();
+    if inverse { planner.plan_fft_inverse(length) } else { planner.plan_fft_forward(length) }
+}
+
+/// Backend of a new transform, which is RustFft with std.
+pub(crate) fn default_backend() -> alloc::sync::Arc<dyn FftBackend>
+{
+    #[cfg(feature = "std")]
+    return alloc::sync::Arc::new(RustFft);
+    #[cfg(not(feature = "std"))]
+    return alloc::sync::Arc::new(Unset);
+}
+
+/// Backend of a new transform without std, which has no FFT until one is set with FastCWT::set_fft_backend().
+#[cfg(not(feature = "std"))]
+struct Unset;
+#[cfg(not(feature = "std"))]
+impl FftBackend for Unset
+{
+    fn plan_f64(& self, _ : usize, _ : bool) -> Box<dyn FftPlan<f64>> { panic!("No FFT backend is set. Set one with FastCWT::set_fft_backend()."); }
+    fn plan_f32(& self, _ : usize, _ : bool) -> Box<dyn FftPlan<f32>> { panic!("No FFT backend is set. Set one with FastCWT::set_fft_backend()."); }
+}
+
+/// FFT of the FFTW library.
+///
+/// Executing an FFTW plan needs exclusive access to it, so every thread plans its own copy on first use and keeps it for later transforms.
+#[cfg(feature = "fftw")]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Fftw;
+#[cfg(feature = "fftw")]
+impl FftBackend for Fftw
+{
+    fn plan_f64(& self, length : usize, inverse : bool) -> Box<dyn FftPlan<f64>> { Box::new(FftwPlan { length, inverse }) }
+    fn plan_f32(& self, length : usize, inverse : bool) -> Box<dyn FftPlan<f32>> { Box::new(FftwPlan { length, inverse }) }
+}
+
+#[cfg(feature = "fftw")]
+struct FftwPlan
+{
+    length : usize,
+    inverse : bool
+}
+
+#[cfg(feature = "fftw")]
+macro_rules! fftw_plan
+{
+    ($float : ty, $complex : ty, $plan : ty, $cache : ident) =>
+    {
+        thread_local!
+        {
+            static $cache : std::cell::RefCell<std::collections::HashMap<(usize, bool), ($plan, fftw::array::AlignedVec<$complex>, fftw::array::AlignedVec<$complex>)>> = Default::default();
+        }
+        impl FftPlan<$float> for FftwPlan
+        {
+            fn process(& self, buffer : &mut [Complex<$float>], _ : &mut Vec<Complex<$float>>)
+            {
+                use fftw::plan::C2CPlan;
+
+                $cache.with(|cache|
+                {
+                    let mut cache = cache.borrow_mut();
+                    let (plan, input, output) = cache.entry((self.length, self.inverse)).or_insert_with(||
+                    {
+                        let sign = if self.inverse { fftw::types::Sign::Backward } else { fftw::types::Sign::Forward };
+                        let plan = <$plan>::aligned(&[self.length], sign, fftw::types::Flag::ESTIMATE).expect("FFTW could not plan the FFT.");
+                        (plan, fftw::array::AlignedVec::new(self.length), fftw::array::AlignedVec::new(self.length))
+                    });
+
+                    //FFTW plans run on aligned memory, so the buffer is copied in and out.
+                    input.copy_from_slice(buffer);
+                    plan.c2c(input, output).expect("FFTW could not execute the FFT.");
+                    buffer.copy_from_slice(output);
+                });
+            }
+        }
+    };
+}
+#[cfg(feature = "fftw")]
+fftw_plan!(f64, fftw::types::c64, fftw::plan::C2CPlan64, PLANS_F64);
+#[cfg(feature = "fftw")]
+fftw_plan!(f32, fftw::types::c32, fftw::plan::C2CPlan32, PLANS_F32);
@@ -0,0 +1,272 @@
+//! GPU backend of the per-scale convolutions.
+//!
+//! The spectrum of the input is computed on the CPU and uploaded once.
+//! The daughter wavelet multiplication and the inverse FFT of every scale run in compute shaders,
+//! and the input part of every row is read back once per batch of scales that fits in a GPU buffer.
+//! Computation on the GPU is always in single precision.
+use crate::*;
+use wgpu::util::DeviceExt;
+
+const WORKGROUP_SIZE : usize = 256;
+const MAX_WORKGROUPS : usize = 65535;
+
+//The step of every row is an integer and a 64 bit fraction, so that the index into the mother wavelet is the same as on the CPU.
+const MULTIPLY : &str = r"
+struct Params { size : u32, rows : u32, flags : u32, unused : u32 }
+struct Row { step : u32, fraction_high : u32, fraction_low : u32, endpoint : u32 }
+
+@group(0) @binding(0) var<uniform> params : Params;
+@group(0) @binding(1) var<storage, read> bins : array<vec2<f32>>;
+@group(0) @binding(2) var<storage, read> mother : array<f32>;
+@group(0) @binding(3) var<storage, read> rows : array<Row>;
+@group(0) @binding(4) var<storage, read_write> output : array<vec2<f32>>;
+
+//Low and high 32 bits of a × b.
+fn multiply(a : u32, b : u32) -> vec2<u32>
+{
+    let low = (a & 0xffffu) * (b & 0xffffu);
+    let cross_a = (a >> 16u) * (b & 0xffffu);
+    let cross_b = (a & 0xffffu) * (b >> 16u);
+    let middle = (low >> 16u) + (cross_a & 0xffffu) + (cross_b & 0xffffu);
+    return vec2<u32>((low & 0xffffu) | (middle << 16u), (a >> 16u) * (b >> 16u) + (cross_a >> 16u) + (cross_b >> 16u) + (middle >> 16u));
+}
+
+fn index(row : Row, k : u32) -> u32
+{
+    let high = multiply(row.fraction_high, k);
+    let low = multiply(row.fraction_low, k);
+    let fraction = high.x + low.y;
+    var whole = row.step * k + high.y + select(0u, 1u, fraction < high.x);
+
+    //The CPU rounds the product to f64 before truncating it, which reaches the next integer within half an ulp below it.
+    if (whole > 0u)
+    {
+        let shift = firstLeadingBit(whole) + 11u;
+        if (shift < 32u) { whole += select(0u, 1u, fraction == 0xffffffffu && low.x >= 0u - (1u << shift)); }
+        else { whole += select(0u, 1u, fraction >= 0u - (1u << (shift - 32u))); }
+    }
+    return min(params.size - 1u, whole);
+}
+
+@compute @workgroup_size(256)
+fn main(@builtin(global_invocation_id) id : vec3<u32>)
+{
+    let k = id.x;
+    let n = params.size;
+    if (k >= n || id.y >= params.rows) { return; }
+
+    let row = rows[id.y];
+    var value = vec2<f32>(0.0, 0.0);
+    if (k < row.endpoint) { value = bins[k] * mother[index(row, k)]; }
+    else if ((params.flags & 1u) != 0u && n - 1u - k < row.endpoint)
+    {
+        let gain = mother[index(row, n - 1u - k)];
+        let sign = select(-1.0, 1.0, (params.flags & 2u) != 0u);
+        value = vec2<f32>(sign * bins[k].x * gain, bins[k].y * gain);
+    }
+    output[id.y * n + k] = value;
+}
+";
+
+//One radix-2 pass of the Stockham inverse FFT, which keeps the output in natural order.
+const FFT : &str = r"
+struct Params { size : u32, rows : u32, span : u32, unused : u32 }
+
+@group(0) @binding(0) var<uniform> params : Params;
+@group(0) @binding(1) var<storage, read> twiddles : array<vec2<f32>>;
+@group(0) @binding(2) var<storage, read> input : array<vec2<f32>>;
+@group(0) @binding(3) var<storage, read_write> output : array<vec2<f32>>;
+
+@compute @workgroup_size(256)
+fn main(@builtin(global_invocation_id) id : vec3<u32>)
+{
+    let j = id.x;
+    let row = id.y;
+    let half = params.size / 2u;
+    if (j >= half || row >= params.rows) { return; }
+
+    let span = params.span;
+    let base = row * params.size;
+    let k = j % span;
+    let w = twiddles[k * (half / span)];
+
+    let a = input[base + j];
+    let c = input[base + j + half];
+    let b = vec2<f32>(c.x * w.x - c.y * w.y, c.x * w.y + c.y * w.x);
+
+    let o = base + (j / span) * span * 2u + k;
+    output[o] = a + b;
+    output[o + span] = a - b;
+}
+";
+
+/// GPU device with the compute pipelines of the transform, which can be shared by several transforms.
+pub struct Gpu
+{
+    device : wgpu::Device,
+    queue : wgpu::Queue,
+    multiply : wgpu::ComputePipeline,
+    fft : wgpu::ComputePipeline,
+    max_buffer : usize
+}
+impl Gpu
+{
+    /// Opens the default GPU adapter and compiles the compute pipelines.
+    pub fn create() -> Result<Gpu, FastCwtError>
+    {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions
+        {
+            power_preference : wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        })).ok_or_else(|| FastCwtError::Gpu("No GPU adapter is available.".to_string()))?;
+
+        let limits = adapter.limits();
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor
+        {
+            label : Some("fastcwt"),
+            required_features : wgpu::Features::empty(),
+            required_limits : limits.clone(),
+            memory_hints : wgpu::MemoryHints::Performance
+        }, None)).map_err(|error| FastCwtError::Gpu(error.to_string()))?;
+
+        let pipeline = |label, source : &str| device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor
+        {
+            label : Some(label),
+            layout : None,
+            module : &device.create_shader_module(wgpu::ShaderModuleDescriptor { label : Some(label), source : wgpu::ShaderSource::Wgsl(source.into()) }),
+            entry_point : Some("main"),
+            compilation_options : Default::default(),
+            cache : None
+        });
+        let multiply = pipeline("multiply", MULTIPLY);
+        let fft = pipeline("fft", FFT);
+
+        let max_buffer = core::cmp::min(limits.max_storage_buffer_binding_size as u64, limits.max_buffer_size) as usize;
+        Ok(Gpu { device, queue, multiply, fft, max_buffer })
+    }
+    /// Whether the convolutions of the FFT size can run on this device, otherwise they run on the CPU.
+    pub(crate) fn supports(& self, newsize : usize) -> bool
+    {
+        newsize >= 2 && newsize.is_power_of_two() && newsize * 8 <= self.max_buffer && newsize / WORKGROUP_SIZE < MAX_WORKGROUPS
+    }
+    /// Same as FastCWT::convolve_with(), on the GPU.
+    pub(crate) fn convolve<T : Send>(& self, transform : &FastCWT, spectrum : &Spectrum, scales : &Scales, output : &mut [T], store : &(impl Fn(&[Complex<f64>], &mut [T]) + Sync))
+    {
+        let newsize = spectrum.bins.len();
+        let (offset, size) = (spectrum.offset, spectrum.size);
+        let columns = output.len() / core::cmp::max(1, scales.num_scales);
+        if columns == 0 { return; }
+
+        let rows = core::cmp::min(core::cmp::min(self.max_buffer / (newsize * 8), MAX_WORKGROUPS), scales.num_scales);
+        let passes = newsize.trailing_zeros();
+
+        let bins : Vec<f32> = spectrum.bins.iter().flat_map(|bin| [bin.re as f32, bin.im as f32]).collect();
+        let mother : Vec<f32> = transform.wavelet.mother.iter().map(|value| *value as f32).collect();
+        let twiddles : Vec<f32> = (0 .. newsize / 2).flat_map(|t|
+        {
+            let angle = 2.0 * core::f64::consts::PI * t as f64 / newsize as f64;
+            [angle.cos() as f32, angle.sin() as f32]
+        }).collect();
+
+        let bins = self.storage("bins", &bins);
+        let mother = self.storage("mother", &mother);
+        let twiddles = self.storage("twiddles", &twiddles);
+        let batch_rows = self.buffer("rows", (rows * 16) as u64, wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST);
+        let ping = self.buffer("ping", (rows * newsize * 8) as u64, wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC);
+        let pong = self.buffer("pong", (rows * newsize * 8) as u64, wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC);
+        let readback = self.buffer("readback", (rows * size * 8) as u64, wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST);
+
+        let flags = transform.wavelet.double_sided as u32 | (transform.wavelet.imag_freq as u32) << 1;
+        let multiply_params = self.buffer("multiply params", 16, wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST);
+        let multiply_group = self.bind_group(&self.multiply, &[&multiply_params, &bins, &mother, &batch_rows, &ping]);
+
+        //Every pass reads from one of the buffers and writes to the other, so the result is in ping after an even number of passes.
+        let fft_params : Vec<wgpu::Buffer> = (0 .. passes).map(|_| self.buffer("fft params", 16, wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST)).collect();
+        let fft_groups : Vec<wgpu::BindGroup> = fft_params.iter().enumerate().map(|(pass, params)|
+        {
+            let (input, output) = if pass % 2 == 0 { (&ping, &pong) } else { (&pong, &ping) };
+            self.bind_group(&self.fft, &[params, &twiddles, input, output])
+        }).collect();
+        let result = if passes % 2 == 0 { &ping } else { &pong };
+
+        let normalization = if transform.use_normalization { 1.0 / newsize as f64 } else { 1.0 };
+        let mut host = vec![Complex::new(0.0, 0.0); rows * size];
+
+        for (first, batch) in scales.scales.chunks(rows).enumerate().map(|(i, batch)| (i * rows, batch))
+        {
+            if transform.is_cancelled() { return; }
+            let count = batch.len();
+            let values : Vec<u8> = batch.iter().flat_map(|scale|
+            {
+                let step = scale / 2.0;
+                let fraction = ((step - step.floor()) * 2.0_f64.powi(64)) as u64;
+                let endpoint = core::cmp::min(newsize / 2, (newsize as f64 * 2.0 / scale) as usize) as u32;
+                [(step.floor() as u32).to_ne_bytes(), ((fraction >> 32) as u32).to_ne_bytes(), (fraction as u32).to_ne_bytes(), endpoint.to_ne_bytes()]
+            }).flatten().collect();
+            self.queue.write_buffer(&batch_rows, 0, &values);
+            self.queue.write_buffer(&multiply_params, 0, &words(&[newsize as u32, count as u32, flags, 0]));
+            for (pass, params) in fft_params.iter().enumerate() { self.queue.write_buffer(params, 0, &words(&[newsize as u32, count as u32, 1 << pass, 0])); }
+
+            let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label : Some("fastcwt") });
+            {
+                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label : Some("convolve"), timestamp_writes : None });
+                pass.set_pipeline(&self.multiply);
+                pass.set_bind_group(0, &multiply_group, &[]);
+                pass.dispatch_workgroups(newsize.div_ceil(WORKGROUP_SIZE) as u32, count as u32, 1);
+
+                pass.set_pipeline(&self.fft);
+                for group in &fft_groups
+                {
+                    pass.set_bind_group(0, group, &[]);
+                    pass.dispatch_workgroups((newsize / 2).div_ceil(WORKGROUP_SIZE) as u32, count as u32, 1);
+                }
+            }
+            for row in 0 .. count
+            {
+                encoder.copy_buffer_to_buffer(result, ((row * newsize + offset) * 8) as u64, &readback, (row * size * 8) as u64, (size * 8) as u64);
+            }
+            self.queue.submit(Some(encoder.finish()));
+
+            let slice = readback.slice(.. (count * size * 8) as u64);
+            slice.map_async(wgpu::MapMode::Read, |_| {});
+            self.device.poll(wgpu::Maintain::Wait);
+            {
+                let data = slice.get_mapped_range();
+                for ((values, bytes), scale) in host.chunks_mut(size).zip(data.chunks(size * 8)).zip(batch)
+                {
+                    //Gain of the wavelet normalization is applied here rather than to the mother wavelet shared by the rows.
+                    let factor = normalization * transform.wavelet.gain(*scale);
+                    for (value, chunk) in values.iter_mut().zip(bytes.chunks_exact(8))
+                    {
+                        let re = f32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) as f64;
+                        let im = f32::from_ne_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]) as f64;
+                        *value = Complex::new(re, im) * factor;
+                    }
+                    transform.mask(values, &spectrum.gaps, *scale);
+                }
+            }
+            readback.unmap();
+
+            let output = &mut output[first * columns .. (first + count) * columns];
+            transform.run(|| for_each_row(transform.sequential, output, columns, || (), |_, i, row| store(&host[i * size .. (i + 1) * size], row)));
+            transform.report(first + count, scales.num_scales);
+        }
+    }
+    fn storage(& self, label : &str, values : &[f32]) -> wgpu::Buffer
+    {
+        self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label : Some(label), contents : &bytes(values), usage : wgpu::BufferUsages::STORAGE })
+    }
+    fn buffer(& self, label : &str, size : u64, usage : wgpu::BufferUsages) -> wgpu::Buffer
+    {
+        self.device.create_buffer(&wgpu::BufferDescriptor { label : Some(label), size, usage, mapped_at_creation : false })
+    }
+    fn bind_group(& self, pipeline : &wgpu::ComputePipeline, buffers : &[&wgpu::Buffer]) -> wgpu::BindGroup
+    {
+        let entries : Vec<wgpu::BindGroupEntry> = buffers.iter().enumerate().map(|(binding, buffer)| wgpu::BindGroupEntry { binding : binding as u32, resource : buffer.as_entire_binding() }).collect();
+        self.device.create_bind_group(&wgpu::BindGroupDescriptor { label : None, layout : &pipeline.get_bind_group_layout(0), entries : &entries })
+    }
+}
+
+fn bytes(values : &[f32]) -> Vec<u8> { values.iter().flat_map(|value| value.to_ne_bytes()).collect() }
+fn words(values : &[u32]) -> Vec<u8> { values.iter().flat_map(|value| value.to_ne_bytes()).collect() }
@@ -0,0 +1,64 @@
+//! HDF5 files of results.
+use crate::*;
+use ::hdf5::types::VarLenUnicode;
+
+/// Complex coefficient as the compound h5py reads as complex128.
+#[derive(::hdf5::H5Type, Clone, Copy)]
+#[repr(C)]
+struct H5Complex
+{
+    r : f64,
+    i : f64
+}
+
+impl CwtResult
+{
+    /// Writes the coefficients with their axes and the parameters of the transform into an HDF5 file.
+    ///
+    /// The file holds the datasets coefficients (num_scales × num_samples), frequencies (Hz), scales and times (seconds),
+    /// and the attributes wavelet, bandwidth, fs, scale_type, hop and mode.
+    /// Complex coefficients are a compound of r and i, which h5py reads as complex128.
+    /// The coefficients are stored in chunks of a row and compressed with deflate, so large scalograms can be read in parts.
+    ///
+    /// # Arguments
+    /// path      - Path of the file, which is replaced if it exists
+    ///
+    /// level     - Deflate compression level (0 ..= 9)
+    pub fn save_hdf5(& self, path : impl AsRef<std::path::Path>, level : u8) -> ::hdf5::Result<()>
+    {
+        let file = ::hdf5::File::create(path)?;
+        let shape = (self.num_scales(), self.num_samples());
+        match self.complex_coefficients()
+        {
+            Some(coefficients) =>
+            {
+                let coefficients : Vec<H5Complex> = coefficients.iter().map(|value| H5Complex { r : value.re, i : value.im }).collect();
+                matrix::<H5Complex>(&file, shape, level)?.write_raw(&coefficients)?;
+            }
+            None => matrix::<f64>(&file, shape, level)?.write_raw(self.real_coefficients().unwrap_or_default())?
+        }
+        for (name, values) in [("frequencies", self.frequencies()), ("scales", self.scales().get_scales().into_vec()), ("times", self.times())]
+        {
+            file.new_dataset::<f64>().shape(values.len()).create(name)?.write_raw(&values)?;
+        }
+
+        let text = |value : &str| value.parse::<VarLenUnicode>().map_err(|error| ::hdf5::Error::from(error.to_string()));
+        let scale_type = self.scales().scale_type().map(|scale_type| format!("{:?}", scale_type)).unwrap_or_else(|| "Custom".to_string());
+        file.new_attr::<VarLenUnicode>().create("wavelet")?.write_scalar(&text("Morlet")?)?;
+        file.new_attr::<f64>().create("bandwidth")?.write_scalar(&self.wavelet().bandwidth())?;
+        file.new_attr::<f64>().create("fs")?.write_scalar(&self.fs())?;
+        file.new_attr::<VarLenUnicode>().create("scale_type")?.write_scalar(&text(&scale_type)?)?;
+        file.new_attr::<u64>().create("hop")?.write_scalar(&(self.hop() as u64))?;
+        file.new_attr::<VarLenUnicode>().create("mode")?.write_scalar(&text(self.mode().name())?)?;
+        file.flush()
+    }
+}
+
+/// Dataset of the coefficients, chunked by rows of at most 128 Ki values and compressed.
+fn matrix<T : ::hdf5::H5Type>(file : &::hdf5::File, shape : (usize, usize), level : u8) -> ::hdf5::Result<::hdf5::Dataset>
+{
+    let builder = file.new_dataset::<T>().shape(shape);
+    //Chunks cannot be larger than the dataset, so an empty dataset is not chunked.
+    if shape.0 == 0 || shape.1 == 0 { return builder.create("coefficients"); }
+    builder.chunk((1, shape.1.min(1 << 17))).shuffle().deflate(level).create("coefficients")
+}
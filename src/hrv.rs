@@ -0,0 +1,105 @@
+//! Heart rate variability in the frequency domain, from a series of RR intervals. (Task Force of the ESC and NASPE, 1996)
+//!
+//! The intervals are unevenly sampled, as each is known at the beat which ends it, so they are interpolated linearly
+//! on an even grid of 4 Hz first. The band power of the transform is the variance of the intervals within a band,
+//! in ms² at every time, and its mean over time is the power of the standard frequency-domain metrics.
+use crate::*;
+
+/// Very low frequency band in Hz.
+pub const VLF : (f64, f64) = (0.0033, 0.04);
+/// Low frequency band in Hz.
+pub const LF : (f64, f64) = (0.04, 0.15);
+/// High frequency band in Hz.
+pub const HF : (f64, f64) = (0.15, 0.4);
+/// Sample rate of the interpolated intervals in Hz.
+const FS : f64 = 4.0;
+/// Scales of the transform per octave.
+const VOICES : usize = 12;
+
+/// Time-resolved band power and frequency-domain metrics of heart rate variability.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Hrv
+{
+    /// Time of every column in seconds from the beat which starts the first interval.
+    pub times : Vec<f64>,
+    /// Power of the LF band at every time in ms².
+    pub lf : Vec<f64>,
+    /// Power of the HF band at every time in ms².
+    pub hf : Vec<f64>,
+    /// Mean power of the VLF band in ms².
+    pub vlf_power : f64,
+    /// Mean power of the LF band in ms².
+    pub lf_power : f64,
+    /// Mean power of the HF band in ms².
+    pub hf_power : f64,
+    /// Mean power from 0.0033 to 0.4 Hz in ms².
+    pub total_power : f64,
+    /// LF power in normalized units, which is LF / (LF + HF) × 100.
+    pub lf_nu : f64,
+    /// HF power in normalized units, which is HF / (LF + HF) × 100.
+    pub hf_nu : f64,
+    /// Ratio of the LF power to the HF power.
+    pub lf_hf : f64
+}
+
+impl FastCWT
+{
+    /// Band power of heart rate variability over time, and its frequency-domain metrics.
+    ///
+    /// The intervals are transformed with the settings of this transform on 12 scales per octave from 0.0033 to 0.4 Hz,
+    /// after their mean is removed. The VLF band needs a recording of at least 5 minutes, and the LF band of 2 minutes, to be meaningful.
+    ///
+    /// Returns FastCwtError::NotNormalized if the transform is not normalized, as the power would not be in ms²,
+    /// or FastCwtError::InvalidIntervals if there are fewer than 2 intervals or one is not positive.
+    ///
+    /// # Arguments
+    /// rr        - RR intervals in ms, which must be positive and cleaned of ectopic beats
+    pub fn hrv(&mut self, rr : &[f64]) -> Result<Hrv, FastCwtError>
+    {
+        if !self.use_normalization { return Err(FastCwtError::NotNormalized); }
+        if rr.len() < 2 || !rr.iter().all(|interval| interval.is_finite() && *interval > 0.0) { return Err(FastCwtError::InvalidIntervals); }
+
+        //Beat times in seconds, where every interval is known at the beat which ends it.
+        let mut beats = Vec::with_capacity(rr.len());
+        let mut time = 0.0;
+        for interval in rr
+        {
+            time += interval / 1000.0;
+            beats.push(time);
+        }
+
+        let start = beats[0];
+        let size = ((beats[beats.len() - 1] - start) * FS) as usize + 1;
+        let mut beat = 0;
+        let mut input : Vec<f64> = (0 .. size).map(|n|
+        {
+            let time = start + n as f64 / FS;
+            while beat + 2 < beats.len() && beats[beat + 1] < time { beat += 1; }
+            let position = ((time - beats[beat]) / (beats[beat + 1] - beats[beat])).clamp(0.0, 1.0);
+            rr[beat] + (rr[beat + 1] - rr[beat]) * position
+        }).collect();
+        let mean = input.iter().sum::<f64>() / size as f64;
+        for value in input.iter_mut() { *value -= mean; }
+
+        let scales = Scales::create(ScaleTypes::Octaves { voices_per_octave : VOICES }, FS, VLF.0, HF.1, 0);
+        let result = self.cwt(size, &input, &scales);
+        let times = (0 .. result.num_samples()).map(|column| start + result.time(column)).collect();
+        let mean = |band : &[f64]| band.iter().sum::<f64>() / band.len().max(1) as f64;
+
+        let (lf, hf) = (result.band_power(LF.0, LF.1)?, result.band_power(HF.0, HF.1)?);
+        let (vlf_power, lf_power, hf_power) = (mean(&result.band_power(VLF.0, VLF.1)?), mean(&lf), mean(&hf));
+        Ok(Hrv
+        {
+            times,
+            vlf_power,
+            lf_power,
+            hf_power,
+            total_power : mean(&result.band_power(VLF.0, HF.1)?),
+            lf_nu : 100.0 * lf_power / (lf_power + hf_power),
+            hf_nu : 100.0 * hf_power / (lf_power + hf_power),
+            lf_hf : lf_power / hf_power,
+            lf,
+            hf
+        })
+    }
+}
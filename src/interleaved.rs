@@ -0,0 +1,59 @@
+//! Interleaved multi-channel audio, where the samples of every frame follow each other like in WAV files and audio callbacks.
+//!
+//! The channels are de-interleaved once and transformed one after another with the same transform, so its plans and mother wavelet are reused.
+use crate::*;
+
+/// Splits interleaved samples into one vector per channel.
+///
+/// # Arguments
+/// input     - Interleaved samples, frame after frame
+///
+/// channels  - Number of channels in a frame
+pub fn deinterleave(input : &[f64], channels : usize) -> Result<Vec<Vec<f64>>, FastCwtError>
+{
+    if channels == 0 || input.len() % channels != 0 { return Err(FastCwtError::InvalidChannels { channels, len : input.len() }); }
+    Ok((0 .. channels).map(|channel| input.iter().skip(channel).step_by(channels).copied().collect()).collect())
+}
+
+/// Splits interleaved stereo samples into the mid (L + R) / 2 and side (L - R) / 2 signals.
+///
+/// # Arguments
+/// input     - Interleaved stereo samples, left then right
+pub fn mid_side(input : &[f64]) -> Result<(Vec<f64>, Vec<f64>), FastCwtError>
+{
+    if input.len() % 2 != 0 { return Err(FastCwtError::InvalidChannels { channels : 2, len : input.len() }); }
+    Ok(input.chunks_exact(2).map(|frame| ((frame[0] + frame[1]) / 2.0, (frame[0] - frame[1]) / 2.0)).unzip())
+}
+
+impl FastCWT
+{
+    /// Same as cwt(), but takes interleaved multi-channel samples and transforms every channel.
+    ///
+    /// # Arguments
+    /// input     - Interleaved samples, frame after frame
+    ///
+    /// channels  - Number of channels in a frame
+    ///
+    /// scales    - Scales object
+    ///
+    /// Returns the result of every channel.
+    pub fn cwt_interleaved(&mut self, input : &[f64], channels : usize, scales : &Scales) -> Result<Vec<CwtResult>, FastCwtError>
+    {
+        Ok(deinterleave(input, channels)?.iter().map(|channel| self.cwt(channel.len(), channel, scales)).collect())
+    }
+    /// Same as cwt(), but takes interleaved stereo samples and transforms their mid and side signals.
+    ///
+    /// The side signal holds what differs between the channels, like reverb and panned sources, and is silent for mono content.
+    ///
+    /// # Arguments
+    /// input     - Interleaved stereo samples, left then right
+    ///
+    /// scales    - Scales object
+    ///
+    /// Returns the results of the mid and the side signal.
+    pub fn cwt_mid_side(&mut self, input : &[f64], scales : &Scales) -> Result<(CwtResult, CwtResult), FastCwtError>
+    {
+        let (mid, side) = mid_side(input)?;
+        Ok((self.cwt(mid.len(), &mid, scales), self.cwt(side.len(), &side, scales)))
+    }
+}
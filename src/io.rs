@@ -0,0 +1,45 @@
+//! Reading audio files.
+use crate::*;
+
+/// Reads every channel of a WAV file as samples in -1.0 ..= 1.0, and the sample rate.
+///
+/// Integer samples are divided by the full scale of their bit depth.
+pub fn read_wav(path : impl AsRef<std::path::Path>) -> Result<(Vec<Vec<f64>>, f64), FastCwtError>
+{
+    let mut reader = hound::WavReader::open(path).map_err(FastCwtError::Wav)?;
+    let spec = reader.spec();
+    let samples : Vec<f64> = match spec.sample_format
+    {
+        hound::SampleFormat::Float => reader.samples::<f32>().map(|sample| sample.map(f64::from)).collect::<Result<_, _>>(),
+        hound::SampleFormat::Int =>
+        {
+            let full_scale = (1_i64 << (spec.bits_per_sample - 1)) as f64;
+            reader.samples::<i32>().map(|sample| sample.map(|sample| sample as f64 / full_scale)).collect::<Result<_, _>>()
+        }
+    }.map_err(FastCwtError::Wav)?;
+
+    //Samples are interleaved by channel.
+    let channels = crate::interleaved::deinterleave(&samples, spec.channels as usize)?;
+    Ok((channels, spec.sample_rate as f64))
+}
+
+impl FastCWT
+{
+    /// Reads a WAV file with read_wav() and transforms every channel.
+    ///
+    /// # Arguments
+    /// path      - Path of the WAV file
+    ///
+    /// scales    - Scales object, which must have the sample rate of the file unless the resample feature is enabled
+    ///
+    /// Returns the result of every channel.
+    pub fn cwt_wav(&mut self, path : impl AsRef<std::path::Path>, scales : &Scales) -> Result<Vec<CwtResult>, FastCwtError>
+    {
+        let (channels, fs) = read_wav(path)?;
+        #[cfg(feature = "resample")]
+        let channels : Vec<Vec<f64>> = channels.iter().map(|channel| crate::resample::resample(channel, fs, scales.fs())).collect();
+        #[cfg(not(feature = "resample"))]
+        if fs != scales.fs() { return Err(FastCwtError::SampleRate { scales : scales.fs(), input : fs }); }
+        Ok(channels.iter().map(|channel| self.cwt(channel.len(), channel, scales)).collect())
+    }
+}
@@ -7,11 +7,13 @@
 //!
 //! ### Usage
 //! ```
+//! # #[cfg(feature = "std")]
+//! # {
 //! use fastcwt::*;
 //! use rand::prelude::*;
 //!
 //! let wavelet = Wavelet::create(1.0); //Create a Morlet wavelet.
-//! let scale = Scales::create(ScaleTypes::LinFreq, 48000, 20.0, 20000.0, 1000); //Create a scale factor.
+//! let scale = Scales::create(ScaleTypes::LinFreq, 48000.0, 20.0, 20000.0, 1000); //Create a scale factor.
 //!
 //! let mut transform = FastCWT::create(wavelet, true); // Create a fCWT instance.
 //!
@@ -21,11 +23,54 @@
 //!     input.push(thread_rng().gen_range(-1.0 .. 1.0))
 //! };
 //!
-//! let result = transform.cwt(1000, input.as_slice(), scale); //Store the result.
+//! let result = transform.cwt(1000, input.as_slice(), &scale); //Store the result.
+//! # }
 //! ```
 //!
+//! ### Features
+//!
+//! std (default) - Uses rustfft as the FFT backend and no_denormals. It also adds csv, which writes and reads results as CSV tables, and FastCWT::cwt_write(), which streams the rows as a binary stream to any writer. Without it the crate is no_std with alloc and needs an FFT backend set with FastCWT::set_fft_backend().
+//!
+//! parallel (default) - Computes the scales in parallel with rayon. Without it every transform runs on the calling thread.
+//!
+//! ndarray - Adds FastCWT::cwt_ndarray() which takes ArrayView1 and returns Array2 (num_scales × samples), and FastCWT::cwt_matrix() which takes one signal per row of an ArrayView2 and returns Array3. (signals × num_scales × samples)
+//!
+//! simd - Multiplies the daughter wavelets with SIMD vectors of the wide crate, which uses the instructions enabled at compile time (e.g. -C target-cpu=native) and falls back to scalar code otherwise.
+//!
+//! gpu - Adds gpu::Gpu and FastCWT::set_gpu(), which run the per-scale convolutions in wgpu compute shaders in single precision.
+//!
+//! fftw - Adds fft::Fftw, which runs the FFTs on the FFTW library like the original fCWT. Set it with FastCWT::set_fft_backend().
+//!
+//! wasm - Adds wasm::compute_cwt() and wasm::CwtConfig for JavaScript through wasm-bindgen. Build for wasm32-unknown-unknown with --no-default-features --features wasm, as browsers cannot spawn rayon threads without extra setup.
+//!
+//! ffi - Adds the C interface in ffi (fastcwt_create(), fastcwt_cwt(), fastcwt_free()) with the header include/fastcwt.h. Build it with cargo rustc --release --features ffi --crate-type staticlib.
+//!
+//! serde - Derives Serialize and Deserialize for the configuration types (Wavelet, Scales, ScaleTypes and the other options) and the results, so configurations and outputs can be stored and loaded. Wavelet only stores its parameters.
+//!
+//! hound - Adds io::read_wav(), which reads every channel of a WAV file as f64 samples with the sample rate, and FastCWT::cwt_wav(), which transforms every channel of a WAV file.
+//!
+//! npy - Adds CwtResult::save_npy(), save_npz() and load_npz(), which write the coefficients as NumPy arrays, with the frequency and time axes in the .npz archive.
+//!
+//! hdf5 - Adds CwtResult::save_hdf5(), which writes the coefficients into chunked and compressed datasets with the axes and the parameters of the transform, for scalograms too large to hold twice in memory or to load at once. It needs the HDF5 library.
+//!
+//! mat - Adds CwtResult::save_mat(), which writes the coefficients, the frequencies and the cone of influence into a MATLAB 7.3 .mat file with the variables of [wt, f, coi] = cwt(x, fs), for comparing with MATLAB. It needs the HDF5 library.
+//!
+//! arrow - Adds CwtResult::to_arrow(), into_arrow() and save_arrow(), which convert the result into an Arrow table with a time column and a column per scale, or write it as an Arrow IPC (Feather) file for pandas, Polars and DataFusion. into_arrow() moves the coefficients into the table without a copy unless they are complex.
+//!
+//! mmap - Adds FastCWT::cwt_mmap(), which writes the coefficients straight into a memory-mapped file, and mmap::MmapScalogram, which reads them back with random access, for scalograms larger than the memory.
+//!
+//! resample - Adds resample::resample(), which converts the sample rate of the input with a windowed sinc, and FastCWT::cwt_resampled(), which transforms an input at any sample rate with the scales of another. FastCWT::cwt_wav() resamples the file to the sample rate of the scales as well.
+//!
+//! async - Adds FastCWT::cwt_async(), which runs the transform on its own thread and returns a future, and FastCWT::cwt_stream(), which returns a stream of the rows of every scale, for async services on any executor without blocking the runtime.
+//!
+//! tracing - Enters tracing spans around the forward FFT, the FFT planning, the generation of the mother and daughter wavelets and every scale, with the scale index and the buffer lengths as fields, at the debug level for each transform and the trace level for each scale.
+//!
+//! cli - Builds the fastcwt binary, which computes the scalogram of a WAV or CSV file and writes it to NPY, CSV or PNG. (e.g. cargo install fastcwt --features cli, then fastcwt input.wav -o scalogram.png --f0 20 --f1 20000)
+//!
 //! Changelog
 //!
+//! 0.2.0 - cwt() returns the coefficients scale by scale (num_scales × samples) instead of one buffer of the padded FFT size, which scales shared and overwrote. The convolution uses an inverse FFT instead of a second forward FFT, so the coefficients are in time order and their phase advances with time, with the opposite sign of 0.1.9.
+//!
 //! 0.1.9 - Used no_denormals to avoid extra latency. Cleaned up the repository.
 //! 
 //! 0.1.8 - Droped mkaudiolibrary which is not used.
@@ -47,14 +92,98 @@
 //! ### Citation
 //!
 //! Arts, L.P.A., van den Broek, E.L. The fast continuous wavelet transformation (fCWT) for real-time, high-quality, noise-resistant time–frequency analysis. Nat Comput Sci 2, 47–58 (2022). <https://doi.org/10.1038/s43588-021-00183-z>
-#![forbid(unsafe_code)]
+#![cfg_attr(not(any(feature = "ffi", feature = "mmap")), forbid(unsafe_code))]
+#![cfg_attr(any(feature = "ffi", feature = "mmap"), deny(unsafe_code))]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
-use no_denormals::*;
-use rustfft;
+use alloc::{vec, vec::Vec, boxed::Box};
+use num_complex::Complex;
+//Float math without std comes from libm, unless another crate in the build links std.
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
+/// Enters a tracing span until the end of the scope, which does nothing without the tracing feature.
+macro_rules! span
+{
+    ($level : ident, $name : expr $(, $field : ident = $value : expr)*) =>
+    {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::span!(tracing::Level::$level, $name $(, $field = $value)*).entered();
+    };
+}
+
+pub mod coherence;
+pub mod synchrosqueeze;
+pub mod reassign;
+pub mod stransform;
+pub mod cqt;
+pub mod dwt;
+pub mod denoise;
+pub mod epochs;
+pub mod chunked;
+pub mod sparse;
+pub mod compact;
+pub mod reference;
+pub mod uneven;
+pub mod interleaved;
+pub mod features;
+pub mod onset;
+pub mod ridge;
+pub mod hrv;
+pub mod trigger;
+pub mod transient;
+pub mod bispectrum;
+pub mod stats;
+pub mod fft;
+#[cfg(feature = "std")]
+pub mod csv;
+#[cfg(feature = "std")]
+pub mod binary;
+#[cfg(feature = "npy")]
+mod npy;
+#[cfg(feature = "hdf5")]
+mod h5;
+#[cfg(feature = "mat")]
+mod mat;
+#[cfg(feature = "arrow")]
+mod arrow;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+#[cfg(feature = "resample")]
+pub mod resample;
+#[cfg(feature = "async")]
+pub mod asynchronous;
+#[cfg(feature = "hound")]
+pub mod io;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod builder;
+mod diagnostics;
+mod error;
+mod estimate;
+mod result;
+mod timing;
+
+pub use builder::FastCWTBuilder;
+pub use diagnostics::{validate, Warning};
+pub use error::FastCwtError;
+pub use estimate::ResourceEstimate;
+pub use result::CwtResult;
+pub use timing::TransformStats;
+use timing::Phase;
+
 /// Scale types selection for Scale object.
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ScaleTypes
 {
     /// Linear scale.
@@ -62,22 +191,209 @@ pub enum ScaleTypes
     /// Logarithmic scale.
     Log,
     /// Linear for frequency.
-    LinFreq
+    LinFreq,
+    /// Scales of 2^(k / voices_per_octave) across the frequency range. The number of wavelets follows from the frequency range.
+    Octaves { voices_per_octave : usize },
+    /// Linear for the mel scale. (2595 × log10(1 + f / 700))
+    Mel,
+    /// Linear for the ERB-rate scale of Glasberg & Moore. (21.4 × log10(1 + 0.00437 × f))
+    ERB,
+    /// Equal-tempered pitches every cents (100.0 for semitones), tuned to the A4 frequency. The number of wavelets follows from the frequency range.
+    Musical { cents : f64, a4 : f64 }
+}
+/// Output mode selection for the transform.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OutputMode
+{
+    /// Complex coefficients.
+    Complex,
+    /// Magnitude of the coefficients. (|W|)
+    Magnitude,
+    /// Power of the coefficients. (|W|²)
+    Power,
+    /// Power of the coefficients in decibels. (10 × log10(|W|²))
+    LogPower
+}
+impl OutputMode
+{
+    /// Lowercase name of the mode, which is stored in files.
+    #[allow(dead_code)]
+    pub(crate) fn name(& self) -> &'static str
+    {
+        match self
+        {
+            OutputMode::Complex => "complex",
+            OutputMode::Magnitude => "magnitude",
+            OutputMode::Power => "power",
+            OutputMode::LogPower => "logpower"
+        }
+    }
+    /// Value of a coefficient in the mode, which is the magnitude for Complex.
+    pub(crate) fn apply(& self, data : Complex<f64>) -> f64
+    {
+        match self
+        {
+            OutputMode::Complex | OutputMode::Magnitude => data.norm(),
+            OutputMode::Power => data.norm_sqr(),
+            OutputMode::LogPower => 10.0 * data.norm_sqr().log10()
+        }
+    }
+}
+/// Padding selection for extending the input to the FFT size.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Padding
+{
+    /// Zeros after the input.
+    Zero,
+    /// Mirror the input without repeating the edge sample. (d c b | a b c d | c b a)
+    Reflect,
+    /// Mirror the input including the edge sample. (c b a | a b c d | d c b)
+    Symmetric,
+    /// Repeat the input. (b c d | a b c d | a b c)
+    Periodic,
+    /// Repeat the edge sample. (a a a | a b c d | d d d)
+    ConstantEdge
+}
+/// Preprocessing of the input before the FFT, which keeps slow trends from leaking into the lowest scales.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Detrend
+{
+    /// Transform the input as it is.
+    #[default]
+    None,
+    /// Subtract the mean of the input.
+    Mean,
+    /// Subtract the least-squares line through the input.
+    Linear,
+    /// Zero-phase 4th order Butterworth high-pass in the frequency domain, with the cutoff relative to the sample rate. (frequency / fs)
+    HighPass { cutoff : f64 }
+}
+/// Window applied to the edges of the input before the padding, which suppresses the jump where a non-periodic input wraps around.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Taper
+{
+    /// Keep the edges as they are.
+    #[default]
+    None,
+    /// Tukey window, with half cosine ramps over the fraction (0 to 1) of the input, half of it at each edge.
+    Tukey { fraction : f64 },
+    /// Hann window over the whole input, which is the Tukey window with fraction 1.
+    Hann
+}
+/// Handling of NaN samples in the input, which otherwise spread over every coefficient through the FFT.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MissingSamples
+{
+    /// Transform the input as it is, so a single NaN sample makes every coefficient NaN.
+    #[default]
+    Propagate,
+    /// Panic in cwt(), or return FastCwtError::MissingSample from try_cwt().
+    Error,
+    /// Replace the NaN samples with zeros.
+    Zero,
+    /// Replace the NaN samples with the line between the valid samples around the gap, or the nearest valid sample at the edges.
+    Interpolate,
+    /// Interpolate like Interpolate, then set the coefficients within the cone of influence of every gap to NaN. (√2 × bandwidth × scale samples around it)
+    Mask
+}
+/// FFT length selection for the padded input.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FftLength
+{
+    /// Next power of two, as fCWT does.
+    PowerOfTwo,
+    /// Exactly the padded input length.
+    Exact,
+    /// Next length which only has 2, 3, 5 and 7 as prime factors, which rustfft handles as fast as powers of two.
+    Fast
+}
+/// Floating point precision of the per-scale convolutions.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Precision
+{
+    /// Convolve in f64.
+    Double,
+    /// Convolve in f32 as fCWT does, which is faster. The result is still stored in f64.
+    Single
+}
+/// Sampling of the daughter wavelets from the mother wavelet.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Accuracy
+{
+    /// Nearest sample of the mother wavelet as fCWT does, which is the fastest.
+    Fast,
+    /// Linear interpolation between the two nearest samples of the mother wavelet.
+    Interpolated,
+    /// Morlet formula evaluated at every frequency of every scale, for quantitative work.
+    Exact
+}
+/// Pooling selection for the samples within a hop.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Pooling
+{
+    /// Keep the first sample of every hop.
+    Decimate,
+    /// Keep the sample with the largest magnitude in every hop.
+    Max,
+    /// Average the samples in every hop.
+    Mean
+}
+/// Normalization of the power relative to a baseline, scale by scale.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Baseline
+{
+    /// Change in decibels from the baseline mean. (10 × log10(P / mean))
+    Decibel,
+    /// Change in percent from the baseline mean. (100 × (P - mean) / mean)
+    Percent,
+    /// Standard score against the baseline. ((P - mean) / standard deviation)
+    ZScore
+}
+/// Normalization of the daughter wavelets, which sets how the amplitudes of the coefficients compare across scales.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Normalization
+{
+    /// Gains of the mother wavelet as in fCWT, where every scale peaks at √(2π) × π^(-1/4) ≈ 1.88 in frequency.
+    #[default]
+    None,
+    /// Every scale peaks at 2 in frequency, so the magnitude at the center frequency is the amplitude of a sinusoid, as in MATLAB.
+    L1,
+    /// Every daughter wavelet has unit energy, so white noise has the same power at every scale, as in Torrence & Compo. Coefficients grow with √scale.
+    L2
 }
 /// Morlet wavelet object.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone)]
 pub struct Wavelet
 {
+    //Mother wavelet is generated again for the FFT size, so only the parameters are serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
     width : usize,
     imag_freq : bool,
     double_sided : bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
     mother : Vec<f64>,
-    fb : f64
+    #[cfg_attr(feature = "serde", serde(rename = "bandwidth"))]
+    fb : f64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    normalization : Normalization
 }
 impl Wavelet
 {
     /// Create a wavelet object.
     ///
-    /// bandwidth           - bandwidth of the Morlet wavelet
+    /// bandwidth           - bandwidth of the Morlet wavelet, which is the standard deviation of its envelope in periods of the center frequency. (ω₀ / 2π)
     pub fn create(bandwidth : f64) -> Wavelet
     {
         return Wavelet
@@ -86,33 +402,226 @@ impl Wavelet
             imag_freq : false,
             double_sided : false,
             mother : vec![],
-            fb : bandwidth
+            fb : bandwidth,
+            normalization : Normalization::None
         }
     }
+    /// Create a wavelet object from the center frequency ω₀ of the mother wavelet e^(iω₀t) e^(-t²/2), like other CWT packages.
+    ///
+    /// omega0              - Radians per standard deviation of the envelope, where larger values trade time resolution for frequency resolution
+    pub fn from_omega0(omega0 : f64) -> Wavelet { Wavelet::create(omega0 / (2.0 * core::f64::consts::PI)) }
+    /// Bandwidth of the Morlet wavelet.
+    pub fn bandwidth(& self) -> f64 { self.fb }
+    /// Center frequency ω₀ of the mother wavelet. (2π × bandwidth)
+    pub fn omega0(& self) -> f64 { 2.0 * core::f64::consts::PI * self.fb }
+    /// Normalization of the daughter wavelets.
+    pub fn normalization(& self) -> Normalization { self.normalization }
+    /// Set the normalization of the daughter wavelets.
+    ///
+    /// normalization       - None | L1 | L2
+    pub fn set_normalization(&mut self, normalization : Normalization) { self.normalization = normalization; }
+    /// Copy of the parameters without the generated mother wavelet.
+    pub(crate) fn parameters(& self) -> Wavelet
+    {
+        Wavelet { width : 0, imag_freq : self.imag_freq, double_sided : self.double_sided, mother : vec![], fb : self.fb, normalization : self.normalization }
+    }
     fn generate(&mut self, size : usize)
     {
+        //Mother wavelet only depends on the size, so reuse it if it is already generated.
+        if self.width == size && self.mother.len() == size { return; }
+
+        span!(DEBUG, "generate_wavelet", length = size);
         //Frequency domain, because we only need size. Default scale is always 2;
         self.width = size;
+        self.mother.clear();
 
-        let toradians = 2.0 * std::f64::consts::PI / size as f64;
-        let norm = (2.0 * std::f64::consts::PI).sqrt() * (1.0 / std::f64::consts::PI).powf(0.25);
+        let toradians = 2.0 * core::f64::consts::PI / size as f64;
+        let norm = (2.0 * core::f64::consts::PI).sqrt() * (1.0 / core::f64::consts::PI).powf(0.25);
 
         //calculate array
         for w in 0 .. self.width
         {
-            let mut tmp1 = 2.0 * (w as f64 * toradians) * self.fb - 2.0 * std::f64::consts::PI * self.fb;
+            let mut tmp1 = 2.0 * (w as f64 * toradians) * self.fb - 2.0 * core::f64::consts::PI * self.fb;
             tmp1 = - tmp1.powf(2.0) / 2.0;
             self.mother.push(norm * (tmp1).exp());
         }
     }
+    /// Gain of the daughter wavelet at u = scale × frequency / fs, which peaks at u = 1 and is cut at u = 2.
+    pub(crate) fn spectrum(&self, u : f64) -> f64
+    {
+        if u <= 0.0 || u >= 2.0 { return 0.0; }
+
+        //Daughter spectrum is norm * exp(-(2 pi fb (u - 1))^2 / 2).
+        let norm = (2.0 * core::f64::consts::PI).sqrt() * (1.0 / core::f64::consts::PI).powf(0.25);
+        let tmp = 2.0 * core::f64::consts::PI * self.fb * (u - 1.0);
+        norm * (- tmp * tmp / 2.0).exp()
+    }
+    /// Factor of the daughter wavelet of a scale over the mother wavelet, which is set by the normalization.
+    ///
+    /// Factors of the spectrum, like reconstruction_factor(), do not include it.
+    pub(crate) fn gain(& self, scale : f64) -> f64
+    {
+        match self.normalization
+        {
+            Normalization::None => 1.0,
+            //Peak of the spectrum becomes 2.
+            Normalization::L1 => 2.0 / ((2.0 * core::f64::consts::PI).sqrt() * (1.0 / core::f64::consts::PI).powf(0.25)),
+            //Energy of the daughter wavelet in samples is the integral of the squared spectrum over u divided by the scale, which is 1 / (fb × scale).
+            Normalization::L2 => (self.fb * scale).sqrt()
+        }
+    }
+    /// Checks that the wavelet gives a meaningful transform, and reports its admissibility constant, analyticity and bandwidth.
+    ///
+    /// Returns FastCwtError::InvalidBandwidth if the bandwidth is not a positive number,
+    /// and FastCwtError::NotAdmissible if the gain at 0 Hz is more than 1% of the peak (bandwidth below about 0.48, or ω₀ below about 3),
+    /// where the mean of the signal leaks into every scale and the inverse transform does not converge.
+    pub fn validate(& self) -> Result<WaveletProperties, FastCwtError>
+    {
+        if !(self.fb.is_finite() && self.fb > 0.0) { return Err(FastCwtError::InvalidBandwidth(self.fb)); }
+
+        //Gaussian spectrum of the mother wavelet, which is exp(-(2 pi fb (u - 1))^2 / 2) relative to its peak.
+        let sigma = 1.0 / (2.0 * core::f64::consts::PI * self.fb);
+        let relative = |u : f64| (- ((u - 1.0) / sigma).powi(2) / 2.0).exp();
+        let dc_gain = relative(0.0);
+        if dc_gain > 0.01 { return Err(FastCwtError::NotAdmissible { dc_gain }); }
+
+        //Energy below 0 Hz is cut from the analytic wavelet, out of sigma × sqrt(pi) in total.
+        let steps = 4096;
+        let lower = 1.0 - 10.0 * sigma;
+        let du = - lower / steps as f64;
+        let negative = (1 ..= steps).map(|i| relative(lower + (i as f64 - 0.5) * du).powi(2) * du).sum::<f64>() / (sigma * core::f64::consts::PI.sqrt());
+
+        //Power falls to half at u = 1 ± sqrt(ln 2) × sigma.
+        let bandwidth = 2.0 * core::f64::consts::LN_2.sqrt() * sigma;
+        Ok(WaveletProperties { admissibility : self.power_factor(), dc_gain, negative_energy : negative, analytic : negative < 1e-6, bandwidth, q : 1.0 / bandwidth })
+    }
+    /// Daughter wavelet of the scale sampled in time from -support to support, which is the inverse Fourier transform of its Gaussian spectrum.
+    ///
+    /// π^(-1/4) / (fb × scale) × exp(-(n / (fb × scale))^2 / 2) × e^(2πi n / scale), with the gain of the normalization.
+    pub(crate) fn daughter_in_time(& self, scale : f64, support : isize) -> Vec<Complex<f64>>
+    {
+        let amplitude = (1.0 / core::f64::consts::PI).powf(0.25) * self.gain(scale) / (self.fb * scale);
+        (- support ..= support).map(|n|
+        {
+            let t = n as f64 / scale;
+            Complex::from_polar(amplitude * (- (t / self.fb).powi(2) / 2.0).exp(), 2.0 * core::f64::consts::PI * t)
+        }).collect()
+    }
+    /// Integral of the daughter wavelet spectrum over log-frequency, which is the same for every scale.
+    ///
+    /// Summing coefficients over scales weighted by d(ln scale) and dividing by this gives the analytic signal divided by 2.
+    pub(crate) fn reconstruction_factor(&self) -> f64
+    {
+        let steps = 4096;
+        let du = 2.0 / steps as f64;
+
+        (1 ..= steps).map(|i|
+        {
+            let u = (i as f64 - 0.5) * du;
+            self.spectrum(u) / u * du
+        }).sum()
+    }
+    /// Integral of the squared daughter wavelet spectrum over log-frequency, which is the same for every scale.
+    ///
+    /// Summing power over scales weighted by d(ln scale) and dividing by this gives the variance of the signal divided by 2.
+    pub(crate) fn power_factor(&self) -> f64
+    {
+        let steps = 4096;
+        let du = 2.0 / steps as f64;
+
+        (1 ..= steps).map(|i|
+        {
+            let u = (i as f64 - 0.5) * du;
+            self.spectrum(u) * self.spectrum(u) / u * du
+        }).sum()
+    }
+}
+/// Properties of a wavelet, reported by Wavelet::validate().
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct WaveletProperties
+{
+    /// Admissibility constant, which is the integral of the squared spectrum over log-frequency. (C_ψ)
+    pub admissibility : f64,
+    /// Gain at 0 Hz relative to the peak, which is 0 for an admissible wavelet.
+    pub dc_gain : f64,
+    /// Fraction of the energy of the Gaussian spectrum below 0 Hz, which is cut from the wavelet.
+    pub negative_energy : f64,
+    /// True if the negative energy is below 1e-6, so the wavelet is analytic for practical purposes.
+    pub analytic : bool,
+    /// Full width at half power relative to the center frequency.
+    pub bandwidth : f64,
+    /// Quality factor, which is the center frequency over the full width at half power.
+    pub q : f64
+}
+/// Frequency and time resolution of a scale, reported by Scales::resolution().
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Resolution
+{
+    /// Scale in samples.
+    pub scale : f64,
+    /// Center frequency in Hz.
+    pub frequency : f64,
+    /// Full width at half power (-3 dB) in Hz.
+    pub bandwidth : f64,
+    /// Quality factor, which is the center frequency over the bandwidth and the same for every scale.
+    pub q : f64,
+    /// Length of the daughter wavelet in samples, within 5 standard deviations of its envelope on each side.
+    pub support : f64
+}
+/// Wavelets are equal if their parameters are, regardless of the generated mother wavelet.
+impl PartialEq for Wavelet
+{
+    fn eq(& self, other : &Self) -> bool
+    {
+        self.fb == other.fb && self.imag_freq == other.imag_freq && self.double_sided == other.double_sided && self.normalization == other.normalization
+    }
+}
+/// Only the parameters, without the generated mother wavelet.
+impl core::fmt::Debug for Wavelet
+{
+    fn fmt(& self, f : &mut core::fmt::Formatter<'_>) -> core::fmt::Result
+    {
+        f.debug_struct("Wavelet").field("bandwidth", &self.fb).field("normalization", &self.normalization).finish_non_exhaustive()
+    }
+}
+/// Morlet wavelet with the common default ω₀ = 6. (bandwidth ≈ 0.955)
+impl Default for Wavelet
+{
+    fn default() -> Self { Wavelet::from_omega0(6.0) }
 }
 
 /// Scale factor for the wavelet transform.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "ScalesData"))]
+#[derive(Clone, PartialEq, Debug)]
 pub struct Scales
 {
     scales : Box<[f64]>,
-    fs : usize,
-    num_scales : usize
+    fs : f64,
+    num_scales : usize,
+    scale_type : Option<ScaleTypes>
+}
+/// Fields of a deserialized Scales object, which are checked before they become one.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct ScalesData
+{
+    scales : Box<[f64]>,
+    fs : f64,
+    num_scales : usize,
+    scale_type : Option<ScaleTypes>
+}
+#[cfg(feature = "serde")]
+impl TryFrom<ScalesData> for Scales
+{
+    type Error = FastCwtError;
+    fn try_from(data : ScalesData) -> Result<Scales, FastCwtError>
+    {
+        if data.num_scales != data.scales.len() { return Err(FastCwtError::ScalesLength { num_scales : data.num_scales, len : data.scales.len() }); }
+        if !(data.fs > 0.0 && data.fs.is_finite()) { return Err(FastCwtError::InvalidSampleRate(data.fs)); }
+        let scales = Scales { scales : data.scales, fs : data.fs, num_scales : data.num_scales, scale_type : data.scale_type };
+        scales.validate()?;
+        Ok(scales)
+    }
 }
 impl Scales
 {
@@ -126,154 +635,1657 @@ impl Scales
     ///
     /// af1                 - End of the frequency range
     ///
-    /// af_num              - Number of wavelets to generate across frequency range
-    pub fn create(st : ScaleTypes, afs : usize, af0 : f64, af1 : f64, af_num : usize) -> Scales
+    /// af_num              - Number of wavelets to generate across frequency range, which is ignored for Octaves and Musical
+    pub fn create(st : ScaleTypes, afs : f64, af0 : f64, af1 : f64, af_num : usize) -> Scales
+    {
+        Scales::try_create(st, afs, af0, af1, af_num).unwrap_or_else(|error| panic!("{}", error))
+    }
+    /// Same as create(), but returns an error with the offending values instead of panicking on an invalid configuration.
+    ///
+    /// st                  - Scale type
+    ///
+    /// afs                 - Sample frequency
+    ///
+    /// af0                 - Beginning of the frequency range
+    ///
+    /// af1                 - End of the frequency range
+    ///
+    /// af_num              - Number of wavelets to generate across frequency range, which is ignored for Octaves and Musical
+    pub fn try_create(st : ScaleTypes, afs : f64, af0 : f64, af1 : f64, af_num : usize) -> Result<Scales, FastCwtError>
     {
+        if !(afs.is_finite() && afs > 0.0) { return Err(FastCwtError::InvalidSampleRate(afs)); }
+        if af1 > afs / 2.0 { return Err(FastCwtError::AboveNyquist { frequency : af1, nyquist : afs / 2.0 }); }
+        if !(af0 > 0.0 && af0 <= af1) { return Err(FastCwtError::InvalidFrequencyRange { f0 : af0, f1 : af1 }); }
+        match st
+        {
+            ScaleTypes::Octaves { voices_per_octave : 0 } => return Err(FastCwtError::InvalidVoices),
+            ScaleTypes::Musical { cents, a4 } if !(cents > 0.0 && a4 > 0.0) => return Err(FastCwtError::InvalidTuning { cents, a4 }),
+            _ => {}
+        }
+
         let mut scales = Scales
         {
             scales: vec![0.0;af_num].into_boxed_slice(),
             fs : afs,
             num_scales: af_num,
+            scale_type : Some(st)
         };
         match st
         {
             ScaleTypes::Linear => { scales.calculate_linscale_array(afs, af0, af1, af_num); }
             ScaleTypes::Log => { scales.calculate_logscale_array(2.0, afs, af0, af1, af_num); }
             ScaleTypes::LinFreq => { scales.calculate_linfreq_array(afs, af0, af1, af_num); }
+            ScaleTypes::Octaves { voices_per_octave } => { scales.calculate_octave_array(afs, af0, af1, voices_per_octave); }
+            ScaleTypes::Musical { cents, a4 } => { scales.calculate_musical_array(afs, af0, af1, cents, a4); }
+            ScaleTypes::Mel => { scales.calculate_perceptual_array(afs, af0, af1, af_num, |f| 2595.0 * (1.0 + f / 700.0).log10(), |m| 700.0 * (10.0_f64.powf(m / 2595.0) - 1.0)); }
+            ScaleTypes::ERB => { scales.calculate_perceptual_array(afs, af0, af1, af_num, |f| 21.4 * (1.0 + 0.00437 * f).log10(), |e| (10.0_f64.powf(e / 21.4) - 1.0) / 0.00437); }
+        }
+        scales.validate()?;
+        Ok(scales)
+    }
+    //Every scale must be finite and at least 2 samples long, i.e. at or below the Nyquist frequency.
+    fn validate(& self) -> Result<(), FastCwtError>
+    {
+        match self.scales.iter().find(|scale| !(**scale >= 2.0 && scale.is_finite()))
+        {
+            Some(scale) => Err(FastCwtError::InvalidScale(*scale)),
+            None => Ok(())
+        }
+    }
+    /// Create the scale factor from the frequencies to analyze.
+    ///
+    /// fs                  - Sample frequency
+    ///
+    /// frequencies         - Center frequency of every wavelet
+    pub fn from_frequencies(fs : f64, frequencies : &[f64]) -> Scales
+    {
+        Scales::try_from_frequencies(fs, frequencies).unwrap_or_else(|error| panic!("{}", error))
+    }
+    /// Same as from_frequencies(), but returns an error with the offending frequency instead of panicking.
+    ///
+    /// fs                  - Sample frequency
+    ///
+    /// frequencies         - Center frequency of every wavelet
+    pub fn try_from_frequencies(fs : f64, frequencies : &[f64]) -> Result<Scales, FastCwtError>
+    {
+        if !(fs.is_finite() && fs > 0.0) { return Err(FastCwtError::InvalidSampleRate(fs)); }
+        //Cannot pass the nyquist frequency
+        for frequency in frequencies
+        {
+            if !(frequency.is_finite() && *frequency > 0.0) { return Err(FastCwtError::InvalidFrequency(*frequency)); }
+            if *frequency > fs / 2.0 { return Err(FastCwtError::AboveNyquist { frequency : *frequency, nyquist : fs / 2.0 }); }
         }
-        return scales;
+
+        Ok(Scales
+        {
+            scales : frequencies.iter().map(|frequency| fs / frequency).collect(),
+            fs,
+            num_scales : frequencies.len(),
+            scale_type : None
+        })
+    }
+    /// Create the scale factor from the scales to analyze.
+    ///
+    /// fs                  - Sample frequency
+    ///
+    /// scales              - Scale of every wavelet, where the center frequency is fs / scale
+    pub fn from_scales(fs : f64, scales : &[f64]) -> Scales
+    {
+        Scales::try_from_scales(fs, scales).unwrap_or_else(|error| panic!("{}", error))
+    }
+    /// Same as from_scales(), but returns an error with the offending scale instead of panicking.
+    ///
+    /// fs                  - Sample frequency
+    ///
+    /// scales              - Scale of every wavelet, where the center frequency is fs / scale
+    pub fn try_from_scales(fs : f64, scales : &[f64]) -> Result<Scales, FastCwtError>
+    {
+        if !(fs.is_finite() && fs > 0.0) { return Err(FastCwtError::InvalidSampleRate(fs)); }
+        //Cannot pass the nyquist frequency
+        if let Some(scale) = scales.iter().find(|scale| !(scale.is_finite() && **scale >= 2.0)) { return Err(FastCwtError::InvalidScale(*scale)); }
+
+        Ok(Scales
+        {
+            scales : scales.into(),
+            fs,
+            num_scales : scales.len(),
+            scale_type : None
+        })
     }
+    /// Number of scales.
+    pub fn len(& self) -> usize { self.num_scales }
+    /// Scale type the scales were created with, which is None for from_frequencies() and from_scales().
+    pub fn scale_type(& self) -> Option<ScaleTypes> { self.scale_type }
+    /// Sample frequency.
+    pub fn fs(& self) -> f64 { self.fs }
+    /// True if there is no scale.
+    pub fn is_empty(& self) -> bool { self.num_scales == 0 }
+    /// Iterate over (scale, frequency) pairs.
+    pub fn iter(& self) -> ScalesIter<'_> { ScalesIter { scales : self.scales.iter(), fs : self.fs } }
     pub fn get_scales(& self) -> Box<[f64]> { return self.scales.clone(); }
+    /// Center frequency of every scale.
+    pub fn frequencies(& self) -> Vec<f64> { self.scales.iter().map(|scale| self.fs / scale).collect() }
+    /// Center frequency of a scale.
+    ///
+    /// idx                 - Index of the scale
+    pub fn frequency(& self, idx : usize) -> f64 { self.fs / self.scales[idx] }
+    /// Scales in a range of indices, e.g. to compute a band of an existing Scales object.
+    ///
+    /// range               - Range of the scale indices
+    pub fn slice(& self, range : core::ops::Range<usize>) -> Scales
+    {
+        assert!(range.start <= range.end && range.end <= self.num_scales, "Range must be within the scales.");
+        Scales { scales : self.scales[range.clone()].into(), fs : self.fs, num_scales : range.len(), scale_type : None }
+    }
+    /// Scales at the indices, in the order of the indices, e.g. every 8th scale first and the rest later for progressive refinement.
+    ///
+    /// indices             - Indices of the scales
+    pub fn select(& self, indices : &[usize]) -> Scales
+    {
+        assert!(indices.iter().all(|index| *index < self.num_scales), "Indices must be less than the number of scales.");
+        Scales { scales : indices.iter().map(|index| self.scales[*index]).collect(), fs : self.fs, num_scales : indices.len(), scale_type : None }
+    }
+    /// Frequency and time resolution of every scale with the wavelet.
+    ///
+    /// wavelet             - Wavelet of the transform
+    pub fn resolution(& self, wavelet : &Wavelet) -> Vec<Resolution>
+    {
+        //Power of the Gaussian spectrum falls to half at u = 1 ± sqrt(ln 2) / (2 pi fb) relative to the center frequency.
+        let relative = core::f64::consts::LN_2.sqrt() / (core::f64::consts::PI * wavelet.fb);
+        self.iter().map(|(scale, frequency)| Resolution
+        {
+            scale,
+            frequency,
+            bandwidth : relative * frequency,
+            q : 1.0 / relative,
+            support : 10.0 * wavelet.fb * scale
+        }).collect()
+    }
+    #[deprecated(since = "0.2.0", note = "Only the length of p_freqs is used. Use frequencies() or frequency() instead.")]
     pub fn get_frequencies(& self, p_freqs : & mut Vec<f64>) -> Vec<f64>
     {
         let mut frequencies = vec![];
-        no_denormals(|| { for i in 0..p_freqs.len() { frequencies.push(self.fs as f64 / self.scales[i]); } });
+        no_denormals(|| { for i in 0..p_freqs.len() { frequencies.push(self.fs / self.scales[i]); } });
         return frequencies;
     }
-    fn calculate_logscale_array(&mut self, base : f64, fs : usize, f0 : f64, f1 : f64, f_num : usize)
+    /// Spacing of every scale in ln(scale), which is the weight of a scale when coefficients are summed over scales.
+    pub(crate) fn log_steps(& self) -> Vec<f64>
+    {
+        if self.num_scales < 2 { return vec![1.0; self.num_scales]; }
+        (0 .. self.num_scales).map(|i|
+        {
+            let lower = i.saturating_sub(1);
+            let upper = core::cmp::min(i + 1, self.num_scales - 1);
+            (self.scales[upper].ln() - self.scales[lower].ln()).abs() / (upper - lower) as f64
+        }).collect()
+    }
+    fn calculate_logscale_array(&mut self, base : f64, fs : f64, f0 : f64, f1 : f64, f_num : usize)
     {
         let nf0 = f0;
         let nf1 = f1;
-        let s0 = fs as f64 / nf1;
-        let s1 = fs as f64 / nf0;
+        let s0 = fs / nf1;
+        let s1 = fs / nf0;
 
         //Cannot pass the nyquist frequency
-        assert!(f1 as usize <= fs / 2, "Max frequency cannot be higher than the Nyquist frequency.");
+        assert!(f1 <= fs / 2.0, "Max frequency cannot be higher than the Nyquist frequency.");
 
-        let power0 = s0.log(std::f64::consts::E) / base.log(std::f64::consts::E);
-        let power1 = s1.log(std::f64::consts::E) / base.log(std::f64::consts::E);
+        let power0 = s0.log(core::f64::consts::E) / base.log(core::f64::consts::E);
+        let power1 = s1.log(core::f64::consts::E) / base.log(core::f64::consts::E);
         let dpower = power1 - power0;
 
         no_denormals(||
         {
             for i in 0 .. f_num
             {
-                let power = power0 + dpower / ((f_num - 1) * i) as f64;
+                let power = power0 + dpower * i as f64 / (f_num - 1).max(1) as f64;
                 self.scales[i] = base.powf(power);
             }
         });
     }
-    fn calculate_linscale_array(&mut self, fs : usize, f0 : f64, f1 : f64, f_num : usize)
+    fn calculate_linscale_array(&mut self, fs : f64, f0 : f64, f1 : f64, f_num : usize)
     {
         //If a signal has fs=100hz and you want to measure [0.1-50]Hz, you need scales 2 to 1000;
 
         //Cannot pass the nyquist frequency
-        assert!(f1 <= (fs / 2) as f64, "Max frequency cannot be higher than the Nyquist frequency.");
+        assert!(f1 <= fs / 2.0, "Max frequency cannot be higher than the Nyquist frequency.");
         let df = f1 - f0;
 
-        no_denormals(|| { for i in 0 .. f_num { self.scales[f_num - i - 1] = fs as f64 / f0 + (df / f_num as f64) * i as f64; } });
+        no_denormals(|| { for i in 0 .. f_num { self.scales[f_num - i - 1] = fs / f0 + (df / f_num as f64) * i as f64; } });
+    }
+    fn calculate_octave_array(&mut self, fs : f64, f0 : f64, f1 : f64, voices : usize)
+    {
+        let s0 = fs / f1;
+        let s1 = fs / f0;
+
+        //Cannot pass the nyquist frequency
+        assert!(f1 <= fs / 2.0, "Max frequency cannot be higher than the Nyquist frequency.");
+        assert!(voices > 0, "There must be at least one voice per octave.");
+
+        let f_num = (voices as f64 * (s1 / s0).log2() + 1e-9).floor() as usize + 1;
+        self.scales = no_denormals(|| (0 .. f_num).map(|k| s0 * 2.0_f64.powf(k as f64 / voices as f64)).collect());
+        self.num_scales = f_num;
+    }
+    fn calculate_musical_array(&mut self, fs : f64, f0 : f64, f1 : f64, cents : f64, a4 : f64)
+    {
+        //Cannot pass the nyquist frequency
+        assert!(f1 <= fs / 2.0, "Max frequency cannot be higher than the Nyquist frequency.");
+        assert!(cents > 0.0 && a4 > 0.0, "Cents and A4 frequency must be positive.");
+
+        //Steps from A4 which fall in the frequency range, highest frequency first.
+        let k0 = (1200.0 * (f0 / a4).log2() / cents - 1e-9).ceil() as i64;
+        let k1 = (1200.0 * (f1 / a4).log2() / cents + 1e-9).floor() as i64;
+
+        self.scales = no_denormals(|| (k0 ..= k1).rev().map(|k| fs / (a4 * 2.0_f64.powf(k as f64 * cents / 1200.0))).collect());
+        self.num_scales = self.scales.len();
+    }
+    fn calculate_perceptual_array(&mut self, fs : f64, f0 : f64, f1 : f64, f_num : usize, to_scale : fn(f64) -> f64, from_scale : fn(f64) -> f64)
+    {
+        //Cannot pass the nyquist frequency
+        assert!(f1 <= fs / 2.0, "Max frequency cannot be higher than the Nyquist frequency.");
+
+        //Highest frequency first, like the other scale types.
+        let (p0, p1) = (to_scale(f0), to_scale(f1));
+        let dp = if f_num > 1 { (p1 - p0) / (f_num - 1) as f64 } else { 0.0 };
+
+        //The round trip through the perceptual scale may land just above f1, so keep it in range.
+        no_denormals(|| { for i in 0 .. f_num { self.scales[i] = fs / from_scale(p1 - dp * i as f64).clamp(f0, f1); } });
     }
-    fn calculate_linfreq_array(&mut self, fs : usize, f0 : f64, f1 : f64, f_num : usize)
+    fn calculate_linfreq_array(&mut self, fs : f64, f0 : f64, f1 : f64, f_num : usize)
     {
         //If a signal has fs=100hz and you want to measure [0.1-50]Hz, you need scales 2 to 1000;
-        let s0 = fs as f64 / f1;
-        let s1 = fs as f64 / f0;
+        let s0 = fs / f1;
+        let s1 = fs / f0;
 
         //Cannot pass the nyquist frequency
-        assert!(f1 <= fs as f64 / 2.0, "Max frequency cannot be higher than the Nyquist frequency.");
+        assert!(f1 <= fs / 2.0, "Max frequency cannot be higher than the Nyquist frequency.");
         let ds = s1 - s0;
 
         no_denormals(|| { for i in 0 .. f_num { self.scales[i] = s0 + (ds / f_num as f64) * i as f64; } });
     }
 }
 
+impl core::ops::Index<usize> for Scales
+{
+    type Output = f64;
+    fn index(& self, index : usize) -> &f64 { &self.scales[index] }
+}
+impl<'a> IntoIterator for &'a Scales
+{
+    type Item = (f64, f64);
+    type IntoIter = ScalesIter<'a>;
+    fn into_iter(self) -> ScalesIter<'a> { self.iter() }
+}
+/// Iterator over (scale, frequency) pairs of a Scales object.
+#[derive(Clone, Debug)]
+pub struct ScalesIter<'a>
+{
+    scales : core::slice::Iter<'a, f64>,
+    fs : f64
+}
+impl Iterator for ScalesIter<'_>
+{
+    type Item = (f64, f64);
+    fn next(&mut self) -> Option<(f64, f64)> { self.scales.next().map(|scale| (*scale, self.fs / scale)) }
+    fn size_hint(& self) -> (usize, Option<usize>) { self.scales.size_hint() }
+}
+impl ExactSizeIterator for ScalesIter<'_> {}
+impl DoubleEndedIterator for ScalesIter<'_>
+{
+    fn next_back(&mut self) -> Option<(f64, f64)> { self.scales.next_back().map(|scale| (*scale, self.fs / scale)) }
+}
+
+/// Iterator of FastCWT::cwt_iter(), which yields the complex coefficients of every scale in order.
+pub struct ScaleRows<'a>
+{
+    transform : &'a FastCWT,
+    spectrum : Option<(Spectrum, Kernel<f64>)>,
+    buffers : RowBuffers<f64>,
+    scales : &'a Scales,
+    index : usize,
+    size : usize
+}
+impl Iterator for ScaleRows<'_>
+{
+    type Item = Vec<Complex<f64>>;
+    fn next(&mut self) -> Option<Vec<Complex<f64>>>
+    {
+        if self.index >= self.scales.num_scales || self.transform.is_cancelled() { return None; }
+
+        let mut row = vec![Complex::new(0.0, 0.0); self.size];
+        if let Some((spectrum, kernel)) = &self.spectrum
+        {
+            let transform = self.transform;
+            span!(TRACE, "scale", index = self.index, scale = self.scales.scales[self.index], fft_length = kernel.bins.len(), samples = self.size);
+            transform.timed(Phase::Convolution, || transform.convolve_scale(spectrum, kernel, None, self.scales.scales[self.index], &mut self.buffers, |coefficients| row.copy_from_slice(coefficients)));
+            transform.count(1, 0);
+        }
+        self.index += 1;
+        self.transform.report(self.index, self.scales.num_scales);
+        Some(row)
+    }
+    fn size_hint(& self) -> (usize, Option<usize>)
+    {
+        //Cancellation can end the rows early.
+        let remaining = self.scales.num_scales - self.index;
+        (if self.transform.cancellation.is_some() { 0 } else { remaining }, Some(remaining))
+    }
+}
+
+/// Input sample type, which is converted to f64 while the input is copied into the FFT buffer.
+pub trait Sample : Copy
+{
+    /// Size of a sample in bytes.
+    const BYTES : usize;
+    /// Sample as f64, divided by the full scale of its type if it is an integer. (-1.0 ..= 1.0)
+    fn to_f64(self) -> f64;
+    /// Sample from its little-endian bytes.
+    fn from_le_bytes(bytes : &[u8]) -> Self;
+}
+impl Sample for f64
+{
+    const BYTES : usize = 8;
+    fn to_f64(self) -> f64 { self }
+    fn from_le_bytes(bytes : &[u8]) -> Self { f64::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]]) }
+}
+impl Sample for f32
+{
+    const BYTES : usize = 4;
+    fn to_f64(self) -> f64 { self as f64 }
+    fn from_le_bytes(bytes : &[u8]) -> Self { f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) }
+}
+/// 16-bit PCM.
+impl Sample for i16
+{
+    const BYTES : usize = 2;
+    fn to_f64(self) -> f64 { self as f64 / 32768.0 }
+    fn from_le_bytes(bytes : &[u8]) -> Self { i16::from_le_bytes([bytes[0], bytes[1]]) }
+}
+/// 32-bit PCM, where 24-bit samples must be shifted to the upper bits.
+impl Sample for i32
+{
+    const BYTES : usize = 4;
+    fn to_f64(self) -> f64 { self as f64 / 2147483648.0 }
+    fn from_le_bytes(bytes : &[u8]) -> Self { i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) }
+}
+/// 8-bit unsigned PCM, whose zero is 128.
+impl Sample for u8
+{
+    const BYTES : usize = 1;
+    fn to_f64(self) -> f64 { (self as f64 - 128.0) / 128.0 }
+    fn from_le_bytes(bytes : &[u8]) -> Self { u8::from_le_bytes([bytes[0]]) }
+}
+
+/// Callback of the progress of a transform, called with the number of completed scales and the number of scales.
+///
+/// It is called from the threads computing the scales, so it should return quickly.
+pub type Progress = alloc::sync::Arc<dyn Fn(usize, usize) + Send + Sync>;
+
+/// Token to cancel running transforms from another thread, which is checked before every scale.
+#[derive(Clone, Default, Debug)]
+pub struct Cancellation(alloc::sync::Arc<core::sync::atomic::AtomicBool>);
+impl Cancellation
+{
+    /// Create a token which is not cancelled.
+    pub fn create() -> Self { Self::default() }
+    /// Cancel the transforms using this token, which skip their remaining scales.
+    pub fn cancel(& self) { self.0.store(true, core::sync::atomic::Ordering::Relaxed); }
+    /// True if the token is cancelled.
+    pub fn is_cancelled(& self) -> bool { self.0.load(core::sync::atomic::Ordering::Relaxed) }
+    /// Clear the cancellation, so that the token can be used again.
+    pub fn reset(& self) { self.0.store(false, core::sync::atomic::Ordering::Relaxed); }
+}
+
 /// Actual continuous wavelet transform.
+///
+/// Clones share the FFT backend, thread pool, GPU device, progress callback, cancellation token and statistics.
+#[derive(Clone)]
 pub struct FastCWT
 {
     wavelet : Wavelet,
-    use_normalization : bool
+    use_normalization : bool,
+    output_mode : OutputMode,
+    hop : usize,
+    pooling : Pooling,
+    padding : Padding,
+    detrend : Detrend,
+    taper : Taper,
+    missing : MissingSamples,
+    fft_length : FftLength,
+    precision : Precision,
+    #[cfg(feature = "parallel")]
+    pool : Option<alloc::sync::Arc<rayon::ThreadPool>>,
+    #[cfg(feature = "gpu")]
+    gpu : Option<alloc::sync::Arc<gpu::Gpu>>,
+    sequential : bool,
+    fft : alloc::sync::Arc<dyn fft::FftBackend>,
+    progress : Option<Progress>,
+    cancellation : Option<Cancellation>,
+    prepared : Option<Prepared>,
+    direct_scale : f64,
+    accuracy : Accuracy,
+    #[cfg(feature = "std")]
+    stats : Option<alloc::sync::Arc<timing::Counters>>
+}
+/// Daughter wavelets sampled for an FFT size and a set of scales by FastCWT::prepare().
+#[derive(Clone)]
+struct Prepared
+{
+    size : usize,
+    accuracy : Accuracy,
+    scales : Box<[f64]>,
+    double : Vec<Vec<f64>>,
+    single : Vec<Vec<f32>>
+}
+/// Spectrum of the padded input, shared by every scale.
+pub(crate) struct Spectrum
+{
+    pub(crate) bins : Vec<Complex<f64>>,
+    /// Position of the first input sample in the padded input.
+    pub(crate) offset : usize,
+    /// Number of input samples.
+    pub(crate) size : usize,
+    /// Padded input in time, which is only kept for the direct convolution of small scales.
+    pub(crate) signal : Option<Vec<Complex<f64>>>,
+    /// Ranges of NaN input samples whose coefficients are masked.
+    pub(crate) gaps : Vec<core::ops::Range<usize>>
+}
+/// Only the configuration, without the shared handles and the prepared daughter wavelets.
+impl core::fmt::Debug for FastCWT
+{
+    fn fmt(& self, f : &mut core::fmt::Formatter<'_>) -> core::fmt::Result
+    {
+        f.debug_struct("FastCWT")
+            .field("wavelet", &self.wavelet)
+            .field("normalization", &self.use_normalization)
+            .field("output_mode", &self.output_mode)
+            .field("hop", &self.hop)
+            .field("pooling", &self.pooling)
+            .field("padding", &self.padding)
+            .field("detrend", &self.detrend)
+            .field("taper", &self.taper)
+            .field("missing", &self.missing)
+            .field("fft_length", &self.fft_length)
+            .field("precision", &self.precision)
+            .field("accuracy", &self.accuracy)
+            .field("sequential", &self.sequential)
+            .field("direct_scale", &self.direct_scale)
+            .finish_non_exhaustive()
+    }
 }
 impl FastCWT
 {
     /// # Arguments
     /// wavelet             - Wavelet object.
     ///
-    /// optplan             - Use FFT optimization plans if true.
-    pub fn create(wavelet : Wavelet, optplan : bool) -> Self { Self { wavelet, use_normalization : optplan, } }
-    /// # Arguments
-    /// input     - Input data in vector format
-    ///
-    /// scales    - Scales object
-    pub fn cwt(&mut self, num : usize, input : &[f64], scales : Scales) -> Vec<rustfft::num_complex::Complex<f64>>
+    /// optplan             - Normalize the coefficients by the FFT size if true.
+    pub fn create(wavelet : Wavelet, optplan : bool) -> Self
     {
-        no_denormals(||
+        Self
         {
-            //Find nearest power of 2
-            let newsize = num.next_power_of_two();
-            let mut buffer = vec![];
-    
-            //Copy input to new input buffer
-            for data in input { buffer.push(rustfft::num_complex::Complex::new(* data, 0.0)); }
-            let mut planner = rustfft::FftPlanner::new();
-
-            // //Perform forward FFT on input signal
-            planner.plan_fft_forward(buffer.len()).process(& mut buffer);
-
-            //Generate mother wavelet function
-            self.wavelet.generate(newsize);
-            for i in 1 .. newsize >> 1 { buffer[newsize - i] = buffer[i]; }
-
-            let buffer = std::sync::Arc::new(std::sync::Mutex::new(Some(buffer)));
-            let buffer_clone = buffer.clone();
-        
-            (0 .. scales.num_scales).into_par_iter().for_each(|i|
-            {
-                if let Ok(mut guard) = buffer_clone.try_lock()
-                {
-                    if let Some(buffer_clone) = guard.as_mut()
-                    {
-                        let mut planner = rustfft::FftPlanner::new();
-                        //FFT-base convolution in the frequency domain
-                        self.daughter_wavelet_multiplication(buffer_clone, self.wavelet.mother.as_slice(), scales.scales[i],num, self.wavelet.imag_freq, self.wavelet.double_sided);
-    
-                        planner.plan_fft_forward(buffer_clone.len()).process(buffer_clone);
-                        if self.use_normalization { for n in 0 .. newsize { buffer_clone[n] = buffer_clone[n] / newsize as f64; } }
-                    }
-                }
-            });
-            return buffer.lock().unwrap().take().unwrap();
-        })
+            wavelet,
+            use_normalization : optplan,
+            output_mode : OutputMode::Complex,
+            hop : 1,
+            pooling : Pooling::Decimate,
+            padding : Padding::Zero,
+            detrend : Detrend::None,
+            taper : Taper::None,
+            missing : MissingSamples::Propagate,
+            fft_length : FftLength::PowerOfTwo,
+            precision : Precision::Double,
+            #[cfg(feature = "parallel")]
+            pool : None,
+            #[cfg(feature = "gpu")]
+            gpu : None,
+            sequential : false,
+            fft : fft::default_backend(),
+            progress : None,
+            cancellation : None,
+            prepared : None,
+            direct_scale : 0.0,
+            accuracy : Accuracy::Fast,
+            #[cfg(feature = "std")]
+            stats : None
+        }
     }
-    fn daughter_wavelet_multiplication(&self, buffer : &mut [rustfft::num_complex::Complex<f64>], mother : &[f64], scale : f64, i_size : usize, imaginary : bool, doublesided : bool)
+    /// Create a builder to configure the transform.
+    ///
+    /// wavelet             - Wavelet object.
+    pub fn builder(wavelet : Wavelet) -> FastCWTBuilder { FastCWTBuilder::create(wavelet) }
+    /// Wavelet of the transform.
+    pub fn wavelet(& self) -> &Wavelet { &self.wavelet }
+    /// True if the coefficients are normalized by the FFT size.
+    pub fn is_normalized(& self) -> bool { self.use_normalization }
+    /// What the transform stores for each coefficient.
+    pub fn output_mode(& self) -> OutputMode { self.output_mode }
+    /// Number of input samples per output sample, and how the samples within a hop are reduced.
+    pub fn hop(& self) -> (usize, Pooling) { (self.hop, self.pooling) }
+    /// How the input is extended to the FFT size.
+    pub fn padding(& self) -> Padding { self.padding }
+    /// Preprocessing of the input.
+    pub fn detrend(& self) -> Detrend { self.detrend }
+    /// Window applied to the edges of the input.
+    pub fn taper(& self) -> Taper { self.taper }
+    /// Handling of NaN samples in the input.
+    pub fn missing(& self) -> MissingSamples { self.missing }
+    /// How the FFT length is chosen.
+    pub fn fft_length(& self) -> FftLength { self.fft_length }
+    /// Floating point precision of the per-scale convolutions.
+    pub fn precision(& self) -> Precision { self.precision }
+    /// How the daughter wavelets are sampled.
+    pub fn accuracy(& self) -> Accuracy { self.accuracy }
+    /// True if all work runs on the calling thread.
+    pub fn is_sequential(& self) -> bool { self.sequential }
+    /// Set what the transform stores for each coefficient. Real modes are converted scale by scale, so the complex coefficients are never stored as a whole.
+    ///
+    /// mode                - Complex | Magnitude | Power | LogPower
+    pub fn set_output_mode(&mut self, mode : OutputMode) { self.output_mode = mode; }
+    /// Store only one sample for every hop samples of each scale.
+    ///
+    /// hop                 - Number of input samples per output sample
+    ///
+    /// pooling             - Decimate | Max | Mean for how the samples within a hop are reduced
+    pub fn set_hop(&mut self, hop : usize, pooling : Pooling)
     {
-        let endpoint = std::cmp::min((i_size as f64 / 2.0) as usize, (i_size as f64 * 2.0 / scale) as usize);
-        let step = scale / 2.0;
+        assert!(hop > 0, "Hop size must be at least 1.");
+        self.hop = hop;
+        self.pooling = pooling;
+    }
+    /// Set how the input is extended to the FFT size. Any padding other than Zero extends both sides by at least half of the input.
+    /// The padded part is not included in the result.
+    ///
+    /// padding             - Zero | Reflect | Symmetric | Periodic | ConstantEdge
+    pub fn set_padding(&mut self, padding : Padding) { self.padding = padding; }
+    /// Set the window applied to the edges of the input, after the preprocessing and before the padding.
+    /// Coefficients near the edges are attenuated as well. cwt_range() and LongSignalCwt apply it to the edges of the whole input.
+    ///
+    /// taper               - None | Tukey | Hann
+    pub fn set_taper(&mut self, taper : Taper)
+    {
+        if let Err(error) = check_taper(taper) { panic!("{}", error); }
+        self.taper = taper;
+    }
+    /// Set how NaN samples in the input are handled, before the preprocessing.
+    ///
+    /// missing             - Propagate | Error | Zero | Interpolate | Mask
+    pub fn set_missing(&mut self, missing : MissingSamples) { self.missing = missing; }
+    /// Set the preprocessing of the input, which applies to every transform of the input, before the padding.
+    ///
+    /// detrend             - None | Mean | Linear | HighPass
+    pub fn set_detrend(&mut self, detrend : Detrend)
+    {
+        if let Err(error) = check_detrend(detrend) { panic!("{}", error); }
+        self.detrend = detrend;
+    }
+    /// Set how the FFT length is chosen from the padded input length.
+    ///
+    /// fft_length          - PowerOfTwo | Exact | Fast
+    pub fn set_fft_length(&mut self, fft_length : FftLength) { self.fft_length = fft_length; }
+    /// Set the floating point precision of the per-scale convolutions.
+    ///
+    /// precision           - Double | Single
+    pub fn set_precision(&mut self, precision : Precision) { self.precision = precision; }
+    /// Set how the daughter wavelets are sampled from the mother wavelet. Only Fast runs on the GPU.
+    ///
+    /// accuracy            - Fast | Interpolated | Exact
+    pub fn set_accuracy(&mut self, accuracy : Accuracy) { self.accuracy = accuracy; }
+    /// Run the transform in a dedicated thread pool with the number of threads.
+    ///
+    /// threads             - Number of threads
+    #[cfg(feature = "parallel")]
+    pub fn set_threads(&mut self, threads : usize) -> Result<(), FastCwtError>
+    {
+        if threads == 0 { return Err(FastCwtError::InvalidThreads); }
+
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().map_err(FastCwtError::ThreadPool)?;
+        self.pool = Some(alloc::sync::Arc::new(pool));
+        Ok(())
+    }
+    /// Run the transform in a thread pool of the application, or in the global rayon pool if None.
+    ///
+    /// pool                - Thread pool handle
+    #[cfg(feature = "parallel")]
+    pub fn set_thread_pool(&mut self, pool : Option<alloc::sync::Arc<rayon::ThreadPool>>) { self.pool = pool; }
+    /// Samples the daughter wavelets of the scales for the FFT size of the signal length once,
+    /// so that later transforms of the same length, scales and precision skip resampling the mother wavelet for every scale.
+    ///
+    /// scales              - Scales object
+    ///
+    /// signal_len          - Number of samples of the signals to transform
+    ///
+    /// The daughter wavelets take up to num_scales × FFT size / 2 values, and they are not used on the GPU.
+    pub fn prepare(&mut self, scales : &Scales, signal_len : usize)
+    {
+        self.prepared = None;
+        if signal_len == 0 { return; }
 
-        let maximum = i_size as f64 - 1.0;
-        let s1 = i_size - 1;
+        let newsize = self.fft_size(signal_len);
+        span!(DEBUG, "prepare", scales = scales.num_scales, fft_length = newsize);
+        self.wavelet.generate(newsize);
+        let (double, single) = match self.precision
+        {
+            Precision::Double => (self.daughters(scales, newsize), vec![]),
+            Precision::Single => (vec![], self.daughters(scales, newsize))
+        };
+        self.prepared = Some(Prepared { size : newsize, accuracy : self.accuracy, scales : scales.scales.clone(), double, single });
+    }
+    /// Drops the daughter wavelets of prepare().
+    pub fn clear_prepared(&mut self) { self.prepared = None; }
+    /// Convolve the small scales in the time domain, where their daughter wavelets are only a few samples long, instead of with an inverse FFT of the full length.
+    ///
+    /// Direct convolution takes about 10 × bandwidth × scale operations per sample, so it is faster than the inverse FFT below a scale of about 4 / bandwidth.
+    /// Its daughter wavelets are sampled in time, so they are not cut at the Nyquist frequency like the ones sampled in frequency. It is not used on the GPU.
+    ///
+    /// max_scale           - Largest scale to convolve directly, or 0.0 to use the FFT for every scale (default)
+    pub fn set_direct_convolution(&mut self, max_scale : f64) { self.direct_scale = max_scale; }
+    /// Set the FFT implementation, which is fft::RustFft by default. Without std, this must be set before the transform is used.
+    ///
+    /// backend             - fft::RustFft | fft::Fftw | any other FftBackend
+    pub fn set_fft_backend(&mut self, backend : alloc::sync::Arc<dyn fft::FftBackend>) { self.fft = backend; }
+    /// Run the per-scale convolutions on the GPU, in single precision regardless of the precision setting.
+    ///
+    /// FFT sizes which are not a power of two, or rows which do not fit in a GPU buffer, are still computed on the CPU.
+    ///
+    /// gpu                 - GPU device, or None to compute on the CPU
+    #[cfg(feature = "gpu")]
+    pub fn set_gpu(&mut self, gpu : Option<alloc::sync::Arc<gpu::Gpu>>) { self.gpu = gpu; }
+    /// Run all work on the calling thread, scale by scale in order, without spawning or using any other thread.
+    ///
+    /// Every scale is computed independently in both modes, so the result is bit-exact with the parallel mode on the same machine.
+    /// The thread pool is not used while this is set.
+    ///
+    /// sequential          - Sequential execution if true
+    pub fn set_sequential(&mut self, sequential : bool) { self.sequential = sequential; }
+    /// Report the progress of every transform after each scale.
+    ///
+    /// progress            - Callback with the number of completed scales and the number of scales, or None
+    pub fn set_progress(&mut self, progress : Option<Progress>) { self.progress = progress; }
+    /// Check the token before every scale, and skip the remaining scales once it is cancelled.
+    ///
+    /// The skipped scales are zero in the result of cwt(), and try_cwt() returns an error instead.
+    ///
+    /// cancellation        - Cancellation token, or None
+    pub fn set_cancellation(&mut self, cancellation : Option<Cancellation>) { self.cancellation = cancellation; }
+    /// # Arguments
+    /// num       - Number of samples to transform
+    ///
+    /// input     - Input data in vector format
+    ///
+    /// scales    - Scales object
+    ///
+    /// Returns the coefficients of every scale in the output mode of the transform.
+    pub fn cwt(&mut self, num : usize, input : &[f64], scales : &Scales) -> CwtResult
+    {
+        assert!(num <= input.len(), "Number of samples cannot be longer than the input.");
 
+        let spectrum = (num > 0).then(|| self.forward(&input[.. num]));
+        self.coefficients(spectrum.as_ref(), num, scales)
+    }
+    /// Same as cwt(), but returns FastCwtError::Cancelled if the cancellation token was cancelled during the transform,
+    /// FastCwtError::InputLength if num is longer than the input,
+    /// and FastCwtError::MissingSample if the input has a NaN sample while missing samples are errors.
+    ///
+    /// # Arguments
+    /// num       - Number of samples to transform
+    ///
+    /// input     - Input data in vector format
+    ///
+    /// scales    - Scales object
+    pub fn try_cwt(&mut self, num : usize, input : &[f64], scales : &Scales) -> Result<CwtResult, FastCwtError>
+    {
+        if num > input.len() { return Err(FastCwtError::InputLength { num, len : input.len() }); }
+        if self.missing == MissingSamples::Error
+        {
+            if let Some(index) = input.iter().take(num).position(|value| value.is_nan()) { return Err(FastCwtError::MissingSample { index }); }
+        }
+        let result = self.cwt(num, input, scales);
+        if self.is_cancelled() { return Err(FastCwtError::Cancelled); }
+        Ok(result)
+    }
+    /// Same as cwt(), but runs in a thread pool the application owns for this call, instead of the pool of the transform or the global pool.
+    ///
+    /// Sequential transforms still run on the calling thread.
+    ///
+    /// # Arguments
+    /// pool      - Thread pool of the application
+    ///
+    /// num       - Number of samples to transform
+    ///
+    /// input     - Input data in vector format
+    ///
+    /// scales    - Scales object
+    #[cfg(feature = "parallel")]
+    pub fn cwt_in_pool(&mut self, pool : &rayon::ThreadPool, num : usize, input : &[f64], scales : &Scales) -> CwtResult
+    {
+        //Parallel work runs in the pool it is called from, as long as the transform does not install its own.
+        let own = self.pool.take();
+        let result = pool.install(|| self.cwt(num, input, scales));
+        self.pool = own;
+        result
+    }
+    /// Same as cwt(), but takes PCM samples like i16 or i32, which are converted on the fly without a converted copy of the input.
+    ///
+    /// # Arguments
+    /// num       - Number of samples to transform
+    ///
+    /// input     - Input samples
+    ///
+    /// scales    - Scales object
+    pub fn cwt_pcm<T : Sample>(&mut self, num : usize, input : &[T], scales : &Scales) -> CwtResult
+    {
+        assert!(num <= input.len(), "Number of samples cannot be longer than the input.");
+
+        let spectrum = (num > 0).then(|| self.forward_from(num, input.iter().map(|value| Complex::new(value.to_f64(), 0.0))));
+        self.coefficients(spectrum.as_ref(), num, scales)
+    }
+    /// Same as cwt(), but takes the input from an iterator, so it is never held in memory except in the FFT buffer.
+    ///
+    /// # Arguments
+    /// input     - Input samples
+    ///
+    /// len_hint  - Number of samples to transform, which sets the FFT size. If the input ends earlier, only the samples until then are transformed.
+    ///
+    /// scales    - Scales object
+    pub fn cwt_from_iter(&mut self, input : impl IntoIterator<Item = f64>, len_hint : usize, scales : &Scales) -> CwtResult
+    {
+        let spectrum = self.forward_from(len_hint, input.into_iter().map(|value| Complex::new(value, 0.0)));
+        let size = spectrum.size;
+        self.coefficients(Some(&spectrum).filter(|_| size > 0), size, scales)
+    }
+    /// Same as cwt_from_iter(), but reads little-endian samples of the type T, like f32, f64 or i16 PCM, from a reader.
+    ///
+    /// # Arguments
+    /// reader    - Reader of raw samples, which is buffered internally
+    ///
+    /// len_hint  - Number of samples to transform. If the reader ends earlier, only the samples until then are transformed.
+    ///
+    /// scales    - Scales object
+    #[cfg(feature = "std")]
+    pub fn cwt_read<T : Sample>(&mut self, reader : impl std::io::Read, len_hint : usize, scales : &Scales) -> std::io::Result<CwtResult>
+    {
+        use std::io::Read;
+
+        let mut reader = std::io::BufReader::new(reader);
+        let mut error = None;
+        let samples = core::iter::from_fn(||
+        {
+            let mut bytes = [0; 8];
+            match reader.read_exact(&mut bytes[.. T::BYTES])
+            {
+                Ok(()) => Some(T::from_le_bytes(&bytes[.. T::BYTES]).to_f64()),
+                Err(read) =>
+                {
+                    if read.kind() != std::io::ErrorKind::UnexpectedEof { error = Some(read); }
+                    None
+                }
+            }
+        });
+        let result = self.cwt_from_iter(samples, len_hint, scales);
+        match error
+        {
+            Some(error) => Err(error),
+            None => Ok(result)
+        }
+    }
+    /// Same as cwt(), but for complex input like IQ data, whose negative frequencies differ from the positive ones.
+    ///
+    /// The wavelet is analytic, so the positive and the negative frequencies are transformed separately with the same scales.
+    /// The result of the negative frequencies is the conjugate of the transform of the conjugate input, whose scales analyze -fs / scale.
+    ///
+    /// # Arguments
+    /// num       - Number of samples to transform
+    ///
+    /// input     - Complex input data
+    ///
+    /// scales    - Scales object
+    ///
+    /// Returns the results of the positive and the negative frequencies.
+    pub fn cwt_complex(&mut self, num : usize, input : &[Complex<f64>], scales : &Scales) -> (CwtResult, CwtResult)
+    {
+        assert!(num <= input.len(), "Number of samples cannot be longer than the input.");
+
+        let input = &input[.. num];
+        let positive = (num > 0).then(|| self.forward_from(num, input.iter().copied()));
+        let negative = (num > 0).then(|| self.forward_from(num, input.iter().map(|value| value.conj())));
+        let positive = self.coefficients(positive.as_ref(), num, scales);
+        let mut negative = self.coefficients(negative.as_ref(), num, scales);
+        negative.conjugate();
+        (positive, negative)
+    }
+    /// Coefficients of only a range of the input samples, which transforms the range with a margin on both sides instead of the whole input.
+    ///
+    /// # Arguments
+    /// input     - Input data in vector format
+    ///
+    /// range     - Range of the input samples, whose start should be a multiple of the hop to keep the hop grid of cwt()
+    ///
+    /// scales    - Scales object
+    ///
+    /// The margin is 5 × bandwidth × the largest scale samples, so the coefficients match the ones of the whole transform like LongSignalCwt.
+    /// Mean and Linear detrending are fitted to the whole input, and the taper is applied to the edges of the whole input, not of the range with its margin.
+    pub fn cwt_range(&mut self, input : &[f64], range : core::ops::Range<usize>, scales : &Scales) -> CwtResult
+    {
+        assert!(range.start <= range.end && range.end <= input.len(), "Range must be within the input.");
+
+        let (margin, hop) = (self.margin(scales), self.hop);
+        //The part before the range is a multiple of the hop, so the columns start at range.start.
+        let lower = range.start - range.start.min(margin) / hop * hop;
+        let upper = (range.end + margin).min(input.len());
+        let result = self.preprocessed(input, |transform, input| transform.cwt(upper - lower, &input[lower .. upper], scales));
+        result.crop((range.start - lower) / hop .. (range.end - lower).div_ceil(hop))
+    }
+    /// Same as cwt(), but writes the complex coefficients into a buffer of the caller, e.g. a pooled buffer or memory shared with FFI or a GPU staging buffer.
+    ///
+    /// The coefficients are complex regardless of the output mode, with the hop and pooling of the transform.
+    /// Rows of scales skipped by a cancellation are left as they are.
+    ///
+    /// # Arguments
+    /// input     - Input data in vector format
+    ///
+    /// scales    - Scales object
+    ///
+    /// output    - Buffer of num_scales × ceil(samples / hop) values, row-major
+    pub fn cwt_into(&mut self, input : &[f64], scales : &Scales, output : &mut [Complex<f64>]) -> Result<(), FastCwtError>
+    {
+        let (hop, pooling) = (self.hop, self.pooling);
+        let expected = scales.num_scales * input.len().div_ceil(hop);
+        if output.len() != expected { return Err(FastCwtError::OutputLength { expected, len : output.len() }); }
+        if input.is_empty() { return Ok(()); }
+
+        let spectrum = self.forward(input);
+        self.convolve_with(&spectrum, scales, output, |row, out|
+        {
+            for (out, window) in out.iter_mut().zip(row.chunks(hop)) { *out = pool(window, pooling, |data| *data); }
+        });
+        Ok(())
+    }
+    /// Transforms the input and hands the complex coefficients of every scale to visit() as soon as the scale is done, instead of keeping the whole scalogram.
+    ///
+    /// # Arguments
+    /// input     - Input data in vector format
+    ///
+    /// scales    - Scales object
+    ///
+    /// visit     - Callback with the index of the scale and its coefficients for every input sample, regardless of the output mode and hop
+    ///
+    /// The row is a buffer that is reused for the next scale, so it must be copied to keep it.
+    /// Scales are visited in any order, and from several threads at once when the transform is parallel.
+    pub fn cwt_for_each_scale(&mut self, input : &[f64], scales : &Scales, visit : impl Fn(usize, &[Complex<f64>]) + Sync)
+    {
+        if input.is_empty() { return; }
+        let spectrum = self.forward(input);
+        //Each output row is a single cell holding the index of its scale, so store() knows which row it gets.
+        let mut indices : Vec<usize> = (0 .. scales.num_scales).collect();
+        self.convolve_with(&spectrum, scales, &mut indices, |row, out| visit(out[0], row));
+    }
+    /// Iterator which computes the complex coefficients of one scale at every call of next(), so only one row is held at a time.
+    ///
+    /// # Arguments
+    /// input     - Input data in vector format
+    ///
+    /// scales    - Scales object
+    ///
+    /// Rows are yielded in the order of the scales, with a coefficient for every input sample regardless of the output mode and hop.
+    /// They are computed in double precision on the calling thread, with one plan and one set of buffers for every row,
+    /// and the progress callback is called after every row with the number of scales.
+    /// Rows do not use the daughter wavelets of prepare(), and the iterator ends early once the transform is cancelled.
+    pub fn cwt_iter<'a>(&'a mut self, input : &[f64], scales : &'a Scales) -> ScaleRows<'a>
+    {
+        let spectrum = (!input.is_empty()).then(|| self.forward(input));
+        let spectrum = spectrum.map(|mut spectrum|
+        {
+            //Kernel holds its own copy of the bins, which the rows read instead.
+            let kernel = self.kernel(&spectrum);
+            spectrum.bins = vec![];
+            (spectrum, kernel)
+        });
+        let newsize = spectrum.as_ref().map_or(0, |(_, kernel)| kernel.bins.len());
+        self.count(0, newsize * core::mem::size_of::<Complex<f64>>() + newsize / 2 * core::mem::size_of::<f64>());
+        let buffers = (vec![Complex::new(0.0, 0.0); newsize], vec![], vec![], vec![]);
+        ScaleRows { transform : self, spectrum, buffers, scales, index : 0, size : input.len() }
+    }
+    /// Coefficients of every scale from the spectrum of the input, in the output mode and the hop of the transform.
+    fn coefficients(&self, spectrum : Option<&Spectrum>, num : usize, scales : &Scales) -> CwtResult
+    {
+        let (mode, hop, pooling) = (self.output_mode, self.hop, self.pooling);
+        let columns = num.div_ceil(hop);
+
+        if mode == OutputMode::Complex
+        {
+            let mut output = vec![Complex::new(0.0, 0.0); scales.num_scales * columns];
+            if let Some(spectrum) = spectrum
+            {
+                self.convolve_with(spectrum, scales, &mut output, |row, out|
+                {
+                    for (out, window) in out.iter_mut().zip(row.chunks(hop)) { *out = pool(window, pooling, |data| *data); }
+                });
+            }
+            return CwtResult::complex(output, scales.clone(), self.wavelet.parameters(), columns, hop, self.use_normalization);
+        }
+
+        let mut output = vec![0.0; scales.num_scales * columns];
+        if let Some(spectrum) = spectrum
+        {
+            self.convolve_with(spectrum, scales, &mut output, |row, out|
+            {
+                for (out, window) in out.iter_mut().zip(row.chunks(hop)) { *out = pool(window, pooling, |data| mode.apply(*data)); }
+            });
+        }
+        CwtResult::real(output, mode, scales.clone(), self.wavelet.parameters(), columns, hop, self.use_normalization)
+    }
+    /// Band-pass filters the input by zeroing the coefficients of the scales outside the band and reconstructing it with the inverse transform.
+    ///
+    /// The filter has zero phase, and its edges are as smooth as the daughter wavelets of the scales at the edges of the band.
+    /// The coefficients are computed in complex without a hop regardless of the settings.
+    ///
+    /// Returns FastCwtError::NotNormalized if the transform is not normalized, which the inverse transform needs.
+    ///
+    /// # Arguments
+    /// input     - Input data in vector format
+    ///
+    /// f_low     - Lowest center frequency of the scales to keep
+    ///
+    /// f_high    - Highest center frequency of the scales to keep
+    ///
+    /// scales    - Scales object, which should be dense enough to overlap (about 8 or more per octave)
+    pub fn bandpass(&mut self, input : &[f64], f_low : f64, f_high : f64, scales : &Scales) -> Result<Vec<f64>, FastCwtError>
+    {
+        if !self.use_normalization { return Err(FastCwtError::NotNormalized); }
+        let size = input.len();
+        let mut output = vec![Complex::new(0.0, 0.0); scales.num_scales * size];
+        self.transform(input, scales, &mut output);
+
+        for (row, (_, frequency)) in output.chunks_mut(size.max(1)).zip(scales.iter())
+        {
+            if frequency < f_low || frequency > f_high { row.fill(Complex::new(0.0, 0.0)); }
+        }
+        Ok(CwtResult::complex(output, scales.clone(), self.wavelet.parameters(), size, 1, true).icwt().unwrap_or_default())
+    }
+    /// Same as cwt(), but takes an ndarray view and returns a (num_scales × samples) matrix.
+    ///
+    /// The coefficients are always complex and not decimated, regardless of the output mode and the hop.
+    ///
+    /// # Arguments
+    /// input     - Input data in ndarray format
+    ///
+    /// scales    - Scales object
+    #[cfg(feature = "ndarray")]
+    pub fn cwt_ndarray(&mut self, input : ndarray::ArrayView1<f64>, scales : &Scales) -> ndarray::Array2<Complex<f64>>
+    {
+        let num = input.len();
+        let mut output = vec![Complex::new(0.0, 0.0); scales.num_scales * num];
+        match input.as_slice()
+        {
+            Some(input) => self.transform(input, scales, &mut output),
+            None => self.transform(input.to_vec().as_slice(), scales, &mut output)
+        };
+        ndarray::Array2::from_shape_vec((scales.num_scales, num), output).expect("Output length always matches scales × samples.")
+    }
+    /// Same as cwt_ndarray(), but takes one signal per row, e.g. trials or sensors, and returns a (signals × num_scales × samples) array.
+    ///
+    /// The signals are transformed in parallel, each with its scales in parallel as well, which keeps the threads busy when the signals are short.
+    ///
+    /// # Arguments
+    /// input     - Input data in ndarray format, one signal per row
+    ///
+    /// scales    - Scales object
+    #[cfg(feature = "ndarray")]
+    pub fn cwt_matrix(&mut self, input : ndarray::ArrayView2<f64>, scales : &Scales) -> ndarray::Array3<Complex<f64>>
+    {
+        let (signals, num) = input.dim();
+        let mut output = vec![Complex::new(0.0, 0.0); signals * scales.num_scales * num];
+        if num > 0 && scales.num_scales > 0
+        {
+            //Every signal has the same FFT size, so the mother wavelet is generated once before they are shared among the threads.
+            self.wavelet.generate(self.fft_size(num));
+            let this = &*self;
+            this.run(|| for_each_row(this.sequential, &mut output, scales.num_scales * num, || (), |_, i, out|
+            {
+                let spectrum = this.spectrum_from(num, input.row(i).iter().map(|value| Complex::new(*value, 0.0)));
+                this.convolve(&spectrum, scales, out);
+            }));
+        }
+        ndarray::Array3::from_shape_vec((signals, scales.num_scales, num), output).expect("Output length always matches signals × scales × samples.")
+    }
+    fn transform(&mut self, input : &[f64], scales : &Scales, output : &mut [Complex<f64>])
+    {
+        if input.is_empty() { return; }
+
+        let spectrum = self.forward(input);
+        self.convolve(&spectrum, scales, output);
+    }
+    /// FFT size of an input of num samples, leaving room on both sides unless it is zero padding.
+    pub(crate) fn fft_size(& self, num : usize) -> usize
+    {
+        let length = if self.padding == Padding::Zero { num } else { 2 * num };
+        match self.fft_length
+        {
+            FftLength::PowerOfTwo => length.next_power_of_two(),
+            FftLength::Exact => length,
+            FftLength::Fast => next_fast_length(length)
+        }
+    }
+    /// Samples around a part of the input that affect its coefficients, which is a multiple of the hop.
+    pub(crate) fn margin(& self, scales : &Scales) -> usize
+    {
+        let widest = scales.scales.iter().fold(0.0_f64, |widest, scale| widest.max(*scale));
+        let margin = (5.0 * self.wavelet.fb * widest).ceil() as usize;
+        margin.div_ceil(self.hop) * self.hop
+    }
+    /// Runs op() on the input detrended and tapered as a whole, with both disabled in the transform, for the transforms which cut the input into parts.
+    ///
+    /// Mean and Linear detrending fit the whole input instead of every part, so the parts share one trend like the whole transform,
+    /// and the taper only attenuates the edges of the whole input instead of the edges of every part.
+    pub(crate) fn preprocessed<R>(&mut self, input : &[f64], op : impl FnOnce(&mut FastCWT, &[f64]) -> R) -> R
+    {
+        if !matches!(self.detrend, Detrend::Mean | Detrend::Linear) && self.taper == Taper::None { return op(self, input); }
+
+        let mut samples : Vec<Complex<f64>> = input.iter().map(|value| Complex::new(*value, 0.0)).collect();
+        detrend(&mut samples, self.detrend);
+        taper(&mut samples, self.taper);
+        let input : Vec<f64> = samples.iter().map(|sample| sample.re).collect();
+
+        let method = if matches!(self.detrend, Detrend::Mean | Detrend::Linear) { core::mem::replace(&mut self.detrend, Detrend::None) } else { self.detrend };
+        let window = core::mem::replace(&mut self.taper, Taper::None);
+        let result = op(self, &input);
+        (self.detrend, self.taper) = (method, window);
+        result
+    }
+    /// Number of threads the scales are computed on.
+    pub(crate) fn threads(& self) -> usize
+    {
+        #[cfg(feature = "parallel")]
+        if !self.sequential { return self.pool.as_ref().map_or_else(rayon::current_num_threads, |pool| pool.current_num_threads()); }
+        1
+    }
+    /// Pads the input to the FFT size, transforms it and generates the mother wavelet for that size.
+    pub(crate) fn forward(&mut self, input : &[f64]) -> Spectrum { self.forward_from(input.len(), input.iter().map(|value| Complex::new(*value, 0.0))) }
+    /// Same as forward(), but takes up to num samples from an iterator, which are copied straight into the FFT buffer.
+    fn forward_from(&mut self, num : usize, samples : impl Iterator<Item = Complex<f64>>) -> Spectrum
+    {
+        let spectrum = self.spectrum_from(num, samples);
+        //Generate mother wavelet function
+        self.wavelet.generate(spectrum.bins.len());
+        spectrum
+    }
+    /// Same as forward_from(), but leaves the mother wavelet as it is, so several inputs of one length can be transformed at once.
+    fn spectrum_from(& self, num : usize, samples : impl Iterator<Item = Complex<f64>>) -> Spectrum
+    {
+        let newsize = self.fft_size(num);
+        let offset = if self.padding == Padding::Zero { 0 } else { (newsize - num) / 2 };
+        span!(DEBUG, "forward", samples = num, fft_length = newsize);
+
+        //Copy input to new input buffer, then pad it to the FFT size from the copied input
+        let mut bins = vec![Complex::new(0.0, 0.0); newsize];
+        let mut size = 0;
+        for (bin, sample) in bins[offset .. offset + num].iter_mut().zip(samples)
+        {
+            *bin = sample;
+            size += 1;
+        }
+        let gaps = fill_missing(&mut bins[offset .. offset + size], self.missing);
+        detrend(&mut bins[offset .. offset + size], self.detrend);
+        taper(&mut bins[offset .. offset + size], self.taper);
+        if size > 0 && self.padding != Padding::Zero
+        {
+            for n in (0 .. offset).chain(offset + size .. newsize)
+            {
+                if let Some(index) = padded_index(n as isize - offset as isize, size, self.padding) { bins[n] = bins[offset + index]; }
+            }
+        }
+
+        let mut signal = (self.direct_scale > 0.0).then(|| bins.clone());
+        self.count(0, newsize * core::mem::size_of::<Complex<f64>>() * if signal.is_some() { 2 } else { 1 });
+
+        //Perform forward FFT on input signal
+        let forward = self.plan_f64(newsize, false);
+        {
+            span!(DEBUG, "forward_fft", fft_length = newsize);
+            self.timed(Phase::Forward, || no_denormals(|| forward.process(&mut bins, &mut vec![])));
+        }
+        if let Detrend::HighPass { cutoff } = self.detrend
+        {
+            for (k, bin) in bins.iter_mut().enumerate()
+            {
+                //|H|^2 = 1 / (1 + (cutoff / f)^8) over the signed frequency of the bin, which is 0 at DC.
+                let frequency = k.min(newsize - k) as f64 / newsize as f64;
+                *bin *= if frequency == 0.0 { 0.0 } else { 1.0 / (1.0 + (cutoff / frequency).powi(8)).sqrt() };
+            }
+
+            //Direct convolution runs on the filtered input as well.
+            if let Some(signal) = signal.as_mut()
+            {
+                signal.copy_from_slice(&bins);
+                no_denormals(|| self.fft.plan_f64(newsize, true).process(signal, &mut vec![]));
+                for sample in signal.iter_mut() { *sample /= newsize as f64; }
+            }
+        }
+
+        Spectrum { bins, offset, size, signal, gaps }
+    }
+    /// Multiplies the spectrum with every daughter wavelet and writes the input part of each inverse FFT.
+    pub(crate) fn convolve(&self, spectrum : &Spectrum, scales : &Scales, output : &mut [Complex<f64>])
+    {
+        self.convolve_with(spectrum, scales, output, |row, out| out.copy_from_slice(row));
+    }
+    /// Same as convolve(), but hands the input part of each inverse FFT to store(), which fills the output row of that scale.
+    pub(crate) fn convolve_with<T : Send>(&self, spectrum : &Spectrum, scales : &Scales, output : &mut [T], store : impl Fn(&[Complex<f64>], &mut [T]) + Sync)
+    {
+        #[cfg(feature = "gpu")]
+        if let Some(gpu) = self.gpu.as_ref().filter(|gpu| self.accuracy == Accuracy::Fast && gpu.supports(spectrum.bins.len())) { return gpu.convolve(self, spectrum, scales, output, &store); }
+
+        match self.precision
+        {
+            Precision::Double => self.run(|| self.convolve_in::<f64, T>(spectrum, scales, output, &store)),
+            Precision::Single => self.run(|| self.convolve_in::<f32, T>(spectrum, scales, output, &store))
+        }
+    }
+    /// Plans a double precision FFT with the backend of the transform.
+    pub(crate) fn plan_f64(& self, length : usize, inverse : bool) -> Box<dyn fft::FftPlan<f64>>
+    {
+        span!(DEBUG, "fft_plan", fft_length = length, inverse = inverse);
+        self.fft.plan_f64(length, inverse)
+    }
+    /// True if the cancellation token is cancelled.
+    pub(crate) fn is_cancelled(& self) -> bool { self.cancellation.as_ref().is_some_and(|cancellation| cancellation.is_cancelled()) }
+    /// Calls the progress callback if there is one.
+    pub(crate) fn report(& self, completed : usize, total : usize)
+    {
+        if let Some(progress) = &self.progress { progress(completed, total); }
+    }
+    /// Runs the work in the thread pool of the transform, or in the global rayon pool if there is none.
+    pub(crate) fn run<R : Send>(&self, work : impl FnOnce() -> R + Send) -> R
+    {
+        #[cfg(feature = "parallel")]
+        if let Some(pool) = self.pool.as_ref().filter(|_| !self.sequential) { return pool.install(work); }
+        work()
+    }
+    fn convolve_in<F : FftFloat, T : Send>(&self, spectrum : &Spectrum, scales : &Scales, output : &mut [T], store : &(impl Fn(&[Complex<f64>], &mut [T]) + Sync))
+    {
+        span!(DEBUG, "convolve", scales = scales.num_scales, fft_length = spectrum.bins.len());
+        let completed = core::sync::atomic::AtomicUsize::new(0);
+        self.convolve_rows(spectrum, &self.kernel::<F>(spectrum), scales, 0 .. scales.num_scales, output, store, &completed);
+    }
+    /// Same as convolve(), but a batch of scales at a time, handing the rows of every batch to write() in order, so only one batch is kept at once.
+    ///
+    /// The spectrum, the plan and the prepared daughter wavelets are shared by all the batches. Stops at the first error of write().
+    #[cfg(feature = "std")]
+    pub(crate) fn convolve_batches<E>(&self, spectrum : &Spectrum, scales : &Scales, batch : usize, mut write : impl FnMut(&[Complex<f64>]) -> Result<(), E>) -> Result<(), E>
+    {
+        let batch = core::cmp::max(1, batch);
+        #[cfg(feature = "gpu")]
+        if self.gpu.as_ref().is_some_and(|gpu| self.accuracy == Accuracy::Fast && gpu.supports(spectrum.bins.len()))
+        {
+            let mut buffer = vec![Complex::new(0.0, 0.0); batch * spectrum.size];
+            for start in (0 .. scales.num_scales).step_by(batch)
+            {
+                let group = scales.slice(start .. (start + batch).min(scales.num_scales));
+                let buffer = &mut buffer[.. group.num_scales * spectrum.size];
+                self.convolve(spectrum, &group, buffer);
+                for row in buffer.chunks(spectrum.size) { write(row)?; }
+            }
+            return Ok(());
+        }
+
+        match self.precision
+        {
+            Precision::Double => self.batches_in::<f64, E>(spectrum, scales, batch, &mut write),
+            Precision::Single => self.batches_in::<f32, E>(spectrum, scales, batch, &mut write)
+        }
+    }
+    #[cfg(feature = "std")]
+    fn batches_in<F : FftFloat, E>(&self, spectrum : &Spectrum, scales : &Scales, batch : usize, write : &mut impl FnMut(&[Complex<f64>]) -> Result<(), E>) -> Result<(), E>
+    {
+        span!(DEBUG, "convolve", scales = scales.num_scales, fft_length = spectrum.bins.len());
+        let mut buffer = vec![Complex::new(0.0, 0.0); batch * spectrum.size];
+        let kernel = self.kernel::<F>(spectrum);
+        let completed = core::sync::atomic::AtomicUsize::new(0);
+        for start in (0 .. scales.num_scales).step_by(batch)
+        {
+            let rows = start .. (start + batch).min(scales.num_scales);
+            let buffer = &mut buffer[.. rows.len() * spectrum.size];
+            self.run(|| self.convolve_rows(spectrum, &kernel, scales, rows, buffer, &|row, out : &mut [Complex<f64>]| out.copy_from_slice(row), &completed));
+            for row in buffer.chunks(spectrum.size) { write(row)?; }
+        }
+        Ok(())
+    }
+    /// Convolves the rows of scales within range into output, reporting the progress against all the scales.
+    #[allow(clippy::too_many_arguments)]
+    fn convolve_rows<F : FftFloat, T : Send>(&self, spectrum : &Spectrum, kernel : &Kernel<F>, scales : &Scales, rows : core::ops::Range<usize>, output : &mut [T], store : &(impl Fn(&[Complex<f64>], &mut [T]) + Sync), completed : &core::sync::atomic::AtomicUsize)
+    {
+        let newsize = spectrum.bins.len();
+
+        //Output rows can be shorter than size when store() decimates.
+        let columns = output.len() / core::cmp::max(1, rows.len());
+        if columns == 0 { return; }
+
+        let zero = Complex::new(F::zero(), F::zero());
+        let prepared = self.prepared.as_ref().filter(|prepared| prepared.size == newsize && prepared.accuracy == self.accuracy && prepared.scales == scales.scales).map(F::prepared).filter(|prepared| prepared.len() == scales.num_scales);
+
+        let init = || -> RowBuffers<F>
+        {
+            //Buffer of the inverse FFT and the daughter wavelet of every thread.
+            self.count(0, newsize * core::mem::size_of::<Complex<F>>() + newsize / 2 * core::mem::size_of::<F>());
+            (vec![zero; newsize], vec![], vec![], vec![])
+        };
+        self.timed(Phase::Convolution, || for_each_row(self.sequential, output, columns, init, |buffers, i, row|
+        {
+            if self.is_cancelled() { return; }
+            let i = rows.start + i;
+            span!(TRACE, "scale", index = i, scale = scales.scales[i], fft_length = newsize, samples = spectrum.size);
+            let gains = prepared.map(|prepared| prepared[i].as_slice());
+            self.convolve_scale(spectrum, kernel, gains, scales.scales[i], buffers, |coefficients| store(coefficients, row));
+            self.count(1, 0);
+            self.report(completed.fetch_add(1, core::sync::atomic::Ordering::Relaxed) + 1, scales.num_scales);
+        }));
+    }
+    /// Spectrum and mother wavelet in the precision of the convolution, with the inverse FFT plan, which every scale of the spectrum shares.
+    fn kernel<F : FftFloat>(& self, spectrum : &Spectrum) -> Kernel<F>
+    {
+        let newsize = spectrum.bins.len();
+        let inverse =
+        {
+            span!(DEBUG, "fft_plan", fft_length = newsize, inverse = true);
+            F::plan(self.fft.as_ref(), newsize, true)
+        };
+        let bins = spectrum.bins.iter().map(|bin| Complex::new(F::from_f64(bin.re).unwrap_or(F::zero()), F::from_f64(bin.im).unwrap_or(F::zero()))).collect();
+        let mother = self.wavelet.mother.iter().map(|value| F::from_f64(*value).unwrap_or(F::zero())).collect();
+        self.count(0, newsize * (core::mem::size_of::<Complex<F>>() + core::mem::size_of::<F>()));
+        Kernel { bins, mother, inverse }
+    }
+    /// Coefficients of one scale, which are handed to store() from the buffers, so the buffers and the plan are reused from scale to scale.
+    ///
+    /// gains are the prepared daughter wavelet of the scale, or None to generate it from the mother wavelet.
+    fn convolve_scale<F : FftFloat>(&self, spectrum : &Spectrum, kernel : &Kernel<F>, gains : Option<&[F]>, scale : f64, (buffer, scratch, widened, daughter) : &mut RowBuffers<F>, store : impl FnOnce(&[Complex<f64>]))
+    {
+        let (newsize, offset, size) = (kernel.bins.len(), spectrum.offset, spectrum.size);
         no_denormals(||
         {
-            for n in 0 .. endpoint
+            if let Some(signal) = spectrum.signal.as_deref().filter(|_| !self.wavelet.double_sided && scale <= self.direct_scale)
             {
-                let tmp = std::cmp::min(maximum as usize, step as usize * n);
-    
-                if doublesided
+                self.timed(Phase::Multiply, || self.direct(signal, scale, offset, size, widened));
+                self.timed(Phase::Normalization, ||
                 {
-                    buffer[s1 - n].re = if imaginary { buffer[s1 - n].re * mother[tmp as usize] } else { buffer[s1 - n].re * mother[tmp as usize] * -1.0 };
-                    buffer[s1 - n].im = buffer[s1 - n].im * mother[tmp as usize];
-                } else { buffer[n].re = buffer[n].re * mother[tmp as usize]; buffer[n].im = buffer[n].im * mother[tmp as usize]; }
+                    self.mask(widened, &spectrum.gaps, scale);
+                    store(widened);
+                });
+                return;
             }
+
+            //FFT-base convolution in the frequency domain
+            self.timed(Phase::Multiply, ||
+            {
+                let gains = match gains
+                {
+                    Some(gains) => gains,
+                    None =>
+                    {
+                        self.daughter(&kernel.mother, scale, newsize, daughter);
+                        &*daughter
+                    }
+                };
+                self.daughter_wavelet_multiplication(&kernel.bins, buffer, gains);
+            });
+            self.timed(Phase::Inverse, || kernel.inverse.process(buffer, scratch));
+
+            self.timed(Phase::Normalization, ||
+            {
+                let buffer = &mut buffer[offset .. offset + size];
+                if self.use_normalization
+                {
+                    let newsize = F::from_usize(newsize).unwrap_or(F::one());
+                    for data in buffer.iter_mut() { *data = *data / newsize; }
+                }
+                self.mask(buffer, &spectrum.gaps, scale);
+                store(F::widen(buffer, widened));
+            });
         });
     }
-}
\ No newline at end of file
+    /// Sets the coefficients of a scale within the cone of influence of every gap to NaN.
+    pub(crate) fn mask<F : num_traits::Float>(& self, row : &mut [Complex<F>], gaps : &[core::ops::Range<usize>], scale : f64)
+    {
+        let reach = (core::f64::consts::SQRT_2 * self.wavelet.fb * scale).ceil() as usize;
+        for gap in gaps
+        {
+            let (start, end) = (gap.start.saturating_sub(reach), (gap.end + reach).min(row.len()));
+            for data in row[start .. end].iter_mut() { *data = Complex::new(F::nan(), F::nan()); }
+        }
+    }
+    /// Convolves the padded input with the daughter wavelet of the scale in the time domain, for the input part of the output.
+    fn direct(&self, signal : &[Complex<f64>], scale : f64, offset : usize, size : usize, output : &mut Vec<Complex<f64>>)
+    {
+        let newsize = signal.len() as isize;
+        let support = (5.0 * self.wavelet.fb * scale).ceil() as isize;
+
+        //Without normalization the FFT path is not divided by the FFT size, so neither is this one.
+        let mut taps = self.wavelet.daughter_in_time(scale, support);
+        if !self.use_normalization { for tap in taps.iter_mut() { *tap *= newsize as f64; } }
+
+        //Indices wrap around like the circular convolution of the FFT path.
+        output.clear();
+        output.extend((offset .. offset + size).map(|n| taps.iter().zip(- support ..).map(|(tap, k)| signal[(n as isize - k).rem_euclid(newsize) as usize] * tap).sum::<Complex<f64>>()));
+    }
+    /// Daughter wavelets of every scale for the FFT size, from the generated mother wavelet.
+    fn daughters<F : FftFloat>(&self, scales : &Scales, size : usize) -> Vec<Vec<F>>
+    {
+        let mother : Vec<F> = self.wavelet.mother.iter().map(|value| F::from_f64(*value).unwrap_or(F::zero())).collect();
+        scales.scales.iter().map(|scale|
+        {
+            let mut gains = vec![];
+            self.daughter(&mother, *scale, size, &mut gains);
+            gains
+        }).collect()
+    }
+    /// Samples the mother wavelet at the scale up to the endpoint of the daughter wavelet.
+    fn daughter<F : FftFloat>(&self, mother : &[F], scale : f64, size : usize, gains : &mut Vec<F>)
+    {
+        let endpoint = core::cmp::min(size / 2, (size as f64 * 2.0 / scale) as usize);
+        let step = scale / 2.0;
+        let maximum = size - 1;
+
+        //Gather the daughter wavelet first, so that the multiplication runs over contiguous memory.
+        let gain = F::from_f64(self.wavelet.gain(scale)).unwrap_or(F::one());
+        gains.clear();
+        match self.accuracy
+        {
+            Accuracy::Fast => gains.extend((0 .. endpoint).map(|n| mother[core::cmp::min(maximum, (step * n as f64) as usize)] * gain)),
+            Accuracy::Interpolated => gains.extend((0 .. endpoint).map(|n|
+            {
+                let position = step * n as f64;
+                let (index, fraction) = (position as usize, F::from_f64(position.fract()).unwrap_or(F::zero()));
+                let (lower, upper) = (mother[core::cmp::min(maximum, index)], mother[core::cmp::min(maximum, index + 1)]);
+                (lower + (upper - lower) * fraction) * gain
+            })),
+            //Frequency of bin n is u = scale × n / size relative to the center frequency of the scale.
+            Accuracy::Exact => gains.extend((0 .. endpoint).map(|n| F::from_f64(self.wavelet.spectrum(scale * n as f64 / size as f64) * self.wavelet.gain(scale)).unwrap_or(F::zero())))
+        }
+    }
+    fn daughter_wavelet_multiplication<F : FftFloat>(&self, input : &[Complex<F>], output : &mut [Complex<F>], gains : &[F])
+    {
+        let endpoint = gains.len();
+        let s1 = input.len() - 1;
+
+        output.fill(Complex::new(F::zero(), F::zero()));
+        F::multiply(&input[.. endpoint], gains, &mut output[.. endpoint]);
+        if self.wavelet.double_sided
+        {
+            for (n, gain) in gains.iter().enumerate()
+            {
+                output[s1 - n].re = if self.wavelet.imag_freq { input[s1 - n].re * *gain } else { -input[s1 - n].re * *gain };
+                output[s1 - n].im = input[s1 - n].im * *gain;
+            }
+        }
+    }
+}
+
+/// Buffer of the inverse FFT, its scratch, the row widened to f64 and the daughter wavelet, which a thread reuses from scale to scale.
+type RowBuffers<F> = (Vec<Complex<F>>, Vec<Complex<F>>, Vec<Complex<f64>>, Vec<F>);
+/// Spectrum of the input and mother wavelet in the precision of the convolution, with the plan of the inverse FFT.
+struct Kernel<F>
+{
+    bins : Vec<Complex<F>>,
+    mother : Vec<F>,
+    inverse : Box<dyn fft::FftPlan<F>>
+}
+/// Floating point type the per-scale convolutions are computed in.
+trait FftFloat : num_traits::Float + num_traits::FromPrimitive + Send + Sync + core::fmt::Debug + 'static
+{
+    /// Converts a row to f64, using the buffer only if the row is not f64 already.
+    fn widen<'a>(row : &'a [Complex<Self>], buffer : &'a mut Vec<Complex<f64>>) -> &'a [Complex<f64>];
+    /// Plans an FFT in this precision.
+    fn plan(backend : &dyn fft::FftBackend, length : usize, inverse : bool) -> Box<dyn fft::FftPlan<Self>>;
+    /// Prepared daughter wavelets in this precision, which are empty if they were prepared in the other one.
+    fn prepared(prepared : &Prepared) -> &[Vec<Self>];
+    /// output[n] = input[n] × gains[n]
+    fn multiply(input : &[Complex<Self>], gains : &[Self], output : &mut [Complex<Self>])
+    {
+        for ((out, data), gain) in output.iter_mut().zip(input).zip(gains) { *out = data * *gain; }
+    }
+}
+impl FftFloat for f64
+{
+    fn widen<'a>(row : &'a [Complex<f64>], _ : &'a mut Vec<Complex<f64>>) -> &'a [Complex<f64>] { row }
+    fn plan(backend : &dyn fft::FftBackend, length : usize, inverse : bool) -> Box<dyn fft::FftPlan<f64>> { backend.plan_f64(length, inverse) }
+    fn prepared(prepared : &Prepared) -> &[Vec<f64>] { &prepared.double }
+    #[cfg(feature = "simd")]
+    fn multiply(input : &[Complex<f64>], gains : &[f64], output : &mut [Complex<f64>])
+    {
+        //2 complex values per f64x4 vector, with each gain duplicated to the real and the imaginary part.
+        let whole = gains.len() / 2 * 2;
+        for ((out, data), gain) in output[.. whole].chunks_exact_mut(2).zip(input.chunks_exact(2)).zip(gains.chunks_exact(2))
+        {
+            let product = (wide::f64x4::from([data[0].re, data[0].im, data[1].re, data[1].im]) * wide::f64x4::from([gain[0], gain[0], gain[1], gain[1]])).to_array();
+            out[0] = Complex::new(product[0], product[1]);
+            out[1] = Complex::new(product[2], product[3]);
+        }
+        for ((out, data), gain) in output[whole ..].iter_mut().zip(&input[whole ..]).zip(&gains[whole ..]) { *out = data * *gain; }
+    }
+}
+impl FftFloat for f32
+{
+    fn widen<'a>(row : &'a [Complex<f32>], buffer : &'a mut Vec<Complex<f64>>) -> &'a [Complex<f64>]
+    {
+        buffer.clear();
+        buffer.extend(row.iter().map(|data| Complex::new(data.re as f64, data.im as f64)));
+        buffer
+    }
+    fn plan(backend : &dyn fft::FftBackend, length : usize, inverse : bool) -> Box<dyn fft::FftPlan<f32>> { backend.plan_f32(length, inverse) }
+    fn prepared(prepared : &Prepared) -> &[Vec<f32>] { &prepared.single }
+    #[cfg(feature = "simd")]
+    fn multiply(input : &[Complex<f32>], gains : &[f32], output : &mut [Complex<f32>])
+    {
+        //4 complex values per f32x8 vector, with each gain duplicated to the real and the imaginary part.
+        let whole = gains.len() / 4 * 4;
+        for ((out, data), gain) in output[.. whole].chunks_exact_mut(4).zip(input.chunks_exact(4)).zip(gains.chunks_exact(4))
+        {
+            let data = wide::f32x8::from([data[0].re, data[0].im, data[1].re, data[1].im, data[2].re, data[2].im, data[3].re, data[3].im]);
+            let gain = wide::f32x8::from([gain[0], gain[0], gain[1], gain[1], gain[2], gain[2], gain[3], gain[3]]);
+            let product = (data * gain).to_array();
+            for (k, out) in out.iter_mut().enumerate() { *out = Complex::new(product[2 * k], product[2 * k + 1]); }
+        }
+        for ((out, data), gain) in output[whole ..].iter_mut().zip(&input[whole ..]).zip(&gains[whole ..]) { *out = data * *gain; }
+    }
+}
+
+/// Analytic signal of the input, whose real part is the input and imaginary part is its Hilbert transform.
+///
+/// The FFT has the exact length of the input, so the input is treated as periodic like scipy.signal.hilbert().
+/// Its magnitude is the envelope and its argument is the instantaneous phase, which the coefficients of a scale approximate within its band.
+#[cfg(feature = "std")]
+pub fn hilbert(input : &[f64]) -> Vec<Complex<f64>>
+{
+    let size = input.len();
+    if size == 0 { return vec![]; }
+
+    let backend = fft::default_backend();
+    let mut buffer : Vec<Complex<f64>> = input.iter().map(|value| Complex::new(*value, 0.0)).collect();
+    let mut scratch = vec![];
+    no_denormals(|| backend.plan_f64(size, false).process(&mut buffer, &mut scratch));
+
+    //Positive frequencies are doubled and negative ones removed, while DC and Nyquist are kept as they are.
+    for (k, bin) in buffer.iter_mut().enumerate()
+    {
+        let gain = if k == 0 || 2 * k == size { 1.0 } else if 2 * k < size { 2.0 } else { 0.0 };
+        *bin *= gain / size as f64;
+    }
+    no_denormals(|| backend.plan_f64(size, true).process(&mut buffer, &mut scratch));
+    buffer
+}
+
+/// Checks the fraction of a Tukey taper.
+pub(crate) fn check_taper(taper : Taper) -> Result<(), FastCwtError>
+{
+    match taper
+    {
+        Taper::Tukey { fraction } if !(0.0 ..= 1.0).contains(&fraction) => Err(FastCwtError::InvalidTaper(fraction)),
+        _ => Ok(())
+    }
+}
+/// Checks the cutoff of a high-pass detrending.
+pub(crate) fn check_detrend(detrend : Detrend) -> Result<(), FastCwtError>
+{
+    match detrend
+    {
+        Detrend::HighPass { cutoff } if !(cutoff > 0.0 && cutoff < 0.5) => Err(FastCwtError::InvalidCutoff(cutoff)),
+        _ => Ok(())
+    }
+}
+/// Replaces the NaN samples as the policy says, in place, and returns the ranges of NaN samples if they are masked.
+fn fill_missing(samples : &mut [Complex<f64>], policy : MissingSamples) -> Vec<core::ops::Range<usize>>
+{
+    if policy == MissingSamples::Propagate { return vec![]; }
+    let missing = |sample : &Complex<f64>| sample.re.is_nan() || sample.im.is_nan();
+
+    let mut gaps = vec![];
+    let mut next = 0;
+    while let Some(start) = samples[next ..].iter().position(missing).map(|index| next + index)
+    {
+        assert!(policy != MissingSamples::Error, "Input has a NaN sample at index {}.", start);
+        let end = samples[start ..].iter().position(|sample| !missing(sample)).map_or(samples.len(), |index| start + index);
+
+        //Line from the sample before the gap to the sample after it.
+        let (before, after) = (start.checked_sub(1).map(|index| samples[index]), samples.get(end).copied());
+        let length = (end - start + 1) as f64;
+        for (k, sample) in samples[start .. end].iter_mut().enumerate()
+        {
+            *sample = match (policy, before, after)
+            {
+                (MissingSamples::Zero, _, _) | (_, None, None) => Complex::new(0.0, 0.0),
+                (_, Some(before), Some(after)) => before + (after - before) * ((k + 1) as f64 / length),
+                (_, Some(edge), None) | (_, None, Some(edge)) => edge
+            };
+        }
+        gaps.push(start .. end);
+        next = end;
+    }
+    if policy == MissingSamples::Mask { gaps } else { vec![] }
+}
+/// Subtracts the mean or the least-squares line from the samples, in place.
+///
+/// NaN samples are left out of the fit and stay NaN.
+fn detrend(samples : &mut [Complex<f64>], method : Detrend)
+{
+    if !matches!(method, Detrend::Mean | Detrend::Linear) { return; }
+    let valid = || samples.iter().enumerate().filter(|(_, sample)| !sample.is_nan());
+    let size = valid().count() as f64;
+    if size == 0.0 { return; }
+    let mean = valid().map(|(_, sample)| sample).sum::<Complex<f64>>() / size;
+    match method
+    {
+        Detrend::Mean => for sample in samples.iter_mut() { *sample -= mean; },
+        Detrend::Linear =>
+        {
+            //Slope of the line through the centered sample positions.
+            let center = valid().map(|(n, _)| n as f64).sum::<f64>() / size;
+            let spread = valid().map(|(n, _)| (n as f64 - center).powi(2)).sum::<f64>();
+            let slope = if spread > 0.0 { valid().map(|(n, sample)| (sample - mean) * (n as f64 - center)).sum::<Complex<f64>>() / spread } else { Complex::new(0.0, 0.0) };
+            for (n, sample) in samples.iter_mut().enumerate() { *sample -= mean + slope * (n as f64 - center); }
+        }
+        _ => {}
+    }
+}
+/// Multiplies the edges of the samples with the window, in place.
+fn taper(samples : &mut [Complex<f64>], window : Taper)
+{
+    let fraction = match window
+    {
+        Taper::None => return,
+        Taper::Tukey { fraction } => fraction,
+        Taper::Hann => 1.0
+    };
+    if samples.len() < 2 || fraction <= 0.0 { return; }
+
+    let last = (samples.len() - 1) as f64;
+    for (n, sample) in samples.iter_mut().enumerate()
+    {
+        //Distance to the nearest edge relative to the length of a ramp.
+        let x = (n as f64).min(last - n as f64) / last / (fraction / 2.0);
+        if x < 1.0 { *sample *= 0.5 * (1.0 - (core::f64::consts::PI * x).cos()); }
+    }
+}
+/// Runs op(state, index, row) on every row of the output, in parallel unless sequential. Each rayon job gets its own state from init().
+///
+/// Without the parallel feature, the rows are always processed sequentially.
+pub(crate) fn for_each_row<T : Send, S>(sequential : bool, output : &mut [T], columns : usize, init : impl Fn() -> S + Sync + Send, op : impl Fn(&mut S, usize, &mut [T]) + Sync + Send)
+{
+    #[cfg(feature = "parallel")]
+    if !sequential { return output.par_chunks_mut(columns).enumerate().for_each_init(init, |state, (i, row)| op(state, i, row)); }
+    #[cfg(not(feature = "parallel"))]
+    let _ = sequential;
+
+    let mut state = init();
+    for (i, row) in output.chunks_mut(columns).enumerate() { op(&mut state, i, row); }
+}
+/// Runs f with denormals flushed to zero, which needs std.
+#[cfg(feature = "std")]
+fn no_denormals<T>(f : impl FnOnce() -> T) -> T { ::no_denormals::no_denormals(f) }
+/// Runs f with denormals flushed to zero, which needs std.
+#[cfg(not(feature = "std"))]
+fn no_denormals<T>(f : impl FnOnce() -> T) -> T { f() }
+/// Smallest length not shorter than the given length, which only has 2, 3, 5 and 7 as prime factors.
+fn next_fast_length(length : usize) -> usize
+{
+    (core::cmp::max(1, length) ..).find(|candidate|
+    {
+        let mut remainder = *candidate;
+        for prime in [2, 3, 5, 7] { while remainder % prime == 0 { remainder /= prime; } }
+        remainder == 1
+    }).expect("There is always a 7-smooth length.")
+}
+/// Index of the input sample at a position relative to the first input sample, or None for zero.
+fn padded_index(position : isize, size : usize, padding : Padding) -> Option<usize>
+{
+    let length = size as isize;
+    if (0 .. length).contains(&position) { return Some(position as usize); }
+
+    let index = match padding
+    {
+        Padding::Zero => return None,
+        Padding::Periodic => position.rem_euclid(length),
+        Padding::ConstantEdge => position.clamp(0, length - 1),
+        Padding::Symmetric =>
+        {
+            let folded = position.rem_euclid(2 * length);
+            if folded < length { folded } else { 2 * length - 1 - folded }
+        }
+        Padding::Reflect =>
+        {
+            if length == 1 { return Some(0); }
+            let folded = position.rem_euclid(2 * length - 2);
+            if folded < length { folded } else { 2 * length - 2 - folded }
+        }
+    };
+    Some(index as usize)
+}
+/// Reduces the samples within a hop to one value.
+fn pool<T>(window : &[Complex<f64>], pooling : Pooling, value : impl Fn(&Complex<f64>) -> T) -> T where T : core::iter::Sum<T> + core::ops::Div<f64, Output = T>
+{
+    match pooling
+    {
+        Pooling::Decimate => value(&window[0]),
+        Pooling::Max => value(window.iter().max_by(|a, b| a.norm_sqr().total_cmp(&b.norm_sqr())).unwrap_or(&window[0])),
+        Pooling::Mean => window.iter().map(value).sum::<T>() / window.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn log_scales_span_frequency_range()
+    {
+        let scales = Scales::create(ScaleTypes::Log, 100.0, 1.0, 50.0, 5);
+        let frequencies = scales.frequencies();
+        assert!((frequencies[0] - 50.0).abs() < 1e-9);
+        assert!((frequencies[4] - 1.0).abs() < 1e-9);
+        //Evenly spaced in log frequency, from f1 down to f0.
+        for pair in frequencies.windows(2) { assert!((pair[0] / pair[1] - 50.0_f64.powf(0.25)).abs() < 1e-9); }
+
+        for st in [ScaleTypes::Linear, ScaleTypes::Log, ScaleTypes::LinFreq, ScaleTypes::Mel, ScaleTypes::ERB]
+        {
+            assert!(Scales::try_create(st, 44100.0, 20.0, 22050.0, 64).is_ok(), "{:?} fails at the Nyquist frequency.", st);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! MATLAB 7.3 .mat files of results, which are HDF5 files with a MATLAB header.
+use crate::*;
+use ::hdf5::types::FixedAscii;
+use std::io::Write;
+
+/// Complex coefficient as the compound MATLAB reads as a complex double.
+#[derive(::hdf5::H5Type, Clone, Copy)]
+#[repr(C)]
+struct MatComplex
+{
+    real : f64,
+    imag : f64
+}
+
+impl CwtResult
+{
+    /// Writes the result into a MATLAB 7.3 .mat file, with the variables of [wt, f, coi] = cwt(x, fs) in MATLAB.
+    ///
+    /// wt is the num_scales × num_samples matrix of the coefficients, which are complex if the output mode is Complex.
+    /// f and coi are column vectors of the frequencies and the cone of influence in Hz, and fs is the sample rate.
+    pub fn save_mat(& self, path : impl AsRef<std::path::Path>) -> ::hdf5::Result<()>
+    {
+        let path = path.as_ref();
+        let (rows, columns) = (self.num_scales(), self.num_samples());
+        {
+            //HDF5 leaves the first 512 bytes to the MATLAB header.
+            let file = ::hdf5::File::with_options().with_fcpl(|fcpl| fcpl.userblock(512)).create(path)?;
+
+            //MATLAB is column-major, so a matrix is stored transposed with the row index varying fastest.
+            let transposed = (0 .. columns).flat_map(|column| (0 .. rows).map(move |row| row * columns + column));
+            match self.complex_coefficients()
+            {
+                Some(coefficients) =>
+                {
+                    let wt : Vec<MatComplex> = transposed.map(|index| MatComplex { real : coefficients[index].re, imag : coefficients[index].im }).collect();
+                    variable(&file, "wt", &wt, (columns, rows))?;
+                }
+                None =>
+                {
+                    let values = self.real_coefficients().unwrap_or_default();
+                    let wt : Vec<f64> = transposed.map(|index| values[index]).collect();
+                    variable(&file, "wt", &wt, (columns, rows))?;
+                }
+            }
+            variable(&file, "f", &self.frequencies(), (1, rows))?;
+            variable(&file, "coi", &self.coi(), (1, columns))?;
+            variable(&file, "fs", &[self.fs()], (1, 1))?;
+        }
+
+        //Header text is 116 bytes, followed by the subsystem data offset, the version 0x0200 and the endian indicator.
+        let mut header = format!("MATLAB 7.3 MAT-file, Platform: {}, Created by: fastcwt HDF5 schema 1.00 .", std::env::consts::OS).into_bytes();
+        header.resize(116, b' ');
+        header.extend_from_slice(&[0; 8]);
+        header.extend_from_slice(&[0x00, 0x02, b'I', b'M']);
+
+        let io_error = |error : std::io::Error| ::hdf5::Error::from(error.to_string());
+        let mut file = std::fs::OpenOptions::new().write(true).open(path).map_err(io_error)?;
+        file.write_all(&header).map_err(io_error)
+    }
+}
+
+/// Writes a double variable, whose shape is the reverse of the MATLAB dimensions.
+fn variable<T : ::hdf5::H5Type>(file : &::hdf5::File, name : &str, values : &[T], shape : (usize, usize)) -> ::hdf5::Result<()>
+{
+    let dataset = file.new_dataset::<T>().shape(shape).create(name)?;
+    dataset.write_raw(values)?;
+    let class = FixedAscii::<6>::from_ascii("double").map_err(|error| ::hdf5::Error::from(error.to_string()))?;
+    dataset.new_attr::<FixedAscii<6>>().create("MATLAB_class")?.write_scalar(&class)
+}
@@ -0,0 +1,178 @@
+//! Memory-mapped files of scalograms, which can be larger than the memory.
+//!
+//! The file starts with a header of 56 bytes, followed by the scales and the coefficients, all little-endian.
+//! The header is "FCWTMMAP", the version (u32), the output mode (u32), num_scales, num_samples and hop (u64), fs and bandwidth (f64).
+//! The coefficients are row-major, as f64 or as [re, im] pairs of f64 if the output mode is Complex.
+use crate::*;
+use std::io::{Error, ErrorKind};
+
+const MAGIC : &[u8; 8] = b"FCWTMMAP";
+const VERSION : u32 = 1;
+const HEADER : usize = 56;
+
+impl FastCWT
+{
+    /// Same as cwt(), but writes every row of the coefficients straight into a memory-mapped file instead of the memory.
+    ///
+    /// # Arguments
+    /// input     - Input data in vector format
+    ///
+    /// scales    - Scales object
+    ///
+    /// path      - Path of the file, which is replaced if it exists
+    ///
+    /// Returns the reader of the written file.
+    pub fn cwt_mmap(&mut self, input : &[f64], scales : &Scales, path : impl AsRef<std::path::Path>) -> std::io::Result<MmapScalogram>
+    {
+        let (mode, hop, pooling) = (self.output_mode, self.hop, self.pooling);
+        let columns = input.len().div_ceil(hop);
+        let width = if mode == OutputMode::Complex { 16 } else { 8 };
+        let data = HEADER + 8 * scales.num_scales;
+
+        let file = std::fs::OpenOptions::new().read(true).write(true).create(true).truncate(true).open(path.as_ref())?;
+        file.set_len((data + width * scales.num_scales * columns) as u64)?;
+        //SAFETY: The file was just created by this function, and it is not resized or truncated while it is mapped.
+        #[allow(unsafe_code)]
+        let mut map = unsafe { memmap2::MmapMut::map_mut(&file)? };
+
+        let mut header = Vec::with_capacity(data);
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        header.extend_from_slice(&mode_code(mode).to_le_bytes());
+        for value in [scales.num_scales, columns, hop] { header.extend_from_slice(&(value as u64).to_le_bytes()); }
+        for value in [scales.fs, self.wavelet.fb] { header.extend_from_slice(&value.to_le_bytes()); }
+        for scale in scales.scales.iter() { header.extend_from_slice(&scale.to_le_bytes()); }
+        map[.. data].copy_from_slice(&header);
+
+        if !input.is_empty()
+        {
+            let spectrum = self.forward(input);
+            match mode
+            {
+                OutputMode::Complex => self.convolve_with(&spectrum, scales, &mut map[data ..], |row, out|
+                {
+                    for (out, window) in out.chunks_exact_mut(16).zip(row.chunks(hop))
+                    {
+                        let value = pool(window, pooling, |data| *data);
+                        out[.. 8].copy_from_slice(&value.re.to_le_bytes());
+                        out[8 ..].copy_from_slice(&value.im.to_le_bytes());
+                    }
+                }),
+                _ => self.convolve_with(&spectrum, scales, &mut map[data ..], |row, out|
+                {
+                    for (out, window) in out.chunks_exact_mut(8).zip(row.chunks(hop)) { out.copy_from_slice(&pool(window, pooling, |data| mode.apply(*data)).to_le_bytes()); }
+                })
+            }
+        }
+        map.flush()?;
+        drop(map);
+        MmapScalogram::open(path)
+    }
+}
+
+/// Reader of a scalogram file written by FastCWT::cwt_mmap(), which only loads the parts that are read.
+///
+/// The file must not be modified while it is open.
+pub struct MmapScalogram
+{
+    map : memmap2::Mmap,
+    mode : OutputMode,
+    num_samples : usize,
+    hop : usize,
+    scales : Scales,
+    bandwidth : f64
+}
+impl MmapScalogram
+{
+    /// Maps a scalogram file for reading.
+    pub fn open(path : impl AsRef<std::path::Path>) -> std::io::Result<MmapScalogram>
+    {
+        let file = std::fs::File::open(path)?;
+        //SAFETY: The file must not be modified while it is mapped, which is documented on this type.
+        #[allow(unsafe_code)]
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+
+        let invalid = |message : &str| Error::new(ErrorKind::InvalidData, message.to_string());
+        if map.len() < HEADER || &map[.. 8] != MAGIC { return Err(invalid("Not a fastcwt scalogram file.")); }
+        if u32_at(&map, 8) != VERSION { return Err(invalid("Unsupported scalogram file version.")); }
+        let mode = match u32_at(&map, 12)
+        {
+            0 => OutputMode::Complex,
+            1 => OutputMode::Magnitude,
+            2 => OutputMode::Power,
+            3 => OutputMode::LogPower,
+            _ => return Err(invalid("Unknown output mode."))
+        };
+        let (num_scales, num_samples, hop) = (u64_at(&map, 16) as usize, u64_at(&map, 24) as usize, u64_at(&map, 32) as usize);
+        let (fs, bandwidth) = (f64_at(&map, 40), f64_at(&map, 48));
+
+        let width = if mode == OutputMode::Complex { 16 } else { 8 };
+        let length = num_scales.checked_mul(num_samples).and_then(|values| values.checked_mul(width)).and_then(|bytes| bytes.checked_add(HEADER + 8 * num_scales));
+        if length != Some(map.len()) { return Err(invalid("Scalogram file size does not match its header.")); }
+
+        let scales = (0 .. num_scales).map(|index| f64_at(&map, HEADER + 8 * index)).collect();
+        let scales = Scales { scales, fs, num_scales, scale_type : None };
+        Ok(MmapScalogram { map, mode, num_samples, hop, scales, bandwidth })
+    }
+    /// Output mode the scalogram was computed with.
+    pub fn mode(& self) -> OutputMode { self.mode }
+    /// Number of scales. (rows)
+    pub fn num_scales(& self) -> usize { self.scales.len() }
+    /// Number of samples of each scale. (columns)
+    pub fn num_samples(& self) -> usize { self.num_samples }
+    /// Number of input samples per output sample.
+    pub fn hop(& self) -> usize { self.hop }
+    /// Scales the scalogram was computed with.
+    pub fn scales(& self) -> &Scales { &self.scales }
+    /// Bandwidth of the wavelet the scalogram was computed with.
+    pub fn bandwidth(& self) -> f64 { self.bandwidth }
+    /// Complex coefficients of the samples of a scale, only if the output mode is Complex.
+    ///
+    /// # Arguments
+    /// scale_idx - Index of the scale
+    ///
+    /// samples   - Range of the samples to read
+    pub fn complex_row(& self, scale_idx : usize, samples : core::ops::Range<usize>) -> Option<Vec<Complex<f64>>>
+    {
+        if self.mode != OutputMode::Complex { return None; }
+        let start = self.offset(scale_idx, samples.clone(), 16);
+        Some((0 .. samples.len()).map(|n| Complex::new(f64_at(&self.map, start + 16 * n), f64_at(&self.map, start + 16 * n + 8))).collect())
+    }
+    /// Magnitude, power or log power values of the samples of a scale, only if the output mode is not Complex.
+    ///
+    /// # Arguments
+    /// scale_idx - Index of the scale
+    ///
+    /// samples   - Range of the samples to read
+    pub fn real_row(& self, scale_idx : usize, samples : core::ops::Range<usize>) -> Option<Vec<f64>>
+    {
+        if self.mode == OutputMode::Complex { return None; }
+        let start = self.offset(scale_idx, samples.clone(), 8);
+        Some((0 .. samples.len()).map(|n| f64_at(&self.map, start + 8 * n)).collect())
+    }
+    fn offset(& self, scale_idx : usize, samples : core::ops::Range<usize>, width : usize) -> usize
+    {
+        assert!(scale_idx < self.num_scales(), "Scale index must be less than the number of scales.");
+        assert!(samples.start <= samples.end && samples.end <= self.num_samples, "Samples must be within the scalogram.");
+        HEADER + 8 * self.num_scales() + width * (scale_idx * self.num_samples + samples.start)
+    }
+}
+
+fn mode_code(mode : OutputMode) -> u32
+{
+    match mode
+    {
+        OutputMode::Complex => 0,
+        OutputMode::Magnitude => 1,
+        OutputMode::Power => 2,
+        OutputMode::LogPower => 3
+    }
+}
+fn u32_at(bytes : &[u8], offset : usize) -> u32 { u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]]) }
+fn u64_at(bytes : &[u8], offset : usize) -> u64
+{
+    let mut value = [0; 8];
+    value.copy_from_slice(&bytes[offset .. offset + 8]);
+    u64::from_le_bytes(value)
+}
+fn f64_at(bytes : &[u8], offset : usize) -> f64 { f64::from_bits(u64_at(bytes, offset)) }
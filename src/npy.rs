@@ -0,0 +1,212 @@
+//! NumPy .npy and .npz files of results.
+use crate::*;
+use std::io::{Error, ErrorKind, Read, Write};
+use std::path::Path;
+
+impl CwtResult
+{
+    /// Writes the coefficients as a NumPy array of num_scales × num_samples, which is complex128 if the output mode is Complex and float64 otherwise.
+    pub fn save_npy(& self, path : impl AsRef<Path>) -> std::io::Result<()>
+    {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.write_coefficients(&mut writer)?;
+        writer.flush()
+    }
+    /// Writes the coefficients with their axes into a NumPy archive, which numpy.load() reads as the arrays
+    /// coefficients (num_scales × num_samples), frequencies (Hz), scales, times (seconds), fs, hop, bandwidth, normalized and mode.
+    ///
+    /// ```python
+    /// data = numpy.load("scalogram.npz")
+    /// pyplot.pcolormesh(data["times"], data["frequencies"], numpy.abs(data["coefficients"]))
+    /// ```
+    pub fn save_npz(& self, path : impl AsRef<Path>) -> std::io::Result<()>
+    {
+        let mut archive = zip::ZipWriter::new(std::io::BufWriter::new(std::fs::File::create(path)?));
+        let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+
+        let size = self.num_scales() * self.num_samples() * if self.mode() == OutputMode::Complex { 16 } else { 8 };
+        archive.start_file("coefficients.npy", options.large_file(size >= u32::MAX as usize))?;
+        self.write_coefficients(&mut archive)?;
+
+        archive.start_file("frequencies.npy", options)?;
+        write_header(&mut archive, "<f8", &[self.num_scales()])?;
+        write_floats(&mut archive, &self.frequencies())?;
+        archive.start_file("scales.npy", options)?;
+        write_header(&mut archive, "<f8", &[self.num_scales()])?;
+        write_floats(&mut archive, &self.scales().get_scales())?;
+        archive.start_file("times.npy", options)?;
+        write_header(&mut archive, "<f8", &[self.num_samples()])?;
+        write_floats(&mut archive, &self.times())?;
+        archive.start_file("fs.npy", options)?;
+        write_header(&mut archive, "<f8", &[])?;
+        write_floats(&mut archive, &[self.fs()])?;
+        archive.start_file("hop.npy", options)?;
+        write_header(&mut archive, "<i8", &[])?;
+        archive.write_all(&(self.hop() as i64).to_le_bytes())?;
+        archive.start_file("bandwidth.npy", options)?;
+        write_header(&mut archive, "<f8", &[])?;
+        write_floats(&mut archive, &[self.wavelet().bandwidth()])?;
+        archive.start_file("normalized.npy", options)?;
+        write_header(&mut archive, "|b1", &[])?;
+        archive.write_all(&[self.is_normalized() as u8])?;
+
+        let mode = self.mode().name();
+        archive.start_file("mode.npy", options)?;
+        write_header(&mut archive, &format!("<U{}", mode.len()), &[])?;
+        for character in mode.chars() { archive.write_all(&(character as u32).to_le_bytes())?; }
+
+        archive.finish()?.flush()
+    }
+    /// Reads a result written by save_npz().
+    pub fn load_npz(path : impl AsRef<Path>) -> std::io::Result<CwtResult>
+    {
+        let mut archive = zip::ZipArchive::new(std::io::BufReader::new(std::fs::File::open(path)?))?;
+        let mut read = |name : &str| Array::read(&mut archive.by_name(name)?);
+
+        let coefficients = read("coefficients.npy")?;
+        let scales = read("scales.npy")?.floats()?;
+        let fs = read("fs.npy")?.float()?;
+        let hop = read("hop.npy")?.int()?;
+        let bandwidth = read("bandwidth.npy")?.float()?;
+        let normalized = read("normalized.npy")?.bool()?;
+        let mode = match read("mode.npy")?.text()?.as_str()
+        {
+            "complex" => OutputMode::Complex,
+            "magnitude" => OutputMode::Magnitude,
+            "power" => OutputMode::Power,
+            "logpower" => OutputMode::LogPower,
+            _ => return Err(invalid("Unknown output mode."))
+        };
+
+        let [num_scales, num_samples] = coefficients.shape[..] else { return Err(invalid("Coefficients must be a matrix.")); };
+        if num_scales != scales.len() { return Err(invalid("Number of scales does not match the coefficients.")); }
+        if hop < 1 { return Err(invalid("Hop size must be at least 1.")); }
+
+        let scales = Scales { scales : scales.into(), fs, num_scales, scale_type : None };
+        let wavelet = Wavelet::create(bandwidth);
+        match mode
+        {
+            OutputMode::Complex => Ok(CwtResult::complex(coefficients.complex()?, scales, wavelet, num_samples, hop as usize, normalized)),
+            _ => Ok(CwtResult::real(coefficients.floats()?, mode, scales, wavelet, num_samples, hop as usize, normalized))
+        }
+    }
+    fn write_coefficients(& self, writer : &mut impl Write) -> std::io::Result<()>
+    {
+        let shape = [self.num_scales(), self.num_samples()];
+        match self.complex_coefficients()
+        {
+            Some(coefficients) =>
+            {
+                write_header(writer, "<c16", &shape)?;
+                for value in coefficients
+                {
+                    writer.write_all(&value.re.to_le_bytes())?;
+                    writer.write_all(&value.im.to_le_bytes())?;
+                }
+                Ok(())
+            }
+            None =>
+            {
+                write_header(writer, "<f8", &shape)?;
+                write_floats(writer, self.real_coefficients().unwrap_or_default())
+            }
+        }
+    }
+}
+
+fn write_header(writer : &mut impl Write, descr : &str, shape : &[usize]) -> std::io::Result<()>
+{
+    let shape = match shape
+    {
+        [length] => format!("({},)", length),
+        _ => format!("({})", shape.iter().map(|length| length.to_string()).collect::<Vec<_>>().join(", "))
+    };
+    let mut header = format!("{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}", descr, shape);
+    //Magic, version and header length take 10 bytes, and the data starts at a multiple of 64.
+    let padding = (64 - (10 + header.len() + 1) % 64) % 64;
+    header.extend(core::iter::repeat(' ').take(padding));
+    header.push('\n');
+
+    writer.write_all(b"\x93NUMPY\x01\x00")?;
+    writer.write_all(&(header.len() as u16).to_le_bytes())?;
+    writer.write_all(header.as_bytes())
+}
+
+fn write_floats(writer : &mut impl Write, values : &[f64]) -> std::io::Result<()>
+{
+    for value in values { writer.write_all(&value.to_le_bytes())?; }
+    Ok(())
+}
+
+fn invalid(message : &str) -> Error { Error::new(ErrorKind::InvalidData, message) }
+
+/// Array of a .npy file, of the types save_npz() writes.
+struct Array
+{
+    descr : String,
+    shape : Vec<usize>,
+    data : Vec<u8>
+}
+impl Array
+{
+    fn read(reader : &mut impl Read) -> std::io::Result<Array>
+    {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic[.. 6] != b"\x93NUMPY" { return Err(invalid("Not a NumPy array.")); }
+
+        //Version 1 has a 16-bit header length, and later versions have a 32-bit one.
+        let length = if magic[6] == 1
+        {
+            let mut length = [0; 2];
+            reader.read_exact(&mut length)?;
+            u16::from_le_bytes(length) as usize
+        }
+        else
+        {
+            let mut length = [0; 4];
+            reader.read_exact(&mut length)?;
+            u32::from_le_bytes(length) as usize
+        };
+        let mut header = vec![0; length];
+        reader.read_exact(&mut header)?;
+        let header = String::from_utf8_lossy(&header);
+
+        let field = |key : &str| header.split_once(&format!("'{}':", key)).map(|(_, rest)| rest.trim_start()).ok_or_else(|| invalid("Header of the array is incomplete."));
+        if field("fortran_order")?.starts_with("True") { return Err(invalid("Fortran order arrays are not supported.")); }
+        let descr = field("descr")?.trim_start_matches(['\'', '"']).split(['\'', '"']).next().unwrap_or_default().to_string();
+        let shape = field("shape")?.trim_start_matches('(').split(')').next().unwrap_or_default().split(',')
+            .map(str::trim).filter(|length| !length.is_empty())
+            .map(|length| length.parse().map_err(|_| invalid("Shape of the array is not a number.")))
+            .collect::<Result<Vec<usize>, _>>()?;
+
+        let mut data = vec![];
+        reader.read_to_end(&mut data)?;
+        Ok(Array { descr, shape, data })
+    }
+    /// Data as elements of the size, checking the type and the length.
+    fn elements<const N : usize>(& self, descr : &str) -> std::io::Result<impl Iterator<Item = [u8; N]> + '_>
+    {
+        if self.descr != descr { return Err(invalid(&format!("Expected an array of {}, not {}.", descr, self.descr))); }
+        if self.data.len() != self.shape.iter().product::<usize>() * N { return Err(invalid("Length of the array does not match its shape.")); }
+        Ok(self.data.chunks_exact(N).map(|element| element.try_into().unwrap_or([0; N])))
+    }
+    fn floats(& self) -> std::io::Result<Vec<f64>> { Ok(self.elements::<8>("<f8")?.map(f64::from_le_bytes).collect()) }
+    fn float(& self) -> std::io::Result<f64> { self.elements::<8>("<f8")?.map(f64::from_le_bytes).next().ok_or_else(|| invalid("Array is empty.")) }
+    fn complex(& self) -> std::io::Result<Vec<Complex<f64>>>
+    {
+        Ok(self.elements::<16>("<c16")?.map(|element|
+        {
+            let (re, im) = element.split_at(8);
+            Complex::new(f64::from_le_bytes(re.try_into().unwrap_or_default()), f64::from_le_bytes(im.try_into().unwrap_or_default()))
+        }).collect())
+    }
+    fn int(& self) -> std::io::Result<i64> { self.elements::<8>("<i8")?.map(i64::from_le_bytes).next().ok_or_else(|| invalid("Array is empty.")) }
+    fn bool(& self) -> std::io::Result<bool> { self.elements::<1>("|b1")?.map(|element| element[0] != 0).next().ok_or_else(|| invalid("Array is empty.")) }
+    fn text(& self) -> std::io::Result<String>
+    {
+        let length = self.descr.strip_prefix("<U").and_then(|length| length.parse::<usize>().ok()).ok_or_else(|| invalid("Expected a string."))?;
+        if self.data.len() != length * 4 { return Err(invalid("Length of the string does not match its type.")); }
+        Ok(self.data.chunks_exact(4).filter_map(|character| char::from_u32(u32::from_le_bytes([character[0], character[1], character[2], character[3]]))).filter(|&character| character != '\0').collect())
+    }
+}
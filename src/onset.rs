@@ -0,0 +1,118 @@
+//! Onset detection from the positive spectral flux of the scalogram.
+//!
+//! The flux sums the increase of the magnitude of every scale from one column to the next, weighted by the width of the scale in log-scale.
+//! Small scales react within a few samples, so the onsets are as sharp as the hop allows, while the large scales add the tonal onsets.
+//!
+//! The flux is an onset envelope, whose own transform from 0.5 to 8 Hz (30 to 480 BPM) is a tempogram of the periodicities of the onsets.
+use crate::*;
+
+/// Lowest and highest tempo of the tempogram in Hz.
+const TEMPO_RANGE : (f64, f64) = (0.5, 8.0);
+/// Scales of the tempogram per octave.
+const TEMPO_VOICES : usize = 12;
+/// Center in BPM and standard deviation in octaves of the log-normal prior of the tempo, which picks among the harmonics of the beat.
+const TEMPO_PRIOR : (f64, f64) = (120.0, 1.0);
+/// Penalty of a tempo jump of one octave between columns of the local tempo, in ln of the power.
+const TEMPO_PENALTY : f64 = 20.0;
+
+/// Tempogram of an onset envelope, with the tempo estimates.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Tempogram
+{
+    /// Transform of the envelope from 0.5 to 8 Hz, where a frequency times 60 is a tempo in BPM.
+    pub result : CwtResult,
+    /// Tempo in BPM of every column, along the ridge of the strongest periodicity weighted by the same prior.
+    pub local_tempo : Vec<f64>,
+    /// Tempo in BPM of the strongest periodicity of the whole envelope, which is the peak of its global wavelet spectrum weighted by a prior around 120 BPM.
+    pub tempo : f64
+}
+
+/// Positive spectral flux of every column, which is 0 for the first column.
+///
+/// Coefficients masked with NaN do not add to the flux.
+pub fn flux(result : &CwtResult) -> Vec<f64>
+{
+    let num_samples = result.num_samples();
+    let mut flux = vec![0.0; num_samples];
+    for (row, weight) in result.magnitude().chunks(num_samples.max(1)).zip(result.scales().log_steps())
+    {
+        for (flux, pair) in flux.iter_mut().skip(1).zip(row.windows(2))
+        {
+            let increase = pair[1] - pair[0];
+            if increase > 0.0 { *flux += weight * increase; }
+        }
+    }
+    flux
+}
+
+/// Sample positions of the onsets, which are the peaks of the flux above its moving average by delta.
+///
+/// A column is an onset if its flux is the highest within min_interval around it, and above the mean flux within window around it
+/// plus delta times the highest flux of the result. Of equal peaks within min_interval, only the first is kept.
+///
+/// # Arguments
+/// result        - Result of the transform
+///
+/// window        - Half length in seconds of the moving average, like 0.05
+///
+/// delta         - Threshold above the moving average relative to the highest flux, like 0.1
+///
+/// min_interval  - Shortest time in seconds between two onsets, like 0.03
+pub fn detect(result : &CwtResult, window : f64, delta : f64, min_interval : f64) -> Vec<usize>
+{
+    assert!(window >= 0.0 && min_interval >= 0.0, "Window and minimum interval cannot be negative.");
+
+    let flux = flux(result);
+    let highest = flux.iter().fold(0.0_f64, |highest, value| highest.max(*value));
+    if highest <= 0.0 { return vec![]; }
+
+    let columns = |seconds : f64| (seconds * result.fs() / result.hop() as f64).round() as usize;
+    let (window, distance) = (columns(window), columns(min_interval));
+    let mut sums = vec![0.0; flux.len() + 1];
+    for (n, value) in flux.iter().enumerate() { sums[n + 1] = sums[n] + value; }
+
+    let mut onsets : Vec<usize> = vec![];
+    let mut last : Option<usize> = None;
+    for (n, value) in flux.iter().enumerate()
+    {
+        let (start, end) = (n.saturating_sub(window), (n + window + 1).min(flux.len()));
+        let mean = (sums[end] - sums[start]) / (end - start) as f64;
+        let (first, after) = (n.saturating_sub(distance), (n + distance + 1).min(flux.len()));
+        let is_peak = flux[first .. after].iter().all(|other| other <= value);
+        if *value > 0.0 && *value >= mean + delta * highest && is_peak && last.map_or(true, |last| n - last > distance)
+        {
+            onsets.push(n * result.hop());
+            last = Some(n);
+        }
+    }
+    onsets
+}
+
+impl FastCWT
+{
+    /// Tempogram of an onset envelope, like flux() or the band energy of a signal, and its tempo.
+    ///
+    /// The mean of the envelope is removed, and it is transformed with the settings of this transform on 12 scales per octave from 0.5 to 8 Hz.
+    /// A tempo is often ambiguous by a factor of 2, as the periodicity at half or twice the tempo can be as strong.
+    ///
+    /// # Arguments
+    /// envelope  - Onset envelope
+    ///
+    /// fs        - Sample rate of the envelope, which is the sample rate of the signal divided by the hop for flux(), and must be above 16 Hz
+    pub fn tempogram(&mut self, envelope : &[f64], fs : f64) -> Result<Tempogram, FastCwtError>
+    {
+        let scales = Scales::try_create(ScaleTypes::Octaves { voices_per_octave : TEMPO_VOICES }, fs, TEMPO_RANGE.0, TEMPO_RANGE.1, 0)?;
+        let mean = envelope.iter().sum::<f64>() / envelope.len().max(1) as f64;
+        let envelope : Vec<f64> = envelope.iter().map(|value| value - mean).collect();
+        let result = self.cwt(envelope.len(), &envelope, &scales);
+
+        //Pulse trains have equally strong harmonics, so the prior prefers the one closest to common tempi.
+        let prior = |row : usize| (- (60.0 * result.frequency(row) / TEMPO_PRIOR.0).log2().powi(2) / (2.0 * TEMPO_PRIOR.1 * TEMPO_PRIOR.1)).exp();
+        let ridge = crate::ridge::weighted(&result, 1, TEMPO_PENALTY, prior).pop();
+        let local_tempo = ridge.map_or_else(Vec::new, |ridge| ridge.frequencies.iter().map(|frequency| 60.0 * frequency).collect());
+        let spectrum : Vec<f64> = result.global_spectrum(false).iter().enumerate().map(|(row, power)| power * prior(row)).collect();
+        let strongest = (0 .. spectrum.len()).max_by(|a, b| spectrum[*a].total_cmp(&spectrum[*b]));
+        let tempo = strongest.map_or(f64::NAN, |row| 60.0 * result.frequency(row));
+        Ok(Tempogram { result, local_tempo, tempo })
+    }
+}
@@ -0,0 +1,114 @@
+//! Reassigned scalogram.
+//!
+//! The power of every coefficient is moved to the instantaneous frequency and the group delay of the signal at that point,
+//! which sharpens the scalogram of multicomponent signals in both time and frequency.
+//! Both are estimated from the transform with the time derivative of the wavelet, since the Morlet wavelet weighted by time
+//! is a combination of the wavelet and its derivative.
+use crate::*;
+use crate::synchrosqueeze::{derivative, nearest};
+
+/// Result of the reassignment.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "ReassignedData"))]
+#[derive(Clone, PartialEq, Debug)]
+pub struct Reassigned
+{
+    power : Vec<f64>,
+    frequencies : Vec<f64>,
+    num_samples : usize
+}
+/// Fields of a deserialized Reassigned result, which are checked before they become one.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct ReassignedData
+{
+    power : Vec<f64>,
+    frequencies : Vec<f64>,
+    num_samples : usize
+}
+#[cfg(feature = "serde")]
+impl TryFrom<ReassignedData> for Reassigned
+{
+    type Error = FastCwtError;
+    fn try_from(data : ReassignedData) -> Result<Reassigned, FastCwtError>
+    {
+        let expected = data.frequencies.len().saturating_mul(data.num_samples);
+        if data.power.len() != expected { return Err(FastCwtError::ResultLength { expected, len : data.power.len() }); }
+        Ok(Reassigned { power : data.power, frequencies : data.frequencies, num_samples : data.num_samples })
+    }
+}
+impl Reassigned
+{
+    /// Reassigned power, frequency by frequency. (frequencies × samples, row-major)
+    ///
+    /// The sum over all cells is the sum of the power of the scalogram.
+    pub fn power(& self) -> &[f64] { &self.power }
+    /// Frequency grid of the rows in ascending order, which is the center frequencies of the scales.
+    pub fn frequencies(& self) -> &[f64] { &self.frequencies }
+    /// Number of samples in each row.
+    pub fn num_samples(& self) -> usize { self.num_samples }
+}
+
+impl FastCWT
+{
+    /// Reassigned scalogram on top of the continuous wavelet transform.
+    ///
+    /// # Arguments
+    /// input     - Input data in vector format
+    ///
+    /// scales    - Scales object, which also defines the frequency grid
+    ///
+    /// gamma     - Coefficients with magnitude below this stay at their scale and time, because their estimates are unstable.
+    ///             So do the ones whose frequency falls outside the grid.
+    pub fn reassign(&mut self, input : &[f64], scales : &Scales, gamma : f64) -> Reassigned
+    {
+        let size = input.len();
+        let num_scales = scales.num_scales;
+        let fs = scales.fs;
+
+        let mut frequencies : Vec<f64> = scales.scales.iter().map(|scale| fs / scale).collect();
+        frequencies.sort_by(|a, b| a.total_cmp(b));
+        let mut output = vec![0.0; num_scales * size];
+
+        if size == 0 || num_scales == 0 { return Reassigned { power : output, frequencies, num_samples : size }; }
+
+        //Derivative is only in the frequency domain, so the coefficients use the FFT as well for every scale.
+        let mut spectrum = self.forward(input);
+        spectrum.signal = None;
+        let derivative = derivative(&spectrum);
+
+        let mut coefficients = vec![Complex::new(0.0, 0.0); num_scales * size];
+        let mut derivatives = vec![Complex::new(0.0, 0.0); num_scales * size];
+        self.convolve(&spectrum, scales, &mut coefficients);
+        self.convolve(&derivative, scales, &mut derivatives);
+
+        let fb = self.wavelet.fb;
+        let lowest = frequencies[0] - if num_scales > 1 { (frequencies[1] - frequencies[0]) / 2.0 } else { 0.0 };
+        let highest = frequencies[num_scales - 1] + if num_scales > 1 { (frequencies[num_scales - 1] - frequencies[num_scales - 2]) / 2.0 } else { 0.0 };
+
+        for (i, scale) in scales.scales.iter().enumerate()
+        {
+            let row = &coefficients[i * size .. (i + 1) * size];
+            let drow = &derivatives[i * size .. (i + 1) * size];
+            let home = nearest(&frequencies, fs / scale);
+
+            for (n, (coefficient, derivative)) in row.iter().zip(drow).enumerate()
+            {
+                let power = coefficient.norm_sqr();
+                let ratio = derivative / coefficient;
+
+                //Instantaneous frequency in Hz, and group delay from the envelope of the daughter wavelet, whose width is fb × scale.
+                let frequency = ratio.im / (2.0 * core::f64::consts::PI) * fs;
+                let delay = n as f64 + (fb * scale).powi(2) * ratio.re;
+
+                let (row, column) = if coefficient.norm() > gamma && frequency >= lowest && frequency <= highest && delay.is_finite()
+                {
+                    (nearest(&frequencies, frequency), delay.round().clamp(0.0, (size - 1) as f64) as usize)
+                }
+                else { (home, n) };
+                output[row * size + column] += power;
+            }
+        }
+
+        Reassigned { power : output, frequencies, num_samples : size }
+    }
+}
@@ -0,0 +1,85 @@
+//! Reference transform by explicit convolution in the time domain, to validate the fast transform against.
+//!
+//! The daughter wavelets are sampled analytically in time up to 8 standard deviations of their envelope,
+//! and the input is zero outside of its samples. It takes O(num_samples × scale) operations per scale, so it is only meant for short signals.
+//! Away from the edges and the Nyquist frequency, it matches the normalized transform with zero padding and Accuracy::Exact.
+use crate::*;
+
+/// Complex coefficients of the input, computed by direct convolution. (num_scales × num_samples, row-major)
+///
+/// # Arguments
+/// input     - Input data in vector format
+///
+/// scales    - Scales object
+///
+/// wavelet   - Wavelet of the transform, with its normalization
+pub fn cwt(input : &[f64], scales : &Scales, wavelet : &Wavelet) -> CwtResult
+{
+    let size = input.len() as isize;
+    let mut output = Vec::with_capacity(scales.num_scales * input.len());
+    for scale in scales.scales.iter()
+    {
+        let support = (8.0 * wavelet.fb * scale).ceil() as isize;
+        let taps = wavelet.daughter_in_time(*scale, support);
+        output.extend((0 .. size).map(|n|
+        {
+            let (first, last) = ((n - support).max(0), (n + support).min(size - 1));
+            (first ..= last).map(|m| taps[(n - m + support) as usize] * input[m as usize]).sum::<Complex<f64>>()
+        }));
+    }
+    CwtResult::complex(output, scales.clone(), wavelet.parameters(), input.len(), 1, true)
+}
+
+/// Root mean square error of every scale relative to the root mean square of the reference.
+///
+/// # Arguments
+/// result    - Result to check, with the output mode Complex and a hop of 1
+///
+/// reference - Result of cwt() with the same scales
+///
+/// samples   - Range of samples to compare, e.g. without the edges
+pub fn relative_error(result : &CwtResult, reference : &CwtResult, samples : core::ops::Range<usize>) -> Vec<f64>
+{
+    assert!(result.num_scales() == reference.num_scales() && result.num_samples() == reference.num_samples(), "Results must have the same shape.");
+    assert!(samples.end <= result.num_samples(), "Samples must be within the results.");
+
+    let (values, expected) = (result.complex_coefficients().expect("Result must be complex."), reference.complex_coefficients().expect("Reference must be complex."));
+    let size = result.num_samples().max(1);
+    values.chunks(size).zip(expected.chunks(size)).map(|(row, expected)|
+    {
+        let (row, expected) = (&row[samples.clone()], &expected[samples.clone()]);
+        let error : f64 = row.iter().zip(expected).map(|(value, expected)| (value - expected).norm_sqr()).sum();
+        let power : f64 = expected.iter().map(|value| value.norm_sqr()).sum();
+        (error / power).sqrt()
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn matches_fast_transform()
+    {
+        //Chirp with a little deterministic noise, so every scale has energy.
+        let input : Vec<f64> = (0 .. 2048).map(|n|
+        {
+            let time = n as f64 / 1000.0;
+            (2.0 * core::f64::consts::PI * (20.0 + 40.0 * time) * time).sin() + 0.1 * ((n * 7919 % 1000) as f64 / 500.0 - 1.0)
+        }).collect();
+        let scales = Scales::create(ScaleTypes::LinFreq, 1000.0, 10.0, 200.0, 16);
+        let wavelet = Wavelet::create(1.0);
+        let expected = cwt(&input, &scales, &wavelet);
+
+        //Nearest sample of the mother wavelet shifts the frequency of every bin, so only the other accuracies match closely.
+        for (accuracy, tolerance) in [(Accuracy::Interpolated, 1e-3), (Accuracy::Exact, 1e-4)]
+        {
+            let mut transform = FastCWT::create(wavelet.parameters(), true);
+            transform.set_accuracy(accuracy);
+            let result = transform.cwt(input.len(), &input, &scales);
+            let margin = transform.margin(&scales);
+            for error in relative_error(&result, &expected, margin .. input.len() - margin) { assert!(error < tolerance, "Relative error {} of {:?} is too large.", error, accuracy); }
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Sample rate conversion of the input to the sample rate of the scales.
+//!
+//! The input is interpolated with a Kaiser-windowed sinc of 32 zero crossings at any ratio, which is a polyphase filter with a phase for every output sample.
+//! Its cutoff is 95% of the lower Nyquist frequency, so downsampling removes what would alias and the stopband is about 90 dB down.
+use crate::*;
+
+/// Zero crossings of the sinc on each side of an output sample.
+const ZERO_CROSSINGS : f64 = 16.0;
+/// Cutoff relative to the lower Nyquist frequency.
+const ROLLOFF : f64 = 0.95;
+/// Shape of the Kaiser window, for a stopband of about 90 dB.
+const BETA : f64 = 8.6;
+
+/// Input resampled from the sample rate `from` to `to`, where the output sample n is at the time n / to.
+///
+/// # Arguments
+/// input     - Input data in vector format
+///
+/// from      - Sample rate of the input
+///
+/// to        - Sample rate of the output
+pub fn resample(input : &[f64], from : f64, to : f64) -> Vec<f64>
+{
+    assert!(from > 0.0 && to > 0.0, "Sample rates must be positive.");
+    if from == to || input.is_empty() { return input.to_vec(); }
+
+    let step = from / to;
+    let cutoff = ROLLOFF * (to / from).min(1.0);
+    //Filter is stretched in input samples when the cutoff is below the input Nyquist frequency.
+    let half = ZERO_CROSSINGS / cutoff;
+    let norm = bessel_i0(BETA);
+    let last = input.len() as isize - 1;
+
+    let size = (input.len() as f64 / step).round() as usize;
+    no_denormals(|| (0 .. size).map(|n|
+    {
+        let time = n as f64 * step;
+        let (first, end) = (((time - half).ceil() as isize).max(0), ((time + half).floor() as isize).min(last));
+        (first ..= end).map(|k|
+        {
+            let x = time - k as f64;
+            let window = bessel_i0(BETA * (1.0 - (x / half).powi(2)).max(0.0).sqrt()) / norm;
+            input[k as usize] * cutoff * sinc(cutoff * x) * window
+        }).sum()
+    }).collect())
+}
+
+impl FastCWT
+{
+    /// Same as cwt(), but takes the input at any sample rate and resamples it to the sample rate of the scales first.
+    ///
+    /// The result has the sample rate of the scales, so its times() are still the times of the input in seconds.
+    /// Frequencies above the Nyquist frequency of the input are empty.
+    ///
+    /// # Arguments
+    /// input     - Input data in vector format
+    ///
+    /// fs        - Sample rate of the input
+    ///
+    /// scales    - Scales object
+    pub fn cwt_resampled(&mut self, input : &[f64], fs : f64, scales : &Scales) -> CwtResult
+    {
+        let input = resample(input, fs, scales.fs());
+        self.cwt(input.len(), &input, scales)
+    }
+}
+
+/// sin(πx) / (πx)
+fn sinc(x : f64) -> f64
+{
+    if x == 0.0 { return 1.0; }
+    let x = core::f64::consts::PI * x;
+    x.sin() / x
+}
+/// Modified Bessel function of the first kind of order 0, by its power series.
+fn bessel_i0(x : f64) -> f64
+{
+    let (mut sum, mut term) = (1.0, 1.0);
+    for k in 1 .. 64
+    {
+        term *= (x / (2.0 * k as f64)).powi(2);
+        sum += term;
+        if term < sum * 1e-16 { break; }
+    }
+    sum
+}
@@ -0,0 +1,419 @@
+use crate::*;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, Debug)]
+enum Coefficients
+{
+    Complex(Vec<Complex<f64>>),
+    Real(Vec<f64>)
+}
+
+/// Result of the transform, scale by scale. (num_scales × num_samples, row-major)
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "CwtResultData"))]
+#[derive(Clone, PartialEq, Debug)]
+pub struct CwtResult
+{
+    coefficients : Coefficients,
+    mode : OutputMode,
+    num_samples : usize,
+    hop : usize,
+    scales : Scales,
+    wavelet : Wavelet,
+    normalized : bool
+}
+/// Fields of a deserialized CwtResult, which are checked before they become one.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct CwtResultData
+{
+    coefficients : Coefficients,
+    mode : OutputMode,
+    num_samples : usize,
+    hop : usize,
+    scales : Scales,
+    wavelet : Wavelet,
+    normalized : bool
+}
+#[cfg(feature = "serde")]
+impl TryFrom<CwtResultData> for CwtResult
+{
+    type Error = FastCwtError;
+    fn try_from(data : CwtResultData) -> Result<CwtResult, FastCwtError>
+    {
+        if data.hop == 0 { return Err(FastCwtError::InvalidHop); }
+        let len = match &data.coefficients
+        {
+            Coefficients::Complex(values) if data.mode == OutputMode::Complex => values.len(),
+            Coefficients::Real(values) if data.mode != OutputMode::Complex => values.len(),
+            _ => return Err(FastCwtError::ResultMode(data.mode))
+        };
+        let expected = data.scales.len().saturating_mul(data.num_samples);
+        if len != expected { return Err(FastCwtError::ResultLength { expected, len }); }
+        Ok(CwtResult { coefficients : data.coefficients, mode : data.mode, num_samples : data.num_samples, hop : data.hop, scales : data.scales, wavelet : data.wavelet, normalized : data.normalized })
+    }
+}
+impl CwtResult
+{
+    pub(crate) fn complex(coefficients : Vec<Complex<f64>>, scales : Scales, wavelet : Wavelet, num_samples : usize, hop : usize, normalized : bool) -> CwtResult
+    {
+        CwtResult { coefficients : Coefficients::Complex(coefficients), mode : OutputMode::Complex, num_samples, hop, scales, wavelet, normalized }
+    }
+    pub(crate) fn real(coefficients : Vec<f64>, mode : OutputMode, scales : Scales, wavelet : Wavelet, num_samples : usize, hop : usize, normalized : bool) -> CwtResult
+    {
+        CwtResult { coefficients : Coefficients::Real(coefficients), mode, num_samples, hop, scales, wavelet, normalized }
+    }
+    /// Result of the columns in the range, which are samples of the input from range.start × hop.
+    pub(crate) fn crop(& self, range : core::ops::Range<usize>) -> CwtResult
+    {
+        let rows = |len : usize| (0 .. self.scales.len()).map(move |row| row * len);
+        let coefficients = match &self.coefficients
+        {
+            Coefficients::Complex(values) => Coefficients::Complex(rows(self.num_samples).flat_map(|start| values[start + range.start .. start + range.end].iter().copied()).collect()),
+            Coefficients::Real(values) => Coefficients::Real(rows(self.num_samples).flat_map(|start| values[start + range.start .. start + range.end].iter().copied()).collect())
+        };
+        CwtResult { coefficients, mode : self.mode, num_samples : range.len(), hop : self.hop, scales : self.scales.clone(), wavelet : self.wavelet.parameters(), normalized : self.normalized }
+    }
+    /// Result of the parts joined in time, which must have the same scales and output mode.
+    pub(crate) fn concatenate(parts : &[CwtResult], scales : &Scales, wavelet : Wavelet, mode : OutputMode, hop : usize, normalized : bool) -> CwtResult
+    {
+        let num_samples = parts.iter().map(|part| part.num_samples).sum();
+        let row = |part : &CwtResult, index : usize| index * part.num_samples .. (index + 1) * part.num_samples;
+        let coefficients = match mode
+        {
+            OutputMode::Complex => Coefficients::Complex((0 .. scales.len()).flat_map(|index| parts.iter().flat_map(move |part| part.complex_coefficients().map(|values| &values[row(part, index)]).unwrap_or_default().iter().copied())).collect()),
+            _ => Coefficients::Real((0 .. scales.len()).flat_map(|index| parts.iter().flat_map(move |part| part.real_coefficients().map(|values| &values[row(part, index)]).unwrap_or_default().iter().copied())).collect())
+        };
+        CwtResult { coefficients, mode, num_samples, hop, scales : scales.clone(), wavelet, normalized }
+    }
+    /// Output mode the result was computed with.
+    pub fn mode(& self) -> OutputMode { self.mode }
+    /// True if the transform was normalized by the FFT size, which the inverse transform needs.
+    pub fn is_normalized(& self) -> bool { self.normalized }
+    /// Number of scales. (rows)
+    pub fn num_scales(& self) -> usize { self.scales.len() }
+    /// Number of samples of each scale. (columns)
+    pub fn num_samples(& self) -> usize { self.num_samples }
+    /// Number of input samples per output sample.
+    pub fn hop(& self) -> usize { self.hop }
+    /// Scales the result was computed with.
+    pub fn scales(& self) -> &Scales { &self.scales }
+    /// Parameters of the wavelet the result was computed with.
+    pub fn wavelet(& self) -> &Wavelet { &self.wavelet }
+    /// Sample rate of the input.
+    pub fn fs(& self) -> f64 { self.scales.fs() }
+    /// Frequency of each scale. (rows)
+    pub fn frequencies(& self) -> Vec<f64> { self.scales.frequencies() }
+    /// Time of each sample in seconds. (columns)
+    pub fn times(& self) -> Vec<f64> { (0 .. self.num_samples).map(|column| self.time(column)).collect() }
+    /// Scale of each row in samples.
+    pub fn scale_axis(& self) -> Vec<f64> { self.scales.scales.to_vec() }
+    /// Time in seconds of a column.
+    ///
+    /// column    - Index of the sample
+    pub fn time(& self, column : usize) -> f64 { (column * self.hop) as f64 / self.fs() }
+    /// Column nearest to a time in seconds, clamped to the samples.
+    ///
+    /// seconds   - Time from the first input sample
+    pub fn time_index(& self, seconds : f64) -> usize
+    {
+        let column = (seconds * self.fs() / self.hop as f64).round().max(0.0) as usize;
+        column.min(self.num_samples.saturating_sub(1))
+    }
+    /// Frequency in Hz of a row.
+    ///
+    /// row       - Index of the scale
+    pub fn frequency(& self, row : usize) -> f64 { self.scales.frequency(row) }
+    /// Row whose center frequency is nearest to the frequency in log-frequency, in any order of the scales.
+    ///
+    /// frequency - Frequency in Hz
+    pub fn frequency_index(& self, frequency : f64) -> usize
+    {
+        assert!(self.num_scales() > 0, "Result has no scales.");
+        let distance = |row : &usize| (self.scales.frequency(*row) / frequency).ln().abs();
+        (0 .. self.num_scales()).min_by(|a, b| distance(a).total_cmp(&distance(b))).unwrap_or(0)
+    }
+    /// Cone of influence, which is the frequency in Hz of each sample below which the coefficients are affected by the edges of the input.
+    ///
+    /// The wavelet power of a scale spreads √2 × bandwidth × scale samples from an edge, which is its e-folding time. (Torrence & Compo, 1998)
+    /// The first and the last samples are infinity.
+    pub fn coi(& self) -> Vec<f64>
+    {
+        let factor = core::f64::consts::SQRT_2 * self.wavelet.bandwidth() * self.fs();
+        (0 .. self.num_samples).map(|column| factor / (column.min(self.num_samples - 1 - column) * self.hop) as f64).collect()
+    }
+    /// Complex coefficients, only if the output mode is Complex.
+    pub fn complex_coefficients(& self) -> Option<&[Complex<f64>]>
+    {
+        match &self.coefficients
+        {
+            Coefficients::Complex(coefficients) => Some(coefficients),
+            Coefficients::Real(_) => None
+        }
+    }
+    /// Magnitude, power or log power values, only if the output mode is not Complex.
+    pub fn real_coefficients(& self) -> Option<&[f64]>
+    {
+        match &self.coefficients
+        {
+            Coefficients::Complex(_) => None,
+            Coefficients::Real(coefficients) => Some(coefficients)
+        }
+    }
+    /// Consumes the result and returns the complex coefficients, only if the output mode is Complex.
+    pub fn into_complex(self) -> Option<Vec<Complex<f64>>>
+    {
+        match self.coefficients
+        {
+            Coefficients::Complex(coefficients) => Some(coefficients),
+            Coefficients::Real(_) => None
+        }
+    }
+    /// Consumes the result and returns the magnitude, power or log power values, only if the output mode is not Complex.
+    pub fn into_real(self) -> Option<Vec<f64>>
+    {
+        match self.coefficients
+        {
+            Coefficients::Complex(_) => None,
+            Coefficients::Real(coefficients) => Some(coefficients)
+        }
+    }
+    /// Conjugates the complex coefficients, which leaves the other output modes as they are.
+    pub(crate) fn conjugate(&mut self)
+    {
+        if let Coefficients::Complex(coefficients) = &mut self.coefficients
+        {
+            for value in coefficients.iter_mut() { *value = value.conj(); }
+        }
+    }
+    /// Magnitude of the coefficients, which is available in every output mode.
+    pub fn magnitude(& self) -> Vec<f64> { self.power().into_iter().map(|value| value.sqrt()).collect() }
+    /// Power of the coefficients, which is available in every output mode.
+    pub fn power(& self) -> Vec<f64>
+    {
+        match &self.coefficients
+        {
+            Coefficients::Complex(coefficients) => coefficients.iter().map(|value| value.norm_sqr()).collect(),
+            Coefficients::Real(values) => match self.mode
+            {
+                OutputMode::Magnitude => values.iter().map(|value| value * value).collect(),
+                OutputMode::LogPower => values.iter().map(|value| 10.0_f64.powf(value / 10.0)).collect(),
+                _ => values.clone()
+            }
+        }
+    }
+    /// Unwrapped phase of the coefficients in radians along time, scale by scale, only if the output mode is Complex.
+    ///
+    /// A jump of more than π between samples is taken as a wrap, so the phase must advance less than half a cycle per hop.
+    pub fn phase(& self) -> Option<Vec<f64>>
+    {
+        let coefficients = self.complex_coefficients()?;
+        let mut phase = Vec::with_capacity(coefficients.len());
+        for row in coefficients.chunks(self.num_samples.max(1))
+        {
+            let mut previous : Option<f64> = None;
+            for coefficient in row
+            {
+                let mut value = coefficient.arg();
+                if let Some(previous) = previous
+                {
+                    let turns = ((previous - value) / (2.0 * core::f64::consts::PI)).round();
+                    value += turns * 2.0 * core::f64::consts::PI;
+                }
+                phase.push(value);
+                previous = Some(value);
+            }
+        }
+        Some(phase)
+    }
+    /// Instantaneous frequency in Hz of a scale at each sample, which is the derivative of the unwrapped phase.
+    ///
+    /// The derivative is the central difference, or the one-sided difference at the first and the last samples.
+    /// Only if the output mode is Complex, otherwise None.
+    ///
+    /// # Arguments
+    /// scale_idx - Index of the scale
+    pub fn instantaneous_frequency(& self, scale_idx : usize) -> Option<Vec<f64>>
+    {
+        assert!(scale_idx < self.num_scales(), "Scale index must be less than the number of scales.");
+
+        let phase = self.phase()?;
+        let row = &phase[scale_idx * self.num_samples .. (scale_idx + 1) * self.num_samples];
+        let factor = self.fs() / (2.0 * core::f64::consts::PI * self.hop as f64);
+        Some((0 .. row.len()).map(|n|
+        {
+            let (lower, upper) = (n.saturating_sub(1), (n + 1).min(row.len() - 1));
+            if upper == lower { 0.0 } else { (row[upper] - row[lower]) / (upper - lower) as f64 * factor }
+        }).collect())
+    }
+    /// Power of the frequency band at each sample, which is the variance of the signal within the band.
+    ///
+    /// The power of the scales from f_low to f_high is summed, weighted by the width of each scale in log-scale,
+    /// so it does not depend on the number of scales as long as they overlap. (about 8 or more per octave)
+    ///
+    /// Returns FastCwtError::NotNormalized if the transform is not normalized, as the power would not be in the units of the input.
+    pub fn band_power(& self, f_low : f64, f_high : f64) -> Result<Vec<f64>, FastCwtError>
+    {
+        if !self.normalized { return Err(FastCwtError::NotNormalized); }
+        Ok(self.band(f_low, f_high))
+    }
+    /// Same as band_power(), but for any transform, which scales the power of an unnormalized one by a constant.
+    pub(crate) fn band(& self, f_low : f64, f_high : f64) -> Vec<f64>
+    {
+        let factor = 2.0 / self.wavelet.power_factor();
+        let mut band = vec![0.0; self.num_samples];
+        for ((row, (scale, frequency)), step) in self.power().chunks(self.num_samples.max(1)).zip(self.scales.iter()).zip(self.scales.log_steps())
+        {
+            if frequency < f_low || frequency > f_high { continue; }
+            let weight = factor * step / self.wavelet.gain(scale).powi(2);
+            for (band, power) in band.iter_mut().zip(row) { *band += weight * power; }
+        }
+        band
+    }
+    /// Global wavelet spectrum, which is the power of each scale averaged over time.
+    ///
+    /// If exclude_coi is true, only the samples outside of the cone of influence are averaged, and a scale without any is NaN.
+    pub fn global_spectrum(& self, exclude_coi : bool) -> Vec<f64>
+    {
+        let coi = if exclude_coi { self.coi() } else { vec![0.0; self.num_samples] };
+        self.power().chunks(self.num_samples.max(1)).zip(self.frequencies()).map(|(row, frequency)|
+        {
+            let (sum, count) = row.iter().zip(&coi).filter(|(_, coi)| frequency >= **coi).fold((0.0, 0), |(sum, count), (power, _)| (sum + power, count + 1));
+            if count == 0 { f64::NAN } else { sum / count as f64 }
+        }).collect()
+    }
+    /// Inverse transform, which sums the coefficients over the scales weighted by their width in log-scale.
+    ///
+    /// Only a normalized transform with the output mode Complex and a hop of 1 can be inverted, otherwise None.
+    /// Components outside the frequency range of the scales, including the mean, are not reconstructed.
+    pub fn icwt(& self) -> Option<Vec<f64>>
+    {
+        let coefficients = self.complex_coefficients().filter(|_| self.hop == 1 && self.normalized)?;
+
+        //Sum over the scales is the analytic signal divided by 2, whose real part is the signal.
+        let factor = 2.0 / self.wavelet.reconstruction_factor();
+        let mut output = vec![0.0; self.num_samples];
+        for ((row, step), (scale, _)) in coefficients.chunks(self.num_samples.max(1)).zip(self.scales.log_steps()).zip(self.scales.iter())
+        {
+            let weight = factor * step / self.wavelet.gain(scale);
+            for (output, coefficient) in output.iter_mut().zip(row) { *output += weight * coefficient.re; }
+        }
+        Some(output)
+    }
+    /// Power normalized by its baseline in every scale, so that results are comparable across trials and subjects. (num_scales × samples, row-major)
+    ///
+    /// # Arguments
+    /// range     - Samples to use as the baseline
+    ///
+    /// method    - Decibel | Percent | ZScore
+    pub fn normalize_baseline(& self, range : core::ops::Range<usize>, method : Baseline) -> Vec<f64>
+    {
+        assert!(range.start < range.end && range.end <= self.num_samples, "Baseline must be a non-empty range within the samples.");
+
+        let mut power = self.power();
+        for row in power.chunks_mut(self.num_samples.max(1))
+        {
+            let count = range.len() as f64;
+            let mean = row[range.clone()].iter().sum::<f64>() / count;
+            let deviation = (row[range.clone()].iter().map(|value| (value - mean) * (value - mean)).sum::<f64>() / count).sqrt();
+            for value in row.iter_mut()
+            {
+                *value = match method
+                {
+                    Baseline::Decibel => 10.0 * (*value / mean).log10(),
+                    Baseline::Percent => 100.0 * (*value - mean) / mean,
+                    Baseline::ZScore => (*value - mean) / deviation
+                };
+            }
+        }
+        power
+    }
+    /// Power in dB relative to the reference, clipped from below at the floor, for display and log-magnitude features. (num_scales × samples, row-major)
+    ///
+    /// Coefficients masked with NaN stay NaN.
+    ///
+    /// # Arguments
+    /// reference - Power of 0 dB, like max_power() to put the peak at 0 dB
+    ///
+    /// floor     - Lowest value in dB, like -80.0, which also replaces the -∞ dB of zero power
+    pub fn to_db(& self, reference : f64, floor : f64) -> Vec<f64>
+    {
+        assert!(reference.is_finite() && reference > 0.0, "Reference must be a positive number.");
+        self.power().into_iter().map(|value| if value.is_nan() { value } else { (10.0 * (value / reference).log10()).max(floor) }).collect()
+    }
+    /// Lowest power of the coefficients, ignoring NaN, or NaN if every coefficient is NaN.
+    pub fn min_power(& self) -> f64 { self.power().into_iter().filter(|value| !value.is_nan()).reduce(f64::min).unwrap_or(f64::NAN) }
+    /// Highest power of the coefficients, ignoring NaN, or NaN if every coefficient is NaN.
+    pub fn max_power(& self) -> f64 { self.power().into_iter().filter(|value| !value.is_nan()).reduce(f64::max).unwrap_or(f64::NAN) }
+    /// Percentile of the power of the coefficients, ignoring NaN, interpolated linearly between the closest ranks.
+    ///
+    /// The 50th percentile is the median, and a percentile like the 99th is a peak level which ignores a few outliers.
+    ///
+    /// # Arguments
+    /// percent   - Percentile from 0.0 to 100.0
+    pub fn power_percentile(& self, percent : f64) -> f64
+    {
+        assert!((0.0 ..= 100.0).contains(&percent), "Percentile must be between 0 and 100.");
+
+        let mut power : Vec<f64> = self.power().into_iter().filter(|value| !value.is_nan()).collect();
+        if power.is_empty() { return f64::NAN; }
+        power.sort_by(|a, b| a.total_cmp(b));
+        let rank = percent / 100.0 * (power.len() - 1) as f64;
+        let (lower, upper) = (power[rank.floor() as usize], power[rank.ceil() as usize]);
+        lower + (upper - lower) * rank.fract()
+    }
+    /// Dynamic range in dB, which is the ratio of the highest power to the lowest power above zero.
+    ///
+    /// It is 0 dB if there is no power above zero.
+    pub fn dynamic_range(& self) -> f64
+    {
+        let (lowest, highest) = self.power().into_iter().filter(|value| *value > 0.0).fold((f64::INFINITY, 0.0_f64), |(lowest, highest), value| (lowest.min(value), highest.max(value)));
+        if highest > 0.0 { 10.0 * (highest / lowest).log10() } else { 0.0 }
+    }
+    /// Wavelet energy of each scale, which is its power summed over time.
+    pub fn energy(& self) -> Vec<f64> { self.power().chunks(self.num_samples.max(1)).map(|row| row.iter().sum()).collect() }
+    /// Total wavelet energy, which is the power summed over every scale and sample.
+    pub fn total_energy(& self) -> f64 { self.power().iter().sum() }
+    /// Wavelet entropy of each sample, which is the Shannon entropy in nats of the power distributed over the scales.
+    ///
+    /// It is 0 if the power is in a single scale, and ln(num_scales) if it is spread evenly, which normalizes it to 0 ..= 1.
+    /// A sample without any power is NaN.
+    pub fn entropy(& self) -> Vec<f64>
+    {
+        let power = self.power();
+        (0 .. self.num_samples).map(|column|
+        {
+            let total : f64 = (0 .. self.num_scales()).map(|row| power[row * self.num_samples + column]).sum();
+            if total <= 0.0 || total.is_nan() { return f64::NAN; }
+            - (0 .. self.num_scales()).map(|row| power[row * self.num_samples + column] / total).filter(|p| *p > 0.0).map(|p| p * p.ln()).sum::<f64>()
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn icwt_reconstructs_input()
+    {
+        let input : Vec<f64> = (0 .. 4096).map(|n|
+        {
+            let time = n as f64 / 1000.0;
+            (2.0 * core::f64::consts::PI * 30.0 * time).sin() + 0.5 * (2.0 * core::f64::consts::PI * 110.0 * time).cos()
+        }).collect();
+        let scales = Scales::create(ScaleTypes::Octaves { voices_per_octave : 16 }, 1000.0, 5.0, 400.0, 0);
+        let mut transform = FastCWT::create(Wavelet::create(1.0), true);
+        transform.set_accuracy(Accuracy::Exact);
+        let output = transform.cwt(input.len(), &input, &scales).icwt().expect("Normalized complex result must be invertible.");
+
+        let margin = transform.margin(&scales).min(input.len() / 4);
+        let samples = margin .. input.len() - margin;
+        let error : f64 = output[samples.clone()].iter().zip(&input[samples.clone()]).map(|(output, input)| (output - input).powi(2)).sum();
+        let power : f64 = input[samples].iter().map(|input| input * input).sum();
+        assert!((error / power).sqrt() < 1e-2, "Relative error {} is too large.", (error / power).sqrt());
+
+        let mut unnormalized = FastCWT::create(Wavelet::create(1.0), false);
+        assert!(unnormalized.cwt(input.len(), &input, &scales).icwt().is_none());
+    }
+}
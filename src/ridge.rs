@@ -0,0 +1,281 @@
+//! Ridges of the scalogram, which follow the frequency of a component through time, and pitch tracking on top of them.
+//!
+//! A ridge is the path of one scale per column which maximizes the log power along it minus a penalty on the frequency jumps
+//! between columns (Carmona et al., 1999), found by dynamic programming in O(num_samples × num_scales²).
+//! Its frequency is refined between the scales by a parabola through the log power of the scale and its neighbours,
+//! and a line fitted to it over a sliding window gives the chirp rate of the component.
+use crate::*;
+
+/// Number of harmonics summed for the salience of a pitch candidate.
+const HARMONICS : usize = 5;
+/// Weight of each harmonic relative to the one below it.
+const HARMONIC_DECAY : f64 = 0.8;
+/// Penalty of a pitch jump of one octave between columns, in ln of the salience.
+const PITCH_PENALTY : f64 = 20.0;
+
+/// Ridge of the scalogram, with one point per column.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Ridge
+{
+    /// Row of the scale of the ridge in every column.
+    pub rows : Vec<usize>,
+    /// Frequency of the ridge in Hz, interpolated between the scales.
+    pub frequencies : Vec<f64>,
+    /// Power of the coefficient on the ridge.
+    pub power : Vec<f64>
+}
+
+/// Pitch trajectory, with one point per column.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Pitch
+{
+    /// Fundamental frequency in Hz.
+    pub frequencies : Vec<f64>,
+    /// Voicing confidence from 0 to 1, which is the fraction of the power of the column within the bands of the harmonics of the fundamental.
+    pub confidence : Vec<f64>
+}
+
+/// Chirp rate along a ridge, with one point per column.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ChirpRate
+{
+    /// Slope of the frequency in Hz per second.
+    pub rates : Vec<f64>,
+    /// Confidence from 0 to 1, which falls as the frequencies of the window scatter around the line by more than half the bandwidth of the wavelet.
+    pub confidence : Vec<f64>
+}
+
+/// Strongest ridge of the result.
+///
+/// # Arguments
+/// result    - Result of the transform
+///
+/// penalty   - Penalty of a jump of one octave between columns in ln of the power, which grows with the square of the jump. 0.0 follows the highest power of every column.
+pub fn extract(result : &CwtResult, penalty : f64) -> Ridge
+{
+    extract_many(result, 1, penalty).pop().unwrap_or(Ridge { rows : vec![], frequencies : vec![], power : vec![] })
+}
+
+/// Up to count ridges of the result, strongest first.
+///
+/// After a ridge is extracted, the scales within the bandwidth of the wavelet around it are left out of the next ones,
+/// so that every ridge follows another component.
+///
+/// # Arguments
+/// result    - Result of the transform
+///
+/// count     - Number of ridges
+///
+/// penalty   - Penalty of a jump of one octave between columns in ln of the power, which grows with the square of the jump.
+pub fn extract_many(result : &CwtResult, count : usize, penalty : f64) -> Vec<Ridge>
+{
+    weighted(result, count, penalty, |_| 1.0)
+}
+
+/// Same as extract_many(), but the ridges follow the power multiplied by the weight of each row, like a prior over the frequencies.
+pub(crate) fn weighted(result : &CwtResult, count : usize, penalty : f64, weight : impl Fn(usize) -> f64) -> Vec<Ridge>
+{
+    assert!(penalty >= 0.0, "Penalty cannot be negative.");
+
+    let (order, octaves) = sorted(result);
+    let num_samples = result.num_samples();
+    if order.is_empty() { return vec![]; }
+
+    let power = result.power();
+    let log_power = |value : f64| if value.is_nan() { f64::MIN_POSITIVE.ln() } else { value.max(f64::MIN_POSITIVE).ln() };
+    let mut score : Vec<f64> = order.iter().flat_map(|row| power[row * num_samples .. (row + 1) * num_samples].iter().map(|value| log_power(*value * weight(*row)))).collect();
+    //Full width at half power of the wavelet in octaves, like Scales::resolution().
+    let width = core::f64::consts::LN_2.sqrt() / (core::f64::consts::PI * result.wavelet().fb) / core::f64::consts::LN_2;
+
+    let mut ridges = vec![];
+    for _ in 0 .. count.min(order.len())
+    {
+        if score.iter().all(|value| *value == f64::NEG_INFINITY) { break; }
+        let path = follow(&score, &octaves, num_samples, penalty);
+        let mut ridge = Ridge { rows : vec![], frequencies : vec![], power : vec![] };
+        for (column, position) in path.iter().enumerate()
+        {
+            let at = |position : usize| score[position * num_samples + column];
+            ridge.rows.push(order[*position]);
+            ridge.frequencies.push(refine(&octaves, *position, at));
+            ridge.power.push(power[order[*position] * num_samples + column]);
+
+            for (position, octave) in octaves.iter().enumerate()
+            {
+                if (octave - octaves[path[column]]).abs() <= width { score[position * num_samples + column] = f64::NEG_INFINITY; }
+            }
+        }
+        ridges.push(ridge);
+    }
+    ridges
+}
+
+/// Fundamental frequency of every column between fmin and fmax, by a ridge through the harmonic salience of the candidates.
+///
+/// The salience of a candidate frequency sums the magnitude at its first harmonics with decreasing weights, so the fundamental
+/// outweighs its subharmonics and its overtones. The scales should reach HARMONICS × fmax for the harmonics of high candidates to count.
+///
+/// # Arguments
+/// result    - Result of the transform
+///
+/// fmin      - Lowest fundamental frequency in Hz
+///
+/// fmax      - Highest fundamental frequency in Hz
+pub fn track_pitch(result : &CwtResult, fmin : f64, fmax : f64) -> Pitch
+{
+    assert!(fmin > 0.0 && fmin <= fmax, "Frequency range must be 0 < fmin <= fmax.");
+
+    let (order, octaves) = sorted(result);
+    let num_samples = result.num_samples();
+    let candidates : Vec<usize> = (0 .. order.len()).filter(|position| (fmin.log2() ..= fmax.log2()).contains(&octaves[*position])).collect();
+    if candidates.is_empty() { return Pitch { frequencies : vec![f64::NAN; num_samples], confidence : vec![0.0; num_samples] }; }
+
+    let magnitude = result.magnitude();
+    let at = |position : usize, column : usize| { let value = magnitude[order[position] * num_samples + column]; if value.is_nan() { 0.0 } else { value } };
+    //Harmonics of every candidate, at the nearest scale within half a step of them.
+    let harmonics : Vec<Vec<(usize, f64)>> = candidates.iter().map(|candidate| (1 ..= HARMONICS).filter_map(|harmonic|
+    {
+        let octave = octaves[*candidate] + (harmonic as f64).log2();
+        nearest(&octaves, octave).map(|position| (position, HARMONIC_DECAY.powi(harmonic as i32 - 1)))
+    }).collect()).collect();
+
+    let score : Vec<f64> = harmonics.iter().flat_map(|harmonics| (0 .. num_samples).map(move |column|
+    {
+        let salience : f64 = harmonics.iter().map(|(position, weight)| weight * at(*position, column)).sum();
+        salience.max(f64::MIN_POSITIVE).ln()
+    })).collect();
+    let candidate_octaves : Vec<f64> = candidates.iter().map(|candidate| octaves[*candidate]).collect();
+    let path = follow(&score, &candidate_octaves, num_samples, PITCH_PENALTY);
+
+    //Half of the full width at half power of the wavelet in octaves around every harmonic.
+    let half_width = core::f64::consts::LN_2.sqrt() / (2.0 * core::f64::consts::PI * result.wavelet().fb) / core::f64::consts::LN_2;
+    let steps = result.scales().log_steps();
+    let weights : Vec<f64> = order.iter().map(|row| steps[*row]).collect();
+    let mut pitch = Pitch { frequencies : vec![], confidence : vec![] };
+    for (column, index) in path.iter().enumerate()
+    {
+        let frequency = refine(&candidate_octaves, *index, |index| score[index * num_samples + column]);
+        let (mut harmonic, mut total) = (0.0, 0.0);
+        for (position, octave) in octaves.iter().enumerate()
+        {
+            let power = weights[position] * at(position, column).powi(2);
+            let ratio = 2.0_f64.powf(*octave) / frequency;
+            if ratio.round() >= 1.0 && ratio.round() <= HARMONICS as f64 && (ratio.log2() - ratio.round().log2()).abs() <= half_width { harmonic += power; }
+            total += power;
+        }
+        pitch.frequencies.push(frequency);
+        pitch.confidence.push(if total > 0.0 { harmonic / total } else { 0.0 });
+    }
+    pitch
+}
+
+/// Chirp rate along the ridge, by a line fitted to its frequencies over a sliding window and weighted by their power.
+///
+/// A window which holds a few periods of the frequency is long enough to average the scatter of the ridge,
+/// while the rate is the mean slope within it.
+///
+/// # Arguments
+/// result    - Result of the transform, which the ridge was extracted from
+///
+/// ridge     - Ridge of the component
+///
+/// window    - Length of the window in seconds, centered on every column
+pub fn chirp_rate(result : &CwtResult, ridge : &Ridge, window : f64) -> ChirpRate
+{
+    assert!(window > 0.0, "Window must be positive.");
+
+    let half = ((window / 2.0 * result.fs() / result.hop() as f64).round() as usize).max(1);
+    let length = ridge.frequencies.len();
+    //Half of the full width at half power of the wavelet relative to the frequency, like Scales::resolution().
+    let relative = core::f64::consts::LN_2.sqrt() / (2.0 * core::f64::consts::PI * result.wavelet().fb);
+    let mut chirp = ChirpRate { rates : vec![], confidence : vec![] };
+    for column in 0 .. length
+    {
+        let points = (column.saturating_sub(half) .. (column + half + 1).min(length)).filter(|n| ridge.power[*n] > 0.0 && ridge.frequencies[*n].is_finite());
+        let (mut weights, mut time, mut frequency) = (0.0, 0.0, 0.0);
+        for n in points.clone()
+        {
+            weights += ridge.power[n];
+            time += ridge.power[n] * result.time(n);
+            frequency += ridge.power[n] * ridge.frequencies[n];
+        }
+        if weights <= 0.0
+        {
+            chirp.rates.push(f64::NAN);
+            chirp.confidence.push(0.0);
+            continue;
+        }
+        let (time, frequency) = (time / weights, frequency / weights);
+
+        let (mut covariance, mut variance) = (0.0, 0.0);
+        for n in points.clone()
+        {
+            covariance += ridge.power[n] * (result.time(n) - time) * (ridge.frequencies[n] - frequency);
+            variance += ridge.power[n] * (result.time(n) - time).powi(2);
+        }
+        let rate = if variance > 0.0 { covariance / variance } else { 0.0 };
+        let residual = (points.map(|n| ridge.power[n] * (ridge.frequencies[n] - frequency - rate * (result.time(n) - time)).powi(2)).sum::<f64>() / weights).sqrt();
+        chirp.rates.push(rate);
+        chirp.confidence.push((- (residual / (relative * frequency)).powi(2)).exp());
+    }
+    chirp
+}
+
+/// Rows of the result in ascending order of frequency, and the frequency of each in octaves. (log2 of Hz)
+fn sorted(result : &CwtResult) -> (Vec<usize>, Vec<f64>)
+{
+    let mut order : Vec<usize> = (0 .. result.num_scales()).collect();
+    order.sort_by(|a, b| result.frequency(*a).total_cmp(&result.frequency(*b)));
+    let octaves = order.iter().map(|row| result.frequency(*row).log2()).collect();
+    (order, octaves)
+}
+
+/// Position of the frequency nearest to the octave, if it is within half the step to its neighbours.
+fn nearest(octaves : &[f64], octave : f64) -> Option<usize>
+{
+    let position = (0 .. octaves.len()).min_by(|a, b| (octaves[*a] - octave).abs().total_cmp(&(octaves[*b] - octave).abs()))?;
+    let step = if octaves.len() < 2 { 0.0 } else { (octaves[position.min(octaves.len() - 2) + 1] - octaves[position.min(octaves.len() - 2)]).abs() };
+    ((octaves[position] - octave).abs() <= step / 2.0).then_some(position)
+}
+
+/// Frequency in Hz of the peak of a parabola through the score of the position and its neighbours in octaves.
+fn refine(octaves : &[f64], position : usize, score : impl Fn(usize) -> f64) -> f64
+{
+    if position == 0 || position + 1 >= octaves.len() { return 2.0_f64.powf(octaves[position]); }
+    let (lower, center, upper) = (score(position - 1), score(position), score(position + 1));
+    let curvature = lower - 2.0 * center + upper;
+    if !(curvature.is_finite() && curvature < 0.0) { return 2.0_f64.powf(octaves[position]); }
+
+    //Offset of the peak in steps, which is within half a step of the position as its score is the highest of the three.
+    let offset = (0.5 * (lower - upper) / curvature).clamp(-0.5, 0.5);
+    let step = if offset < 0.0 { octaves[position] - octaves[position - 1] } else { octaves[position + 1] - octaves[position] };
+    2.0_f64.powf(octaves[position] + offset * step)
+}
+
+/// Path of one position per column through the score (positions × columns, row-major), which maximizes the score along it
+/// minus penalty × octaves² of every jump between columns.
+pub(crate) fn follow(score : &[f64], octaves : &[f64], columns : usize, penalty : f64) -> Vec<usize>
+{
+    let positions = octaves.len();
+    if columns == 0 || positions == 0 { return vec![]; }
+
+    let mut total : Vec<f64> = (0 .. positions).map(|position| score[position * columns]).collect();
+    let mut next = vec![0.0; positions];
+    let mut from = vec![0; positions * columns];
+    for column in 1 .. columns
+    {
+        for (position, next) in next.iter_mut().enumerate()
+        {
+            let (best, value) = (0 .. positions).map(|previous| (previous, total[previous] - penalty * (octaves[position] - octaves[previous]).powi(2)))
+                .max_by(|a, b| a.1.total_cmp(&b.1)).unwrap_or((position, f64::NEG_INFINITY));
+            from[column * positions + position] = best;
+            *next = value + score[position * columns + column];
+        }
+        core::mem::swap(&mut total, &mut next);
+    }
+
+    let mut path = vec![0; columns];
+    path[columns - 1] = (0 .. positions).max_by(|a, b| total[*a].total_cmp(&total[*b])).unwrap_or(0);
+    for column in (1 .. columns).rev() { path[column - 1] = from[column * positions + path[column]]; }
+    path
+}
@@ -0,0 +1,118 @@
+//! Sparse scalograms, which only keep the coefficients above a magnitude threshold.
+//!
+//! Scalograms of sparse signals like clicks or spikes are mostly near zero, so keeping only the large coefficients
+//! in compressed sparse rows (CSR) cuts the memory by orders of magnitude.
+use crate::*;
+
+/// Complex coefficients above a magnitude threshold in compressed sparse rows, a row per scale.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, Debug)]
+pub struct SparseCwt
+{
+    offsets : Vec<usize>,
+    columns : Vec<usize>,
+    values : Vec<Complex<f64>>,
+    num_samples : usize,
+    hop : usize,
+    threshold : f64,
+    scales : Scales,
+    wavelet : Wavelet,
+    normalized : bool
+}
+impl SparseCwt
+{
+    /// Number of scales. (rows)
+    pub fn num_scales(& self) -> usize { self.scales.len() }
+    /// Number of samples of each scale, including the ones that are not stored. (columns)
+    pub fn num_samples(& self) -> usize { self.num_samples }
+    /// Number of input samples per output sample.
+    pub fn hop(& self) -> usize { self.hop }
+    /// Magnitude the stored coefficients exceed.
+    pub fn threshold(& self) -> f64 { self.threshold }
+    /// Scales the result was computed with.
+    pub fn scales(& self) -> &Scales { &self.scales }
+    /// Wavelet the result was computed with. (only its parameters)
+    pub fn wavelet(& self) -> &Wavelet { &self.wavelet }
+    /// Number of stored coefficients.
+    pub fn nnz(& self) -> usize { self.values.len() }
+    /// Start of every row in columns() and values(), followed by nnz(). (CSR row pointers)
+    pub fn offsets(& self) -> &[usize] { &self.offsets }
+    /// Column of every stored coefficient.
+    pub fn columns(& self) -> &[usize] { &self.columns }
+    /// Every stored coefficient.
+    pub fn values(& self) -> &[Complex<f64>] { &self.values }
+    /// Columns and coefficients stored for a scale.
+    pub fn row(& self, scale_idx : usize) -> (&[usize], &[Complex<f64>])
+    {
+        assert!(scale_idx < self.num_scales(), "Scale index must be less than the number of scales.");
+        let range = self.offsets[scale_idx] .. self.offsets[scale_idx + 1];
+        (&self.columns[range.clone()], &self.values[range])
+    }
+    /// Every stored coefficient as (scale index, column, value) triplets, row by row.
+    pub fn triplets(& self) -> impl Iterator<Item = (usize, usize, Complex<f64>)> + '_
+    {
+        (0 .. self.num_scales()).flat_map(move |scale_idx|
+        {
+            let (columns, values) = self.row(scale_idx);
+            columns.iter().zip(values).map(move |(column, value)| (scale_idx, *column, *value))
+        })
+    }
+    /// Complex result with zeros in place of the coefficients that are not stored.
+    pub fn to_dense(& self) -> CwtResult
+    {
+        let mut output = vec![Complex::new(0.0, 0.0); self.num_scales() * self.num_samples];
+        for (scale_idx, column, value) in self.triplets() { output[scale_idx * self.num_samples + column] = value; }
+        CwtResult::complex(output, self.scales.clone(), self.wavelet.parameters(), self.num_samples, self.hop, self.normalized)
+    }
+}
+
+impl FastCWT
+{
+    /// Same as cwt() in the Complex output mode, but only keeps the coefficients whose magnitude exceeds the threshold.
+    ///
+    /// # Arguments
+    /// input     - Input data in vector format
+    ///
+    /// scales    - Scales object
+    ///
+    /// threshold - Magnitude a coefficient must exceed to be stored
+    ///
+    /// The hop and pooling of the transform apply before thresholding.
+    pub fn cwt_sparse(&mut self, input : &[f64], scales : &Scales, threshold : f64) -> SparseCwt
+    {
+        let (hop, pooling) = (self.hop, self.pooling);
+        let num_samples = input.len().div_ceil(hop);
+
+        //Each output row is a single cell holding the entries of its scale, so rows are filled without a lock.
+        let mut rows : Vec<(Vec<usize>, Vec<Complex<f64>>)> = (0 .. scales.num_scales).map(|_| (vec![], vec![])).collect();
+        if !input.is_empty()
+        {
+            let spectrum = self.forward(input);
+            self.convolve_with(&spectrum, scales, &mut rows, |row, out|
+            {
+                let (columns, values) = &mut out[0];
+                for (column, window) in row.chunks(hop).enumerate()
+                {
+                    let value = pool(window, pooling, |data| *data);
+                    if value.norm() > threshold
+                    {
+                        columns.push(column);
+                        values.push(value);
+                    }
+                }
+            });
+        }
+
+        let mut offsets = Vec::with_capacity(scales.num_scales + 1);
+        offsets.push(0);
+        let nnz = rows.iter().map(|(columns, _)| columns.len()).sum();
+        let (mut columns, mut values) = (Vec::with_capacity(nnz), Vec::with_capacity(nnz));
+        for (row_columns, row_values) in rows
+        {
+            columns.extend(row_columns);
+            values.extend(row_values);
+            offsets.push(columns.len());
+        }
+        SparseCwt { offsets, columns, values, num_samples, hop, threshold, scales : scales.clone(), wavelet : self.wavelet.parameters(), normalized : self.use_normalization }
+    }
+}
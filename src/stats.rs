@@ -0,0 +1,205 @@
+//! Significance of the wavelet power against red noise, and confidence intervals of the power. (Torrence & Compo, 1998)
+//!
+//! The background of a scale is the expected power of an AR(1) process, integrated over the whole daughter wavelet spectrum
+//! instead of taken at the center frequency, with the gain of the normalization of the wavelet at that scale, so it holds for every Normalization.
+//! Divided by the background, the power of a complex coefficient is χ² distributed with 2 degrees of freedom, divided by 2.
+//! Results must be normalized, and pooled over a hop only by Decimate.
+use crate::*;
+
+/// Lag-1 autocorrelation and variance of the input, which are the parameters of the AR(1) process to test against.
+pub fn ar1(input : &[f64]) -> (f64, f64)
+{
+    let num = input.len().max(1) as f64;
+    let mean = input.iter().sum::<f64>() / num;
+    let variance = input.iter().map(|value| (value - mean) * (value - mean)).sum::<f64>() / num;
+    let covariance = input.windows(2).map(|pair| (pair[0] - mean) * (pair[1] - mean)).sum::<f64>() / num;
+
+    (if variance > 0.0 { covariance / variance } else { 0.0 }, variance)
+}
+
+/// Expected power of each scale for an AR(1) process.
+///
+/// # Arguments
+/// scales    - Scales object
+///
+/// wavelet   - Wavelet of the transform
+///
+/// alpha     - Lag-1 autocorrelation of the process, which is 0.0 for white noise
+///
+/// variance  - Variance of the process
+pub fn background(scales : &Scales, wavelet : &Wavelet, alpha : f64, variance : f64) -> Vec<f64>
+{
+    let steps = 4096;
+    scales.iter().map(|(scale, _)|
+    {
+        //Daughter wavelet covers u = scale × f up to 2 or the Nyquist frequency, where f is in cycles per sample.
+        let du = 2.0_f64.min(scale / 2.0) / steps as f64;
+        (1 ..= steps).map(|i|
+        {
+            let u = (i as f64 - 0.5) * du;
+            let red = (1.0 - alpha * alpha) / (1.0 + alpha * alpha - 2.0 * alpha * (2.0 * core::f64::consts::PI * u / scale).cos());
+            let gain = wavelet.spectrum(u) * wavelet.gain(scale);
+            red * gain * gain * du / scale
+        }).sum::<f64>() * variance
+    }).collect()
+}
+
+/// Power of each scale, which the AR(1) process exceeds with the probability 1 - confidence.
+///
+/// # Arguments
+/// scales     - Scales object
+///
+/// wavelet    - Wavelet of the transform
+///
+/// alpha      - Lag-1 autocorrelation of the process
+///
+/// variance   - Variance of the process
+///
+/// confidence - Confidence level, like 0.95
+pub fn significance_level(scales : &Scales, wavelet : &Wavelet, alpha : f64, variance : f64, confidence : f64) -> Vec<f64>
+{
+    //χ² with 2 degrees of freedom divided by 2 exceeds x with the probability exp(-x).
+    let factor = - (1.0 - confidence).ln();
+    background(scales, wavelet, alpha, variance).into_iter().map(|power| power * factor).collect()
+}
+
+/// Mask of the coefficients whose power is above the significance level of their scale. (num_scales × num_samples, row-major)
+///
+/// # Arguments
+/// result     - Result of the transform
+///
+/// alpha      - Lag-1 autocorrelation of the process, from ar1() of the input
+///
+/// variance   - Variance of the process, from ar1() of the input
+///
+/// confidence - Confidence level, like 0.95
+///
+/// Returns FastCwtError::NotNormalized if the transform is not normalized, as the power would not be in the units of the background.
+pub fn significant(result : &CwtResult, alpha : f64, variance : f64, confidence : f64) -> Result<Vec<bool>, FastCwtError>
+{
+    if !result.is_normalized() { return Err(FastCwtError::NotNormalized); }
+    let levels = significance_level(result.scales(), result.wavelet(), alpha, variance, confidence);
+    let power = result.power();
+    Ok(power.chunks(result.num_samples().max(1)).zip(levels).flat_map(|(row, level)| row.iter().map(move |power| *power > level)).collect())
+}
+
+/// Confidence interval of a power with the degrees of freedom, whose true value lies within it with the probability confidence.
+///
+/// The power is χ² distributed with dof degrees of freedom, divided by dof. (Torrence & Compo, 1998, eq. 24)
+/// A complex coefficient has 2 degrees of freedom, and averaging or smoothing adds more, like time_average_dof().
+///
+/// # Arguments
+/// power      - Power of a coefficient, or an average of them
+///
+/// dof        - Degrees of freedom of the power
+///
+/// confidence - Confidence level, like 0.95
+///
+/// Returns the lower and the upper bound.
+pub fn confidence_interval(power : f64, dof : f64, confidence : f64) -> (f64, f64)
+{
+    assert!(dof > 0.0, "Degrees of freedom must be positive.");
+    assert!(confidence > 0.0 && confidence < 1.0, "Confidence must be between 0 and 1.");
+
+    let tail = (1.0 - confidence) / 2.0;
+    (dof * power / chi2_quantile(dof, 1.0 - tail), dof * power / chi2_quantile(dof, tail))
+}
+
+/// Degrees of freedom of the power of each scale averaged over a number of columns. (Torrence & Compo, 1998, eq. 23)
+///
+/// Neighbouring coefficients are correlated over the decorrelation length 2.32 × the standard deviation of the envelope of the wavelet,
+/// which is the factor of the Morlet wavelet with ω₀ = 6 and changes little with the bandwidth.
+///
+/// # Arguments
+/// result     - Result of the transform
+///
+/// columns    - Number of columns averaged
+pub fn time_average_dof(result : &CwtResult, columns : usize) -> Vec<f64>
+{
+    let samples = (columns * result.hop()) as f64;
+    result.scales().iter().map(|(scale, _)|
+    {
+        let decorrelation = 2.32 * result.wavelet().fb * scale;
+        2.0 * (1.0 + (samples / decorrelation).powi(2)).sqrt()
+    }).collect()
+}
+
+/// Confidence interval of the global wavelet spectrum, which averages every column. (global_spectrum(false))
+///
+/// # Arguments
+/// result     - Result of the transform
+///
+/// confidence - Confidence level, like 0.95
+///
+/// Returns the lower and the upper bound of each scale, or FastCwtError::NotNormalized if the transform is not normalized.
+pub fn global_spectrum_interval(result : &CwtResult, confidence : f64) -> Result<Vec<(f64, f64)>, FastCwtError>
+{
+    if !result.is_normalized() { return Err(FastCwtError::NotNormalized); }
+    let dof = time_average_dof(result, result.num_samples());
+    Ok(result.global_spectrum(false).into_iter().zip(dof).map(|(power, dof)| confidence_interval(power, dof, confidence)).collect())
+}
+
+/// Quantile of the χ² distribution with dof degrees of freedom at the probability p.
+///
+/// It is exact for 2 degrees of freedom, which is a single complex coefficient, and the Wilson-Hilferty approximation otherwise.
+fn chi2_quantile(dof : f64, p : f64) -> f64
+{
+    if dof == 2.0 { return - 2.0 * (1.0 - p).ln(); }
+    let h = 2.0 / (9.0 * dof);
+    dof * (1.0 - h + normal_quantile(p) * h.sqrt()).max(0.0).powi(3)
+}
+
+/// Quantile of the standard normal distribution at the probability p, with a relative error below 1.2e-9. (Acklam)
+fn normal_quantile(p : f64) -> f64
+{
+    const A : [f64; 6] = [-3.969683028665376e+01, 2.209460984245205e+02, -2.759285104469687e+02, 1.38357751867269e+02, -3.066479806614716e+01, 2.506628277459239e+00];
+    const B : [f64; 5] = [-5.447609879822406e+01, 1.615858368580409e+02, -1.556989798598866e+02, 6.680131188771972e+01, -1.328068155288572e+01];
+    const C : [f64; 6] = [-7.784894002430293e-03, -3.223964580411365e-01, -2.400758277161838e+00, -2.549732539343734e+00, 4.374664141464968e+00, 2.938163982698783e+00];
+    const D : [f64; 4] = [7.784695709041462e-03, 3.224671290700398e-01, 2.445134137142996e+00, 3.754408661907416e+00];
+    let polynomial = |coefficients : &[f64], x : f64| coefficients.iter().fold(0.0, |sum, coefficient| sum * x + coefficient);
+
+    if p < 0.02425
+    {
+        let q = (- 2.0 * p.ln()).sqrt();
+        polynomial(&C, q) / (polynomial(&D, q) * q + 1.0)
+    }
+    else if p > 1.0 - 0.02425
+    {
+        let q = (- 2.0 * (1.0 - p).ln()).sqrt();
+        - polynomial(&C, q) / (polynomial(&D, q) * q + 1.0)
+    }
+    else
+    {
+        let (q, r) = (p - 0.5, (p - 0.5) * (p - 0.5));
+        polynomial(&A, r) * q / (polynomial(&B, r) * r + 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use rand::prelude::*;
+
+    #[test]
+    fn white_noise_exceeds_level_by_chance()
+    {
+        //Gaussian white noise from the Box-Muller transform.
+        let mut rng = StdRng::seed_from_u64(7);
+        let input : Vec<f64> = (0 .. 16384).map(|_| (-2.0 * (1.0 - rng.gen::<f64>()).ln()).sqrt() * (2.0 * core::f64::consts::PI * rng.gen::<f64>()).cos()).collect();
+        let scales = Scales::create(ScaleTypes::Log, 1000.0, 5.0, 250.0, 24);
+        let mut transform = FastCWT::create(Wavelet::create(1.0), true);
+        let result = transform.cwt(input.len(), &input, &scales);
+
+        let (alpha, variance) = ar1(&input);
+        let mask = significant(&result, alpha, variance, 0.95).unwrap();
+        //Only the coefficients outside the cone of influence, where zero padding lowers the power.
+        let (coi, frequencies) = (result.coi(), result.frequencies());
+        let (above, count) = mask.chunks(result.num_samples()).zip(frequencies).flat_map(|(row, frequency)| row.iter().zip(&coi).filter(move |(_, coi)| frequency >= **coi)).fold((0, 0), |(above, count), (significant, _)| (above + *significant as usize, count + 1));
+        let fraction = above as f64 / count as f64;
+        assert!((fraction - 0.05).abs() < 0.01, "{} of the coefficients are above the 0.95 level.", fraction);
+
+        let unnormalized = FastCWT::create(Wavelet::create(1.0), false).cwt(input.len(), &input, &scales);
+        assert!(matches!(significant(&unnormalized, alpha, variance, 0.95), Err(FastCwtError::NotNormalized)));
+    }
+}
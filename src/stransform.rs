@@ -0,0 +1,81 @@
+//! Stockwell transform. (S-transform)
+//!
+//! The S-transform windows the spectrum with a Gaussian whose width is proportional to the frequency, like a Morlet wavelet
+//! with bandwidth 1 (ω₀ = 2π), but keeps the phase referenced to the first sample instead of to each point in time.
+//! It runs on the same padded spectrum and FFT backend as the transform itself.
+use crate::*;
+
+/// Result of the S-transform.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, Debug)]
+pub struct Stockwell
+{
+    coefficients : Vec<Complex<f64>>,
+    frequencies : Vec<f64>,
+    num_samples : usize
+}
+impl Stockwell
+{
+    /// Complex coefficients, frequency by frequency. (frequencies × samples, row-major)
+    ///
+    /// A cosine of amplitude A at one of the frequencies gives coefficients of magnitude A / 2.
+    pub fn coefficients(& self) -> &[Complex<f64>] { &self.coefficients }
+    /// Frequency of every row, in the order of the scales.
+    pub fn frequencies(& self) -> &[f64] { &self.frequencies }
+    /// Number of samples in each row.
+    pub fn num_samples(& self) -> usize { self.num_samples }
+    /// Coefficients of a frequency.
+    pub fn row(& self, idx : usize) -> &[Complex<f64>]
+    {
+        assert!(idx < self.frequencies.len(), "Index must be less than the number of frequencies.");
+        &self.coefficients[idx * self.num_samples .. (idx + 1) * self.num_samples]
+    }
+}
+
+impl FastCWT
+{
+    /// S-transform of the input at the center frequencies of the scales, with the padding, FFT backend and threads of the transform.
+    ///
+    /// # Arguments
+    /// input     - Input data in vector format
+    ///
+    /// scales    - Scales object, which defines the frequencies
+    ///
+    /// The wavelet, output mode, hop and precision of the transform do not apply.
+    pub fn stransform(&mut self, input : &[f64], scales : &Scales) -> Stockwell
+    {
+        let size = input.len();
+        let frequencies = scales.frequencies();
+        let mut output = vec![Complex::new(0.0, 0.0); scales.num_scales * size];
+        if size == 0 || scales.num_scales == 0 { return Stockwell { coefficients : output, frequencies, num_samples : size }; }
+
+        let spectrum = self.forward(input);
+        let (newsize, offset) = (spectrum.bins.len(), spectrum.offset);
+        let inverse = self.fft.plan_f64(newsize, true);
+        let fs = scales.fs;
+
+        self.run(|| for_each_row(self.sequential, &mut output, size, || (vec![], vec![]), |(buffer, scratch), i, row|
+        {
+            if self.is_cancelled() { return; }
+            let frequency = frequencies[i];
+
+            //Gaussian window of the voice, exp(-2 pi^2 (nu - f)^2 / f^2), over the signed frequency of every bin.
+            buffer.clear();
+            buffer.extend(spectrum.bins.iter().enumerate().map(|(k, bin)|
+            {
+                let k = if k <= newsize / 2 { k as f64 } else { k as f64 - newsize as f64 };
+                let tmp = 2.0 * core::f64::consts::PI * (k * fs / newsize as f64 - frequency) / frequency;
+                bin * (- tmp * tmp / 2.0).exp()
+            }));
+            no_denormals(|| inverse.process(buffer, scratch));
+
+            //Demodulation references the phase to the first input sample, and the FFT size is divided out so the spectrum is normalized.
+            for (n, (out, data)) in row.iter_mut().zip(&buffer[offset .. offset + size]).enumerate()
+            {
+                *out = data * Complex::from_polar(1.0 / newsize as f64, - 2.0 * core::f64::consts::PI * frequency * n as f64 / fs);
+            }
+        }));
+
+        Stockwell { coefficients : output, frequencies, num_samples : size }
+    }
+}
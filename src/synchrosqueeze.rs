@@ -0,0 +1,115 @@
+//! Synchrosqueezed continuous wavelet transform.
+//!
+//! Every coefficient is moved from its scale to the instantaneous frequency of the signal at that point,
+//! which is estimated from the derivative of the coefficients in time.
+//! The derivative is computed with the same FFT-based convolution as the transform itself.
+use crate::*;
+
+/// Result of the synchrosqueezing transform.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, Debug)]
+pub struct Synchrosqueezed
+{
+    coefficients : Vec<Complex<f64>>,
+    frequencies : Vec<f64>,
+    num_samples : usize
+}
+impl Synchrosqueezed
+{
+    /// Reassigned coefficients, frequency by frequency. (frequencies × samples, row-major)
+    ///
+    /// 2 × real part of the sum over frequencies approximately reconstructs the signal.
+    pub fn coefficients(& self) -> &[Complex<f64>] { &self.coefficients }
+    /// Frequency grid of the rows in ascending order, which is the center frequencies of the scales.
+    pub fn frequencies(& self) -> &[f64] { &self.frequencies }
+    /// Number of samples in each row.
+    pub fn num_samples(& self) -> usize { self.num_samples }
+}
+
+impl FastCWT
+{
+    /// Synchrosqueezing transform on top of the continuous wavelet transform.
+    ///
+    /// # Arguments
+    /// input     - Input data in vector format
+    ///
+    /// scales    - Scales object, which also defines the frequency grid
+    ///
+    /// gamma     - Coefficients with magnitude below this are not reassigned, because their frequency estimate is unstable
+    pub fn synchrosqueeze(&mut self, input : &[f64], scales : &Scales, gamma : f64) -> Synchrosqueezed
+    {
+        let size = input.len();
+        let num_scales = scales.num_scales;
+        let fs = scales.fs;
+
+        let mut frequencies : Vec<f64> = scales.scales.iter().map(|scale| fs / scale).collect();
+        frequencies.sort_by(|a, b| a.total_cmp(b));
+        let mut output = vec![Complex::new(0.0, 0.0); num_scales * size];
+
+        if size == 0 || num_scales == 0 { return Synchrosqueezed { coefficients : output, frequencies, num_samples : size }; }
+
+        //Coefficients and their time derivative, which is the same convolution with the spectrum multiplied by i * omega.
+        let mut spectrum = self.forward(input);
+        //Derivative is only in the frequency domain, so the coefficients use the FFT as well for every scale.
+        spectrum.signal = None;
+        let derivative = derivative(&spectrum);
+
+        let mut coefficients = vec![Complex::new(0.0, 0.0); num_scales * size];
+        let mut derivatives = vec![Complex::new(0.0, 0.0); num_scales * size];
+        self.convolve(&spectrum, scales, &mut coefficients);
+        self.convolve(&derivative, scales, &mut derivatives);
+
+        //Coefficients are summed with d(ln scale) weights, so that the rows keep the amplitude of the signal.
+        let factor = self.wavelet.reconstruction_factor();
+        let weights : Vec<f64> = scales.log_steps().iter().zip(scales.iter()).map(|(step, (scale, _))| step / (factor * self.wavelet.gain(scale))).collect();
+
+        let lowest = frequencies[0] - if num_scales > 1 { (frequencies[1] - frequencies[0]) / 2.0 } else { 0.0 };
+        let highest = frequencies[num_scales - 1] + if num_scales > 1 { (frequencies[num_scales - 1] - frequencies[num_scales - 2]) / 2.0 } else { 0.0 };
+
+        for i in 0 .. num_scales
+        {
+            let row = &coefficients[i * size .. (i + 1) * size];
+            let drow = &derivatives[i * size .. (i + 1) * size];
+
+            for (n, (coefficient, derivative)) in row.iter().zip(drow).enumerate()
+            {
+                if coefficient.norm() <= gamma { continue; }
+
+                //Instantaneous frequency in Hz.
+                let frequency = (derivative / coefficient).im / (2.0 * core::f64::consts::PI) * fs;
+                if !(frequency.is_finite() && frequency >= lowest && frequency <= highest) { continue; }
+
+                output[nearest(&frequencies, frequency) * size + n] += coefficient * weights[i];
+            }
+        }
+
+        Synchrosqueezed { coefficients : output, frequencies, num_samples : size }
+    }
+}
+
+/// Spectrum of the time derivative, which is the spectrum multiplied by i * omega.
+pub(crate) fn derivative(spectrum : &Spectrum) -> Spectrum
+{
+    let newsize = spectrum.bins.len();
+    Spectrum
+    {
+        bins : spectrum.bins.iter().enumerate().map(|(k, bin)|
+        {
+            let k = if k <= newsize / 2 { k as f64 } else { k as f64 - newsize as f64 };
+            bin * Complex::new(0.0, 2.0 * core::f64::consts::PI * k / newsize as f64)
+        }).collect(),
+        offset : spectrum.offset,
+        size : spectrum.size,
+        signal : None,
+        gaps : spectrum.gaps.clone()
+    }
+}
+
+/// Index of the nearest value in an ascending grid.
+pub(crate) fn nearest(grid : &[f64], value : f64) -> usize
+{
+    let upper = grid.partition_point(|frequency| *frequency < value);
+    if upper == 0 { return 0; }
+    if upper == grid.len() { return grid.len() - 1; }
+    if value - grid[upper - 1] < grid[upper] - value { upper - 1 } else { upper }
+}
@@ -0,0 +1,123 @@
+use crate::*;
+#[cfg(feature = "std")]
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// Time and memory spent in the transforms since the statistics were enabled or reset, reported by FastCWT::stats().
+///
+/// Per-scale phases are summed over the threads, so with several threads they can add up to more than the convolution time.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct TransformStats
+{
+    /// Number of forward transforms of an input.
+    pub transforms : usize,
+    /// Number of scales convolved.
+    pub scales : usize,
+    /// Wall time of the forward FFTs of the padded inputs.
+    pub forward_fft : core::time::Duration,
+    /// Wall time of the per-scale convolutions, from the first scale to the last.
+    pub convolution : core::time::Duration,
+    /// Time of sampling and multiplying the daughter wavelets, or of the direct convolution of small scales.
+    pub multiply : core::time::Duration,
+    /// Time of the inverse FFTs.
+    pub inverse_fft : core::time::Duration,
+    /// Time of normalizing the rows and converting them into the output.
+    pub normalization : core::time::Duration,
+    /// Bytes allocated for the FFT buffers, the spectrum and the daughter wavelets, without the input and the output.
+    pub allocated_bytes : usize
+}
+
+/// Phase of the transform which is timed.
+#[derive(Clone, Copy)]
+pub(crate) enum Phase
+{
+    Forward,
+    Convolution,
+    Multiply,
+    Inverse,
+    Normalization
+}
+
+/// Counters of TransformStats, shared by the threads and the clones of a transform.
+#[cfg(feature = "std")]
+#[derive(Default, Debug)]
+pub(crate) struct Counters
+{
+    transforms : AtomicUsize,
+    scales : AtomicUsize,
+    nanoseconds : [AtomicU64; 5],
+    allocated : AtomicUsize
+}
+#[cfg(feature = "std")]
+impl Counters
+{
+    fn snapshot(& self) -> TransformStats
+    {
+        let time = |phase : Phase| core::time::Duration::from_nanos(self.nanoseconds[phase as usize].load(Ordering::Relaxed));
+        TransformStats
+        {
+            transforms : self.transforms.load(Ordering::Relaxed),
+            scales : self.scales.load(Ordering::Relaxed),
+            forward_fft : time(Phase::Forward),
+            convolution : time(Phase::Convolution),
+            multiply : time(Phase::Multiply),
+            inverse_fft : time(Phase::Inverse),
+            normalization : time(Phase::Normalization),
+            allocated_bytes : self.allocated.load(Ordering::Relaxed)
+        }
+    }
+    fn reset(& self)
+    {
+        self.transforms.store(0, Ordering::Relaxed);
+        self.scales.store(0, Ordering::Relaxed);
+        for counter in &self.nanoseconds { counter.store(0, Ordering::Relaxed); }
+        self.allocated.store(0, Ordering::Relaxed);
+    }
+}
+
+impl FastCWT
+{
+    /// Collect the time and memory of every phase of the transforms, which costs two clock readings per phase and scale.
+    ///
+    /// Enabling starts from zero, and disabling drops the statistics.
+    ///
+    /// enabled             - Collect statistics if true
+    #[cfg(feature = "std")]
+    pub fn set_stats(&mut self, enabled : bool) { self.stats = enabled.then(Default::default); }
+    /// Statistics since set_stats(true) or reset_stats(), or None if they are not collected.
+    #[cfg(feature = "std")]
+    pub fn stats(& self) -> Option<TransformStats> { self.stats.as_ref().map(|counters| counters.snapshot()) }
+    /// Sets the statistics back to zero.
+    #[cfg(feature = "std")]
+    pub fn reset_stats(& self)
+    {
+        if let Some(counters) = &self.stats { counters.reset(); }
+    }
+    /// Runs f and adds its wall time to the phase, if statistics are collected.
+    pub(crate) fn timed<R>(& self, phase : Phase, f : impl FnOnce() -> R) -> R
+    {
+        #[cfg(feature = "std")]
+        if let Some(counters) = &self.stats
+        {
+            let start = std::time::Instant::now();
+            let result = f();
+            counters.nanoseconds[phase as usize].fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+            if let Phase::Forward = phase { counters.transforms.fetch_add(1, Ordering::Relaxed); }
+            return result;
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = phase;
+        f()
+    }
+    /// Counts finished scales and allocated bytes, if statistics are collected.
+    pub(crate) fn count(& self, scales : usize, bytes : usize)
+    {
+        #[cfg(feature = "std")]
+        if let Some(counters) = &self.stats
+        {
+            counters.scales.fetch_add(scales, Ordering::Relaxed);
+            counters.allocated.fetch_add(bytes, Ordering::Relaxed);
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = (scales, bytes);
+    }
+}
@@ -0,0 +1,73 @@
+//! Detection of transients like clicks and pops, which are broadband vertical structures in the scalogram.
+//!
+//! The power of every scale is compared with its median over time, which is the background of the scale as long as
+//! transients are rare. A column is part of a transient if the power rises above the background in most of the scales at once,
+//! weighted by their width in log-scale. Tones and noise raise a few scales and long events are dropped,
+//! while the small scales keep the transients as short as the click itself.
+use crate::*;
+
+/// Transient found in the scalogram.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Transient
+{
+    /// Sample position of the first column of the transient.
+    pub start : usize,
+    /// Length of the transient in samples, which is a multiple of the hop.
+    pub duration : usize,
+    /// Highest fraction of the scales above the threshold during the transient, from 0 to 1.
+    pub strength : f64
+}
+
+/// Transients of the result, in the order of their positions, except at the first and the last column.
+///
+/// # Arguments
+/// result        - Result of the transform
+///
+/// threshold     - Rise in dB of the power of a scale above its median, like 10.0
+///
+/// fraction      - Fraction of the scales which must rise at once, like 0.6
+///
+/// max_duration  - Longest transient in seconds, like 0.005 for clicks, above which broadband events are not transients
+pub fn detect(result : &CwtResult, threshold : f64, fraction : f64, max_duration : f64) -> Vec<Transient>
+{
+    assert!((0.0 ..= 1.0).contains(&fraction), "Fraction must be between 0 and 1.");
+
+    let num_samples = result.num_samples();
+    let factor = 10.0_f64.powf(threshold / 10.0);
+    let steps = result.scales().log_steps();
+    let total : f64 = steps.iter().sum();
+    let mut broadness = vec![0.0; num_samples];
+    for (row, step) in result.power().chunks(num_samples.max(1)).zip(&steps)
+    {
+        let mut sorted : Vec<f64> = row.iter().copied().filter(|value| !value.is_nan()).collect();
+        if sorted.is_empty() { continue; }
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let median = sorted[sorted.len() / 2];
+        for (broadness, power) in broadness.iter_mut().zip(row)
+        {
+            if *power > factor * median { *broadness += step / total; }
+        }
+    }
+
+    let longest = (max_duration * result.fs() / result.hop() as f64).ceil() as usize;
+    let mut transients = vec![];
+    let mut column = 0;
+    while column < num_samples
+    {
+        if broadness[column] < fraction
+        {
+            column += 1;
+            continue;
+        }
+        let start = column;
+        let mut strength : f64 = 0.0;
+        while column < num_samples && broadness[column] >= fraction
+        {
+            strength = strength.max(broadness[column]);
+            column += 1;
+        }
+        //Edges of the input are steps to the transform, so a transient touching them is left out.
+        if start > 0 && column < num_samples && column - start <= longest.max(1) { transients.push(Transient { start : start * result.hop(), duration : (column - start) * result.hop(), strength }); }
+    }
+    transients
+}
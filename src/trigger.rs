@@ -0,0 +1,109 @@
+//! STA/LTA event trigger on the wavelet power of frequency bands, like the classic trigger of seismic networks.
+//!
+//! The short-term average (STA) of the band power follows an event, while the long-term average (LTA) follows the noise,
+//! and an event is picked when their ratio rises above a threshold. Both averages trail the column, so the picks are causal
+//! in the averages, though the wavelet power itself spreads over the support of the scales.
+use crate::*;
+
+/// Frequency band and settings of a trigger.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Band
+{
+    /// Lowest frequency of the band in Hz.
+    pub f_low : f64,
+    /// Highest frequency of the band in Hz.
+    pub f_high : f64,
+    /// Length of the short-term average in seconds, like 1.0.
+    pub sta : f64,
+    /// Length of the long-term average in seconds, like 30.0.
+    pub lta : f64,
+    /// STA/LTA ratio which turns the trigger on, like 4.0.
+    pub on : f64,
+    /// STA/LTA ratio which turns the trigger off again, like 1.5.
+    pub off : f64
+}
+
+/// Event picked in a band.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Trigger
+{
+    /// Index of the band.
+    pub band : usize,
+    /// Time in seconds when the ratio rose above the on threshold.
+    pub on : f64,
+    /// Time in seconds when the ratio fell below the off threshold, or the time of the last column if it did not.
+    pub off : f64,
+    /// Highest STA/LTA ratio during the event.
+    pub peak : f64
+}
+
+/// STA/LTA ratio of the power of a band at every column, which is 0 until the long-term average is full.
+///
+/// # Arguments
+/// result    - Result of the transform
+///
+/// f_low     - Lowest frequency of the band in Hz
+///
+/// f_high    - Highest frequency of the band in Hz
+///
+/// sta       - Length of the short-term average in seconds
+///
+/// lta       - Length of the long-term average in seconds, which must be longer
+pub fn sta_lta(result : &CwtResult, f_low : f64, f_high : f64, sta : f64, lta : f64) -> Vec<f64>
+{
+    assert!(sta > 0.0 && lta > sta, "Long-term average must be longer than the short-term average, which must be positive.");
+
+    let columns = |seconds : f64| ((seconds * result.fs() / result.hop() as f64).round() as usize).max(1);
+    let (short, long) = (columns(sta), columns(lta));
+    //Ratio does not depend on the normalization, so any transform will do.
+    let power = result.band(f_low, f_high);
+    let mut sums = vec![0.0; power.len() + 1];
+    for (n, value) in power.iter().enumerate() { sums[n + 1] = sums[n] + if value.is_nan() { 0.0 } else { *value }; }
+
+    (0 .. power.len()).map(|n|
+    {
+        if n + 1 < long { return 0.0; }
+        let short_average = (sums[n + 1] - sums[n + 1 - short]) / short as f64;
+        let long_average = (sums[n + 1] - sums[n + 1 - long]) / long as f64;
+        if long_average > 0.0 { short_average / long_average } else { 0.0 }
+    }).collect()
+}
+
+/// Events of every band, in the order of the bands and of their times.
+///
+/// # Arguments
+/// result    - Result of the transform
+///
+/// bands     - Bands and settings of the triggers
+pub fn triggers(result : &CwtResult, bands : &[Band]) -> Vec<Trigger>
+{
+    let mut triggers = vec![];
+    for (index, band) in bands.iter().enumerate()
+    {
+        assert!(band.on > band.off, "On threshold must be higher than the off threshold.");
+
+        let ratio = sta_lta(result, band.f_low, band.f_high, band.sta, band.lta);
+        let mut event : Option<Trigger> = None;
+        for (column, value) in ratio.iter().enumerate()
+        {
+            match event.as_mut()
+            {
+                None if *value > band.on => event = Some(Trigger { band : index, on : result.time(column), off : result.time(column), peak : *value }),
+                Some(trigger) if *value < band.off =>
+                {
+                    trigger.off = result.time(column);
+                    triggers.extend(event.take());
+                }
+                Some(trigger) => trigger.peak = trigger.peak.max(*value),
+                None => {}
+            }
+        }
+        if let Some(mut trigger) = event
+        {
+            trigger.off = result.time(ratio.len().saturating_sub(1));
+            triggers.push(trigger);
+        }
+    }
+    triggers
+}
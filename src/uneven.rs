@@ -0,0 +1,136 @@
+//! Weighted wavelet Z-transform for unevenly sampled time series. (Foster, 1996)
+//!
+//! At every time and frequency, a constant, a cosine and a sine are fitted to the samples by weighted least squares,
+//! with the Gaussian envelope of the Morlet wavelet of the scale as the weights. The fit only uses the samples that exist,
+//! so gaps and irregular timestamps need no resampling. Foster's decay constant c = 0.0125 is a wavelet bandwidth of about 1.
+//! It takes O(num_samples) operations per coefficient, so it is meant for series of up to some thousand samples.
+use crate::*;
+
+/// Result of the weighted wavelet Z-transform. (num_scales × num_times, row-major)
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, Debug)]
+pub struct WeightedWavelet
+{
+    coefficients : Vec<Complex<f64>>,
+    z : Vec<f64>,
+    effective : Vec<f64>,
+    frequencies : Vec<f64>,
+    times : Vec<f64>
+}
+impl WeightedWavelet
+{
+    /// Complex amplitude of the fitted sinusoid, whose magnitude is its amplitude and argument its phase at the time. (WWA)
+    pub fn coefficients(& self) -> &[Complex<f64>] { &self.coefficients }
+    /// Amplitude of the fitted sinusoid. (WWA)
+    pub fn amplitude(& self) -> Vec<f64> { self.coefficients.iter().map(|value| value.norm()).collect() }
+    /// Weighted wavelet Z-statistic, which follows an F-distribution with 2 and N_eff - 3 degrees of freedom for white noise. (WWZ)
+    pub fn z(& self) -> &[f64] { &self.z }
+    /// Effective number of samples under the weights, which is (Σw)² / Σw². (N_eff)
+    pub fn effective_samples(& self) -> &[f64] { &self.effective }
+    /// Frequency of every row, in the order of the scales.
+    pub fn frequencies(& self) -> &[f64] { &self.frequencies }
+    /// Time of every column, in the unit of the timestamps.
+    pub fn times(& self) -> &[f64] { &self.times }
+    /// Number of times in each row.
+    pub fn num_times(& self) -> usize { self.times.len() }
+}
+
+/// Weighted wavelet Z-transform of the samples at the times, on the frequencies of the scales.
+///
+/// Coefficients without enough samples under the weights to fit the sinusoid (N_eff ≤ 3) are NaN.
+///
+/// # Arguments
+/// timestamps - Time of every sample in seconds, in any order
+///
+/// values    - Value of every sample
+///
+/// times     - Times to compute the coefficients at, e.g. an even grid over the timestamps
+///
+/// scales    - Scales object, where the frequencies are in Hz and the sample frequency is not used
+///
+/// wavelet   - Wavelet, whose bandwidth sets the width of the weights
+pub fn wwz(timestamps : &[f64], values : &[f64], times : &[f64], scales : &Scales, wavelet : &Wavelet) -> WeightedWavelet
+{
+    assert!(timestamps.len() == values.len(), "Timestamps and values must have the same length.");
+
+    let frequencies = scales.frequencies();
+    let size = times.len();
+    let mut output = vec![(Complex::new(0.0, 0.0), 0.0, 0.0); scales.num_scales * size];
+    if size > 0
+    {
+        for_each_row(false, &mut output, size, || (), |_, i, row|
+        {
+            for (out, tau) in row.iter_mut().zip(times) { *out = project(timestamps, values, *tau, frequencies[i], wavelet.fb); }
+        });
+    }
+
+    let coefficients = output.iter().map(|(coefficient, _, _)| *coefficient).collect();
+    let z = output.iter().map(|(_, z, _)| *z).collect();
+    let effective = output.iter().map(|(_, _, effective)| *effective).collect();
+    WeightedWavelet { coefficients, z, effective, frequencies, times : times.to_vec() }
+}
+
+/// Fits a constant, a cosine and a sine of the frequency around tau, and returns the complex amplitude, the Z-statistic and N_eff.
+fn project(timestamps : &[f64], values : &[f64], tau : f64, frequency : f64, fb : f64) -> (Complex<f64>, f64, f64)
+{
+    let omega = 2.0 * core::f64::consts::PI * frequency;
+    //Envelope of the daughter wavelet has a standard deviation of fb periods, and is cut at 5 of them like the margin of the transform.
+    let (sigma, reach) = (fb / frequency, 5.0 * fb / frequency);
+
+    let (mut weights, mut squares) = (0.0, 0.0);
+    let (mut gram, mut moments, mut energy) = ([[0.0; 3]; 3], [0.0; 3], 0.0);
+    for (time, value) in timestamps.iter().zip(values)
+    {
+        let dt = time - tau;
+        if dt.abs() > reach || value.is_nan() { continue; }
+
+        let weight = (- (dt / sigma).powi(2) / 2.0).exp();
+        let basis = [1.0, (omega * dt).cos(), (omega * dt).sin()];
+        for (row, a) in gram.iter_mut().zip(basis) { for (cell, b) in row.iter_mut().zip(basis) { *cell += weight * a * b; } }
+        for (moment, a) in moments.iter_mut().zip(basis) { *moment += weight * a * value; }
+        energy += weight * value * value;
+        weights += weight;
+        squares += weight * weight;
+    }
+
+    let effective = if squares > 0.0 { weights * weights / squares } else { 0.0 };
+    let fit = if effective > 3.0 { solve(gram, moments) } else { None };
+    let Some(fit) = fit else { return (Complex::new(f64::NAN, f64::NAN), f64::NAN, effective); };
+
+    //Weighted variances of the samples and of the model, where the model projected on the samples is fit · moments.
+    let mean = moments[0] / weights;
+    let variance = energy / weights - mean * mean;
+    let model = fit.iter().zip(moments).map(|(y, moment)| y * moment).sum::<f64>() / weights - mean * mean;
+    let z = (effective - 3.0) * model / (2.0 * (variance - model).max(0.0));
+
+    //y1 cos + y2 sin is the real part of (y1 - i y2) e^(i omega dt).
+    (Complex::new(fit[1], - fit[2]), z, effective)
+}
+
+/// Solves the 3 × 3 system by Gaussian elimination with partial pivoting, or None if it is singular.
+fn solve(mut matrix : [[f64; 3]; 3], mut vector : [f64; 3]) -> Option<[f64; 3]>
+{
+    let scale = matrix.iter().flatten().fold(0.0_f64, |largest, value| largest.max(value.abs()));
+    for column in 0 .. 3
+    {
+        let pivot = (column .. 3).max_by(|a, b| matrix[*a][column].abs().total_cmp(&matrix[*b][column].abs()))?;
+        if matrix[pivot][column].abs() <= 1e-12 * scale { return None; }
+        matrix.swap(column, pivot);
+        vector.swap(column, pivot);
+        for row in column + 1 .. 3
+        {
+            let factor = matrix[row][column] / matrix[column][column];
+            let pivot = matrix[column];
+            for (cell, value) in matrix[row].iter_mut().zip(pivot).skip(column) { *cell -= factor * value; }
+            vector[row] -= factor * vector[column];
+        }
+    }
+
+    let mut solution = [0.0; 3];
+    for row in (0 .. 3).rev()
+    {
+        let rest = (row + 1 .. 3).map(|k| matrix[row][k] * solution[k]).sum::<f64>();
+        solution[row] = (vector[row] - rest) / matrix[row][row];
+    }
+    Some(solution)
+}
@@ -0,0 +1,102 @@
+//! WebAssembly bindings for JavaScript.
+//!
+//! ```js
+//! const config = new CwtConfig(48000, 20, 20000, 200);
+//! config.set_output_mode("magnitude");
+//! const scalogram = compute_cwt(samples, config); //Float64Array, num_scales × samples
+//! ```
+use crate::*;
+use wasm_bindgen::prelude::*;
+
+/// Configuration of compute_cwt().
+#[wasm_bindgen]
+pub struct CwtConfig
+{
+    fs : f64,
+    f0 : f64,
+    f1 : f64,
+    num_scales : usize,
+    bandwidth : f64,
+    scale_type : ScaleTypes,
+    output_mode : OutputMode,
+    hop : usize
+}
+#[wasm_bindgen]
+impl CwtConfig
+{
+    /// Linear frequency scales and magnitude output with a Morlet wavelet of bandwidth 1.0.
+    ///
+    /// # Arguments
+    /// fs         - Sample rate
+    ///
+    /// f0         - Beginning frequency
+    ///
+    /// f1         - End frequency
+    ///
+    /// num_scales - Number of scales
+    #[wasm_bindgen(constructor)]
+    pub fn new(fs : f64, f0 : f64, f1 : f64, num_scales : usize) -> CwtConfig
+    {
+        CwtConfig { fs, f0, f1, num_scales, bandwidth : 1.0, scale_type : ScaleTypes::LinFreq, output_mode : OutputMode::Magnitude, hop : 1 }
+    }
+    /// Bandwidth of the Morlet wavelet.
+    pub fn set_bandwidth(&mut self, bandwidth : f64) { self.bandwidth = bandwidth; }
+    /// "linear" | "log" | "linfreq" | "mel" | "erb"
+    pub fn set_scale_type(&mut self, scale_type : &str) -> Result<(), JsError>
+    {
+        self.scale_type = match scale_type
+        {
+            "linear" => ScaleTypes::Linear,
+            "log" => ScaleTypes::Log,
+            "linfreq" => ScaleTypes::LinFreq,
+            "mel" => ScaleTypes::Mel,
+            "erb" => ScaleTypes::ERB,
+            _ => return Err(JsError::new("Scale type must be linear, log, linfreq, mel or erb."))
+        };
+        Ok(())
+    }
+    /// "complex" | "magnitude" | "power" | "logpower". Complex output interleaves the real and the imaginary parts.
+    pub fn set_output_mode(&mut self, output_mode : &str) -> Result<(), JsError>
+    {
+        self.output_mode = match output_mode
+        {
+            "complex" => OutputMode::Complex,
+            "magnitude" => OutputMode::Magnitude,
+            "power" => OutputMode::Power,
+            "logpower" => OutputMode::LogPower,
+            _ => return Err(JsError::new("Output mode must be complex, magnitude, power or logpower."))
+        };
+        Ok(())
+    }
+    /// Number of input samples per output sample, which keeps the maximum of each hop.
+    pub fn set_hop(&mut self, hop : usize) -> Result<(), JsError>
+    {
+        if hop == 0 { return Err(JsError::new("Hop size must be at least 1.")); }
+        self.hop = hop;
+        Ok(())
+    }
+}
+
+/// Transforms the input and returns the coefficients scale by scale. (num_scales × samples / hop, row-major)
+///
+/// Throws if the frequency range of the configuration is invalid, e.g. above the Nyquist frequency.
+///
+/// # Arguments
+/// input     - Input data
+///
+/// config    - Configuration of the transform
+#[wasm_bindgen]
+pub fn compute_cwt(input : &[f64], config : &CwtConfig) -> Result<Vec<f64>, JsError>
+{
+    let scales = Scales::try_create(config.scale_type, config.fs, config.f0, config.f1, config.num_scales).map_err(|error| JsError::new(&error.to_string()))?;
+    let mut transform = FastCWT::create(Wavelet::create(config.bandwidth), true);
+    transform.set_output_mode(config.output_mode);
+    transform.set_hop(config.hop, Pooling::Max);
+
+    let result = transform.cwt(input.len(), input, &scales);
+    Ok(match result.complex_coefficients()
+    {
+        Some(coefficients) => coefficients.iter().flat_map(|value| [value.re, value.im]).collect(),
+        None => result.real_coefficients().unwrap_or_default().to_vec()
+    })
+}